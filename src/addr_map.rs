@@ -0,0 +1,115 @@
+/// Maps offsets in the data to the addresses they were loaded from.
+///
+/// Used for sparse sources, where the data is made up of multiple segments that are
+/// laid out one after another, with the gaps between them skipped.
+///
+/// Offsets are deliberately not equal to the real addresses. A process address space
+/// spans up to 2^47 bytes, and the data has to be one contiguous buffer. Instead, real
+/// addresses are shown next to offsets, and entered addresses are translated back with
+/// [`AddrMap::addr_to_offset`].
+#[derive(Debug, Default, Clone)]
+pub struct AddrMap {
+    /// Segments, in order of their offset in the data
+    pub segments: Vec<Segment>,
+}
+
+/// A contiguous piece of data that was loaded from an address
+#[derive(Debug, Clone)]
+pub struct Segment {
+    pub name: String,
+    /// Address the segment starts at
+    pub addr: usize,
+    /// Offset of the segment in the data
+    pub offset: usize,
+    pub len: usize,
+}
+
+impl Segment {
+    fn contains_offset(&self, offset: usize) -> bool {
+        offset >= self.offset && offset < self.offset + self.len
+    }
+    fn contains_addr(&self, addr: usize) -> bool {
+        addr >= self.addr && addr < self.addr + self.len
+    }
+}
+
+impl AddrMap {
+    /// Add a segment after the last one, and return its offset in the data
+    pub fn push(&mut self, name: String, addr: usize, len: usize) -> usize {
+        let offset = self.segments.last().map_or(0, |seg| seg.offset + seg.len);
+        self.segments.push(Segment {
+            name,
+            addr,
+            offset,
+            len,
+        });
+        offset
+    }
+    pub fn segment_idx_at_offset(&self, offset: usize) -> Option<usize> {
+        let idx = self.segments.partition_point(|seg| seg.offset <= offset);
+        let idx = idx.checked_sub(1)?;
+        self.segments[idx].contains_offset(offset).then_some(idx)
+    }
+    pub fn segment_at_offset(&self, offset: usize) -> Option<&Segment> {
+        self.segment_idx_at_offset(offset)
+            .map(|idx| &self.segments[idx])
+    }
+    pub fn offset_to_addr(&self, offset: usize) -> Option<usize> {
+        self.segment_at_offset(offset)
+            .map(|seg| seg.addr + (offset - seg.offset))
+    }
+    pub fn addr_to_offset(&self, addr: usize) -> Option<usize> {
+        self.segments
+            .iter()
+            .find(|seg| seg.contains_addr(addr))
+            .map(|seg| seg.offset + (addr - seg.addr))
+    }
+    /// Returns the pieces of the inclusive offset range `begin..=end`, split at
+    /// segment boundaries, as `(segment, offset range)` pairs.
+    pub fn split_range(
+        &self,
+        begin: usize,
+        end: usize,
+    ) -> impl Iterator<Item = (&Segment, std::ops::Range<usize>)> {
+        self.segments.iter().filter_map(move |seg| {
+            let start = begin.max(seg.offset);
+            let end = (end + 1).min(seg.offset + seg.len);
+            (start < end).then_some((seg, start..end))
+        })
+    }
+}
+
+#[cfg(test)]
+fn test_map() -> AddrMap {
+    let mut map = AddrMap::default();
+    map.push("a".into(), 0x1000, 0x100);
+    map.push("b".into(), 0x5000, 0x10);
+    map.push("c".into(), 0x5010, 0x20);
+    map
+}
+
+#[test]
+fn test_translate() {
+    let map = test_map();
+    assert_eq!(map.offset_to_addr(0), Some(0x1000));
+    assert_eq!(map.offset_to_addr(0xFF), Some(0x10FF));
+    assert_eq!(map.offset_to_addr(0x100), Some(0x5000));
+    assert_eq!(map.offset_to_addr(0x115), Some(0x5015));
+    assert_eq!(map.offset_to_addr(0x130), None);
+    assert_eq!(map.addr_to_offset(0x1080), Some(0x80));
+    assert_eq!(map.addr_to_offset(0x5011), Some(0x111));
+    assert_eq!(map.addr_to_offset(0x2000), None);
+}
+
+#[test]
+fn test_split_range() {
+    let map = test_map();
+    let pieces: Vec<_> = map
+        .split_range(0xF0, 0x112)
+        .map(|(seg, range)| (seg.name.as_str(), range))
+        .collect();
+    assert_eq!(
+        pieces,
+        [("a", 0xF0..0x100), ("b", 0x100..0x110), ("c", 0x110..0x113)]
+    );
+}
@@ -3,28 +3,49 @@ use {
     rlua::Lua,
 };
 
+pub mod compare;
+pub mod document;
 pub mod edit_state;
 pub mod interact_mode;
 pub mod presentation;
 
 use {
-    self::edit_state::EditState,
+    self::{
+        compare::{Compare, RegionCompareViews},
+        document::Document,
+        edit_state::EditState,
+    },
     crate::{
+        addr_map::AddrMap,
         args::{Args, SourceArgs},
+        binary_diff, block_dev,
+        change_history::ChangeRecorder,
+        compression::{Algorithm, Compression},
         config::Config,
+        damage_region::DamageRegion,
+        data::{Data, MMAP_THRESHOLD},
+        elf_core::CoreDump,
         gui::Gui,
+        heatmap::Heatmap,
         hex_ui::HexUi,
+        ihex_srec::{self, RecordFormat},
         input::Input,
         layout::{default_margin, do_auto_layout, Layout},
         meta::{
-            perspective::Perspective, region::Region, LayoutKey, Meta, NamedRegion, NamedView,
-            PerspectiveKey, PerspectiveMap, RegionMap, ViewKey,
+            perspective::Perspective, region::Region, LayoutKey, Meta, ModuleAnchor, NamedRegion,
+            NamedView, PerspectiveKey, PerspectiveMap, RegionKey, RegionMap, ViewKey,
         },
         meta_state::MetaState,
+        patch::{self, PatchFormat},
         preferences::Preferences,
+        proc_mem,
         shell::msg_if_fail,
-        source::{Source, SourceAttributes, SourcePermissions, SourceProvider, SourceState},
+        source::{
+            CommandStream, Socket, SocketStream, Source, SourceAttributes, SourcePermissions,
+            SourceProvider, SourceState,
+        },
         view::{HexData, TextData, View, ViewKind},
+        watch_list::WatchList,
     },
     anyhow::{bail, Context},
     egui_sfml::sfml::graphics::Font,
@@ -43,11 +64,13 @@ use {
 
 /// The hexerator application state
 pub struct App {
-    pub data: Vec<u8>,
+    pub data: Data,
     pub edit_state: EditState,
     pub input: Input,
     pub args: Args,
     pub source: Option<Source>,
+    /// Maps offsets to real addresses, for sources that are made up of multiple segments
+    pub addr_map: Option<AddrMap>,
     pub just_reloaded: bool,
     pub stream_read_recv: Option<Receiver<Vec<u8>>>,
     pub cfg: Config,
@@ -56,6 +79,25 @@ pub struct App {
     pub hex_ui: HexUi,
     pub meta_state: MetaState,
     pub lua: Lua,
+    /// Values of the opened process that are being watched
+    pub watch_list: WatchList,
+    /// Set when a compressed file was opened, to offer decompressing it
+    pub compression_offer: bool,
+    /// Second file being compared against the data, if in compare mode
+    pub compare: Option<Compare>,
+    /// Views showing two regions of the data side by side, if shown
+    pub region_compare: Option<RegionCompareViews>,
+    /// Timeline of the changes seen on reloads
+    pub change_recorder: ChangeRecorder,
+    /// How recently and how often each byte changed, for the heatmap color methods
+    pub heatmap: Heatmap,
+    /// Open documents, one per tab.
+    ///
+    /// The slot of the active tab holds a placeholder, since its document lives in `App` itself.
+    pub tabs: Vec<Document>,
+    pub active_tab: usize,
+    /// Bytes copied with Edit -> Copy, shared between tabs
+    pub clipboard: Vec<u8>,
 }
 
 impl App {
@@ -69,11 +111,12 @@ impl App {
             args.src = recent.clone();
         }
         let mut this = Self {
-            data: Vec::new(),
+            data: Data::default(),
             edit_state: EditState::default(),
             input: Input::default(),
             args: Args::default(),
             source: None,
+            addr_map: None,
             just_reloaded: true,
             stream_read_recv: None,
             cfg,
@@ -82,6 +125,15 @@ impl App {
             hex_ui: HexUi::default(),
             meta_state: MetaState::default(),
             lua: Lua::default(),
+            watch_list: WatchList::default(),
+            compression_offer: false,
+            compare: None,
+            region_compare: None,
+            change_recorder: ChangeRecorder::default(),
+            heatmap: Heatmap::default(),
+            tabs: vec![Document::default()],
+            active_tab: 0,
+            clipboard: Vec::new(),
         };
         msg_if_fail(
             this.load_file_args(args, font, msg),
@@ -91,34 +143,180 @@ impl App {
         Ok(this)
     }
     pub fn reload(&mut self) -> anyhow::Result<()> {
+        let prev_data =
+            (self.change_recorder.enabled || self.heatmap.enabled).then(|| self.data.to_vec());
         match &mut self.source {
             Some(src) => match &mut src.provider {
                 SourceProvider::File(file) => {
                     self.data = read_contents(&self.args.src, file)?;
+                    if let Some(comp) = src.state.compression {
+                        self.data = comp.algo.decompress(&self.data)?.into();
+                    }
                     self.edit_state.dirty_region = None;
+                    self.edit_state.resized = false;
                 }
-                SourceProvider::Stdin(_) => {
+                SourceProvider::Stdin(_)
+                | SourceProvider::Command(_)
+                | SourceProvider::Socket(_) => {
                     bail!("Can't reload streaming sources like standard input")
                 }
+                SourceProvider::BlockDevice { file, sector_size } => {
+                    self.data = read_block_device(&self.args.src, file, *sector_size)?;
+                    self.edit_state.dirty_region = None;
+                }
+                #[cfg(target_os = "linux")]
+                SourceProvider::ProcAddrSpace(file) => {
+                    use std::os::unix::fs::FileExt;
+                    if let Some(map) = &self.addr_map {
+                        for seg in &map.segments {
+                            // Mappings can go away, keep the old data for those
+                            let _ = file.read_exact_at(
+                                &mut self.data[seg.offset..seg.offset + seg.len],
+                                seg.addr as u64,
+                            );
+                        }
+                    }
+                    self.edit_state.dirty_region = None;
+                }
+                SourceProvider::RecordFile { file, format, .. } => {
+                    let mut text = String::new();
+                    file.seek(SeekFrom::Start(0))?;
+                    file.read_to_string(&mut text)?;
+                    let parsed = ihex_srec::parse(&text, *format)?;
+                    let map = self.addr_map.as_ref().context("Missing address map")?;
+                    let same_layout = parsed.blocks.len() == map.segments.len()
+                        && parsed.blocks.iter().zip(&map.segments).all(|(block, seg)| {
+                            block.addr == seg.addr && block.data.len() == seg.len
+                        });
+                    if !same_layout {
+                        bail!("The blocks of the file changed, open it again to reload");
+                    }
+                    for (block, seg) in parsed.blocks.iter().zip(&map.segments) {
+                        self.data[seg.offset..seg.offset + seg.len].copy_from_slice(&block.data);
+                    }
+                    self.edit_state.dirty_region = None;
+                }
+                SourceProvider::ElfCore { file, file_offsets } => {
+                    if let Some(map) = &self.addr_map {
+                        for (seg, &file_offset) in map.segments.iter().zip(file_offsets.iter()) {
+                            file.seek(SeekFrom::Start(file_offset))?;
+                            file.read_exact(&mut self.data[seg.offset..seg.offset + seg.len])?;
+                        }
+                    }
+                    self.edit_state.dirty_region = None;
+                }
                 #[cfg(windows)]
                 SourceProvider::WinProc {
                     handle,
                     start,
                     size,
+                    ..
                 } => unsafe {
-                    crate::windows::read_proc_memory(*handle, &mut self.data, *start, *size)?;
+                    let data = self
+                        .data
+                        .vec_mut()
+                        .context("Process memory can't be memory mapped")?;
+                    crate::windows::read_proc_memory(*handle, data, *start, *size)?;
                 },
             },
             None => bail!("No file to reload"),
         }
+        if let Some(prev_data) = prev_data {
+            if self.change_recorder.enabled {
+                self.change_recorder.record(&prev_data, &self.data);
+            }
+            if self.heatmap.enabled {
+                self.heatmap.record(&prev_data, &self.data);
+            }
+        }
         self.just_reloaded = true;
         Ok(())
     }
     pub fn save(&mut self) -> anyhow::Result<()> {
         let file = match &mut self.source {
             Some(src) => match &mut src.provider {
-                SourceProvider::File(file) => file,
+                SourceProvider::File(file) => {
+                    if let Some(comp) = src.state.compression {
+                        let compressed = comp.compress(&self.data)?;
+                        let path = self.args.src.file.as_ref().context("Missing file path")?;
+                        *file = replace_file(path, &compressed)?;
+                        self.edit_state.dirty_region = None;
+                        return Ok(());
+                    }
+                    file
+                }
                 SourceProvider::Stdin(_) => bail!("Standard input doesn't support saving"),
+                SourceProvider::Command(_) => bail!("Command output doesn't support saving"),
+                SourceProvider::Socket(stream) => {
+                    // Edited bytes are sent back to the other end
+                    if let Some(region) = self.edit_state.dirty_region {
+                        stream
+                            .socket
+                            .write_all(&self.data[region.begin..=region.end])?;
+                        self.edit_state.dirty_region = None;
+                    }
+                    return Ok(());
+                }
+                SourceProvider::BlockDevice { file, sector_size } => {
+                    let offset = self.args.src.hard_seek.unwrap_or(0);
+                    let (begin, data) = match self.edit_state.dirty_region {
+                        // Same inclusive end as with regular files below
+                        Some(region) => (region.begin, &self.data[region.begin..=region.end]),
+                        None => (0, &self.data[..]),
+                    };
+                    block_dev::write_aligned(file, offset + begin, data, *sector_size)?;
+                    self.edit_state.dirty_region = None;
+                    return Ok(());
+                }
+                #[cfg(target_os = "linux")]
+                SourceProvider::ProcAddrSpace(file) => {
+                    use std::os::unix::fs::FileExt;
+                    if let Some(region) = self.edit_state.dirty_region
+                        && let Some(map) = &self.addr_map
+                    {
+                        for (seg, range) in map.split_range(region.begin, region.end) {
+                            let addr = seg.addr + (range.start - seg.offset);
+                            file.write_all_at(&self.data[range], addr as u64)?;
+                        }
+                        self.edit_state.dirty_region = None;
+                    }
+                    return Ok(());
+                }
+                SourceProvider::RecordFile {
+                    file,
+                    format,
+                    record_size,
+                    extra,
+                } => {
+                    // The whole file is written again, since records have checksums
+                    let map = self.addr_map.as_ref().context("Missing address map")?;
+                    let blocks: Vec<_> = map
+                        .segments
+                        .iter()
+                        .map(|seg| (seg.addr, &self.data[seg.offset..seg.offset + seg.len]))
+                        .collect();
+                    let text = ihex_srec::write(*format, &blocks, *record_size, extra)?;
+                    let path = self.args.src.file.as_ref().context("Missing file path")?;
+                    *file = replace_file(path, text.as_bytes())?;
+                    self.edit_state.dirty_region = None;
+                    return Ok(());
+                }
+                SourceProvider::ElfCore { file, file_offsets } => {
+                    if let Some(region) = self.edit_state.dirty_region
+                        && let Some(map) = &self.addr_map
+                    {
+                        for (seg, range) in map.split_range(region.begin, region.end) {
+                            let Some(idx) = map.segment_idx_at_offset(range.start) else {
+                                continue;
+                            };
+                            let pos = file_offsets[idx] + (range.start - seg.offset) as u64;
+                            file.seek(SeekFrom::Start(pos))?;
+                            file.write_all(&self.data[range])?;
+                        }
+                        self.edit_state.dirty_region = None;
+                    }
+                    return Ok(());
+                }
                 #[cfg(windows)]
                 SourceProvider::WinProc { handle, start, .. } => {
                     if let Some(region) = self.edit_state.dirty_region {
@@ -162,7 +360,12 @@ impl App {
             None => &self.data,
         };
         file.write_all(data_to_write)?;
+        // Patches can change the length of the data
+        if self.edit_state.resized && file.metadata()?.is_file() {
+            file.set_len(self.data.len() as u64)?;
+        }
         self.edit_state.dirty_region = None;
+        self.edit_state.resized = false;
         if let Err(e) = self.save_temp_metafile_backup() {
             per_msg!("Failed to save metafile backup: {}", e);
         }
@@ -262,8 +465,18 @@ impl App {
         )
     }
 
+    /// Whether a newly loaded file gets new metadata, instead of keeping the current one.
+    ///
+    /// A fresh tab has no metadata to keep, even if keeping it is preferred.
+    pub(crate) fn should_readjust_meta(&self) -> bool {
+        !self.preferences.keep_meta || self.meta_state.meta.layouts.is_empty()
+    }
+
     /// Readjust to a new file
     pub fn new_file_readjust(&mut self, font: &Font) {
+        self.compare = None;
+        self.region_compare = None;
+        self.heatmap.clear();
         self.meta_state.meta = Meta::default();
         self.meta_state.current_meta_path.clear();
         let def_region = self.meta_state.meta.low.regions.insert(NamedRegion {
@@ -273,6 +486,7 @@ impl App {
                 end: self.data.len().saturating_sub(1),
             },
             desc: String::new(),
+            anchor: None,
         });
         let default_perspective = self.meta_state.meta.low.perspectives.insert(Perspective {
             region: def_region,
@@ -294,10 +508,13 @@ impl App {
     }
 
     pub fn close_file(&mut self) {
+        self.exit_compare();
+        self.remove_region_compare_views();
         // We potentially had large data, free it instead of clearing the Vec
-        self.data = Vec::new();
+        self.data = Data::default();
         self.args.src.file = None;
         self.source = None;
+        self.addr_map = None;
     }
 
     pub(crate) fn restore_backup(&mut self) -> Result<(), anyhow::Error> {
@@ -349,7 +566,15 @@ impl App {
                     if buf.is_empty() {
                         src.state.stream_end = true;
                     } else {
-                        self.data.extend_from_slice(&buf[..]);
+                        let prev_len = self.data.len();
+                        let Some(data) = self.data.vec_mut() else {
+                            per_msg!("Can't append stream data to memory mapped data");
+                            return;
+                        };
+                        data.extend_from_slice(&buf[..]);
+                        if self.heatmap.enabled {
+                            self.heatmap.mark_range(prev_len..self.data.len());
+                        }
                         let perspective = &self.meta_state.meta.low.perspectives[view.perspective];
                         let region =
                             &mut self.meta_state.meta.low.regions[perspective.region].region;
@@ -414,15 +639,47 @@ impl App {
     }
 
     pub fn save_meta_to_file(&mut self, path: PathBuf, temp: bool) -> Result<(), anyhow::Error> {
-        let data = rmp_serde::to_vec(&self.meta_state.meta)?;
+        let meta = self.meta_for_saving();
+        let data = rmp_serde::to_vec(&meta)?;
         std::fs::write(&path, &data)?;
         if !temp {
             self.meta_state.current_meta_path = path;
-            self.meta_state.clean_meta = self.meta_state.meta.clone();
+            self.meta_state.clean_meta = meta;
         }
         Ok(())
     }
 
+    /// The meta without the temporary views, perspectives, regions and layouts of compare mode
+    /// and of regions shown side by side
+    fn meta_for_saving(&self) -> Meta {
+        let mut meta = self.meta_state.meta.clone();
+        if let Some(created) = &self.region_compare {
+            for &key in &created.views {
+                meta.views.remove(key);
+            }
+            for &key in &created.perspectives {
+                meta.low.perspectives.remove(key);
+            }
+            for &key in &created.regions {
+                meta.low.regions.remove(key);
+            }
+            meta.layouts.remove(created.layout);
+        }
+        if let Some(compare) = &self.compare {
+            for &(_, key) in &compare.views {
+                meta.views.remove(key);
+            }
+            for &(_, key) in &compare.perspectives {
+                meta.low.perspectives.remove(key);
+            }
+            for &(_, key) in &compare.regions {
+                meta.low.regions.remove(key);
+            }
+            meta.layouts.remove(compare.layout);
+        }
+        meta
+    }
+
     pub(crate) fn load_file_args(
         &mut self,
         mut args: Args,
@@ -436,10 +693,29 @@ impl App {
             &mut self.data,
             msg,
         ) {
-            if !self.preferences.keep_meta {
+            self.addr_map = None;
+            self.edit_state.resized = false;
+            self.compression_offer = match &self.source {
+                Some(Source {
+                    provider: SourceProvider::File(_),
+                    attr,
+                    ..
+                }) if !attr.stream && args.src.hard_seek.is_none() && args.src.take.is_none() => {
+                    Algorithm::detect(&self.data).is_some()
+                }
+                _ => false,
+            };
+            let sector_size = self.source.as_ref().and_then(|src| match &src.provider {
+                SourceProvider::BlockDevice { sector_size, .. } => Some(*sector_size),
+                _ => None,
+            });
+            self.hex_ui.sector_grid = sector_size;
+            if self.should_readjust_meta() {
                 self.new_file_readjust(font);
                 if let Some(meta_path) = &args.meta {
                     self.consume_meta_from_file(meta_path.clone())?;
+                } else if sector_size.is_some() {
+                    self.add_partition_regions();
                 }
             }
             self.args = args;
@@ -477,6 +753,17 @@ impl App {
             }
             self.last_reload = Instant::now();
         }
+        let heatmap_used = self
+            .meta_state
+            .meta
+            .views
+            .values()
+            .any(|view| view.view.presentation.color_method.is_heatmap());
+        if heatmap_used != self.heatmap.enabled {
+            self.heatmap.enabled = heatmap_used;
+            self.heatmap.clear();
+        }
+        self.watch_list.update();
     }
     pub(crate) fn focused_view_select_all(&mut self) {
         if let Some(view) = self.hex_ui.focused_view {
@@ -494,18 +781,486 @@ impl App {
 
     pub(crate) fn diff_with_file(&mut self, path: PathBuf, gui: &mut Gui) -> anyhow::Result<()> {
         let file_data = read_source_to_buf(&path, &self.args.src)?;
-        let mut diff_entries = Vec::new();
-        for ((offset, &my_byte), &file_byte) in self.data.iter().enumerate().zip(file_data.iter()) {
-            if my_byte != file_byte {
-                diff_entries.push(FileDiffEntry {
-                    my_val: my_byte,
-                    file_val: file_byte,
-                    offset,
-                });
+        let win = &mut gui.file_diff_result_window;
+        win.hunks = binary_diff::diff(&self.data, &file_data);
+        win.file_data = file_data.into();
+        win.path = path;
+        gui.file_diff_result_window.open.set(true);
+        Ok(())
+    }
+
+    /// Apply an IPS, UPS, BPS or bsdiff patch to the data, marking the changed bytes dirty.
+    ///
+    /// Returns the number of changed bytes.
+    pub(crate) fn apply_patch(&mut self, patch_data: &[u8]) -> anyhow::Result<usize> {
+        let patched = patch::apply(patch_data, &self.data)?;
+        if patched.len() == self.data.len() {
+            let mut changed = 0;
+            for (offset, (old, &new)) in self.data.iter_mut().zip(&patched).enumerate() {
+                if *old != new {
+                    *old = new;
+                    changed += 1;
+                    self.edit_state
+                        .widen_dirty_region(DamageRegion::Single(offset));
+                }
+            }
+            return Ok(changed);
+        }
+        // Sources that are loaded in pieces, only partly, or streamed can't change their size
+        let resizable = self.addr_map.is_none()
+            && self.args.src.hard_seek.is_none()
+            && self.args.src.take.is_none()
+            && match &self.source {
+                None => true,
+                Some(src) => matches!(src.provider, SourceProvider::File(_)) && !src.attr.stream,
+            };
+        if !resizable {
+            bail!(
+                "The patch changes the size of the data, which isn't supported for this source, \
+                 or when only part of the file is open"
+            );
+        }
+        let first_diff = self
+            .data
+            .iter()
+            .zip(&patched)
+            .position(|(a, b)| a != b)
+            .unwrap_or(self.data.len().min(patched.len()));
+        let changed = self
+            .data
+            .iter()
+            .zip(&patched)
+            .filter(|(a, b)| a != b)
+            .count()
+            + patched.len().abs_diff(self.data.len());
+        let old_last = self.data.len().saturating_sub(1);
+        self.data = patched.into();
+        self.edit_state.resized = true;
+        let last = self.data.len().saturating_sub(1);
+        // Regions that reached the old end follow the new end
+        for reg in self.meta_state.meta.low.regions.values_mut() {
+            if reg.region.end == old_last || reg.region.end > last {
+                reg.region.end = last;
+                reg.region.begin = reg.region.begin.min(last);
+            }
+        }
+        if let Some(dirty) = self.edit_state.dirty_region {
+            self.edit_state.dirty_region = (dirty.begin <= last).then_some(Region {
+                begin: dirty.begin,
+                end: dirty.end.min(last),
+            });
+        }
+        if first_diff < self.data.len() {
+            self.edit_state
+                .widen_dirty_region(DamageRegion::RangeInclusive(first_diff..=last));
+        }
+        Ok(changed)
+    }
+
+    /// Create a patch from the contents of `path` to the current data
+    pub(crate) fn create_patch(&self, format: PatchFormat, path: &Path) -> anyhow::Result<Vec<u8>> {
+        let base = self.read_like_source(path)?;
+        patch::create(format, &base, &self.data)
+    }
+
+    /// Read `path` the same way the data was derived from the source file, like decompressing
+    /// it, or laying out the blocks of a record file
+    fn read_like_source(&self, path: &Path) -> anyhow::Result<Vec<u8>> {
+        let Some(src) = &self.source else {
+            return read_source_to_buf(path, &self.args.src);
+        };
+        match &src.provider {
+            SourceProvider::File(_) => match src.state.compression {
+                Some(comp) => Ok(comp.algo.decompress(&std::fs::read(path)?)?),
+                None => read_source_to_buf(path, &self.args.src),
+            },
+            SourceProvider::RecordFile { format, .. } => {
+                let parsed = ihex_srec::parse(&std::fs::read_to_string(path)?, *format)?;
+                Ok(parsed
+                    .blocks
+                    .into_iter()
+                    .flat_map(|block| block.data)
+                    .collect())
+            }
+            SourceProvider::ElfCore { file_offsets, .. } => {
+                let map = self.addr_map.as_ref().context("Missing address map")?;
+                let mut file = File::open(path)?;
+                let mut data = vec![0; self.data.len()];
+                for (seg, &file_offset) in map.segments.iter().zip(file_offsets) {
+                    file.seek(SeekFrom::Start(file_offset))?;
+                    file.read_exact(&mut data[seg.offset..seg.offset + seg.len])?;
+                }
+                Ok(data)
+            }
+            _ => read_source_to_buf(path, &self.args.src),
+        }
+    }
+
+    /// Enter compare mode, showing the contents of `path` next to the data.
+    ///
+    /// Every view of the current layout gets a clone showing the other file, placed to the
+    /// right of it in a new layout. The clones get their own perspectives and regions, sized
+    /// to the other file.
+    pub(crate) fn enter_compare(&mut self, path: &Path) -> anyhow::Result<()> {
+        let data = read_source_to_buf(path, &self.args.src)?;
+        self.exit_compare();
+        let meta = &mut self.meta_state.meta;
+        let prev_layout = self.hex_ui.current_layout;
+        let orig = meta
+            .layouts
+            .get(prev_layout)
+            .context("No layout to compare in")?
+            .clone();
+        let file_name = path.file_name().map_or_else(
+            || path.display().to_string(),
+            |name| name.to_string_lossy().into_owned(),
+        );
+        let mut views = Vec::new();
+        let mut perspectives: Vec<(PerspectiveKey, PerspectiveKey)> = Vec::new();
+        let mut regions: Vec<(RegionKey, RegionKey)> = Vec::new();
+        let mut view_grid = Vec::new();
+        for row in &orig.view_grid {
+            let mut new_row = row.clone();
+            for &key in row {
+                let mut view = meta.views[key].clone();
+                view.name = format!("{} ({file_name})", view.name);
+                let orig_per = view.view.perspective;
+                view.view.perspective = match perspectives.iter().find(|(o, _)| *o == orig_per) {
+                    Some(&(_, per)) => per,
+                    None => {
+                        let mut per = meta.low.perspectives[orig_per].clone();
+                        let orig_reg = per.region;
+                        per.region = match regions.iter().find(|(o, _)| *o == orig_reg) {
+                            Some(&(_, reg)) => reg,
+                            None => {
+                                let named = &meta.low.regions[orig_reg];
+                                let region = compare::counterpart_region(
+                                    named.region,
+                                    self.data.len(),
+                                    data.len(),
+                                );
+                                let reg = meta.low.regions.insert(NamedRegion {
+                                    name: format!("{} ({file_name})", named.name),
+                                    ..NamedRegion::new_from_selection(region)
+                                });
+                                regions.push((orig_reg, reg));
+                                reg
+                            }
+                        };
+                        per.name = format!("{} ({file_name})", per.name);
+                        let new_per = meta.low.perspectives.insert(per);
+                        perspectives.push((orig_per, new_per));
+                        new_per
+                    }
+                };
+                let new_key = meta.views.insert(view);
+                views.push((key, new_key));
+                new_row.push(new_key);
+            }
+            view_grid.push(new_row);
+        }
+        let layout = meta.layouts.insert(Layout {
+            name: format!("Compare: {file_name}"),
+            view_grid,
+            margin: orig.margin,
+        });
+        App::switch_layout(&mut self.hex_ui, meta, layout);
+        self.compare = Some(Compare {
+            path: path.to_owned(),
+            data,
+            views,
+            perspectives,
+            regions,
+            layout,
+            prev_layout,
+        });
+        Ok(())
+    }
+
+    /// Read the compared file again, and resize the regions of the compare views to it
+    pub(crate) fn reload_compare(&mut self) -> anyhow::Result<()> {
+        let cmp = self.compare.as_mut().context("Not comparing")?;
+        cmp.data = read_source_to_buf(&cmp.path, &self.args.src)?;
+        let regions = &mut self.meta_state.meta.low.regions;
+        for &(orig, reg) in &cmp.regions {
+            if let Some(orig) = regions.get(orig) {
+                let region =
+                    compare::counterpart_region(orig.region, self.data.len(), cmp.data.len());
+                regions[reg].region = region;
             }
         }
-        gui.file_diff_result_window.diff_entries = diff_entries;
-        gui.file_diff_result_window.path = path;
+        Ok(())
+    }
+
+    /// Leave compare mode, removing the views and layout created for it
+    pub(crate) fn exit_compare(&mut self) {
+        let Some(compare) = self.compare.take() else {
+            return;
+        };
+        let meta = &mut self.meta_state.meta;
+        for (_, key) in compare.views {
+            meta.views.remove(key);
+        }
+        for (_, key) in compare.perspectives {
+            meta.low.perspectives.remove(key);
+        }
+        for (_, key) in compare.regions {
+            meta.low.regions.remove(key);
+        }
+        meta.layouts.remove(compare.layout);
+        self.restore_layout(compare.prev_layout);
+    }
+
+    /// Switch to `prev_layout` if it still exists, or to any layout otherwise
+    fn restore_layout(&mut self, prev_layout: LayoutKey) {
+        let meta = &self.meta_state.meta;
+        let layout = if meta.layouts.contains_key(prev_layout) {
+            Some(prev_layout)
+        } else {
+            meta.layouts.keys().next()
+        };
+        if let Some(layout) = layout {
+            App::switch_layout(&mut self.hex_ui, meta, layout);
+        }
+    }
+
+    /// Show two regions of the data next to each other in a new layout, replacing the
+    /// previously shown ones.
+    ///
+    /// Each side is an existing named region, or a range that gets a new region.
+    /// The views are clones of the focused view.
+    pub(crate) fn show_regions_side_by_side(
+        &mut self,
+        sides: [(Option<RegionKey>, Region); 2],
+    ) -> anyhow::Result<()> {
+        self.remove_region_compare_views();
+        let focused = self
+            .hex_ui
+            .focused_view
+            .context("No view to show the regions with")?;
+        let meta = &mut self.meta_state.meta;
+        let orig = meta.views[focused].clone();
+        let cols = meta.low.perspectives[orig.view.perspective].cols;
+        let mut created = RegionCompareViews {
+            views: Vec::new(),
+            perspectives: Vec::new(),
+            regions: Vec::new(),
+            layout: LayoutKey::null(),
+            prev_layout: self.hex_ui.current_layout,
+        };
+        let mut row = Vec::new();
+        for ((key, region), label) in sides.into_iter().zip(["A", "B"]) {
+            let region_key = match key {
+                Some(key) if meta.low.regions.contains_key(key) => key,
+                _ => {
+                    let key = meta.low.regions.insert(NamedRegion {
+                        name: format!("Compare {label}"),
+                        ..NamedRegion::new_from_selection(region)
+                    });
+                    created.regions.push(key);
+                    key
+                }
+            };
+            let perspective = meta.low.perspectives.insert(Perspective {
+                region: region_key,
+                cols,
+                flip_row_order: false,
+                name: format!("Compare {label}"),
+            });
+            created.perspectives.push(perspective);
+            let mut view = orig.clone();
+            view.name = format!("Compare {label}");
+            view.view.perspective = perspective;
+            view.view.scroll_offset = Default::default();
+            let view_key = meta.views.insert(view);
+            created.views.push(view_key);
+            row.push(view_key);
+        }
+        created.layout = meta.layouts.insert(Layout {
+            name: "Compare regions".into(),
+            view_grid: vec![row],
+            margin: default_margin(),
+        });
+        App::switch_layout(&mut self.hex_ui, meta, created.layout);
+        self.region_compare = Some(created);
+        Ok(())
+    }
+
+    /// Remove the metadata that [`Self::show_regions_side_by_side`] created
+    pub(crate) fn remove_region_compare_views(&mut self) {
+        let Some(created) = self.region_compare.take() else {
+            return;
+        };
+        let meta = &mut self.meta_state.meta;
+        for key in created.views {
+            meta.views.remove(key);
+        }
+        for key in created.perspectives {
+            meta.low.perspectives.remove(key);
+        }
+        for key in created.regions {
+            meta.low.regions.remove(key);
+        }
+        meta.layouts.remove(created.layout);
+        if self.hex_ui.current_layout == created.layout {
+            self.restore_layout(created.prev_layout);
+        }
+    }
+
+    /// The data that the view `key` shows
+    pub(crate) fn view_data(&self, key: ViewKey) -> &[u8] {
+        match &self.compare {
+            Some(compare) if compare.is_compare_view(key) => &compare.data,
+            _ => &self.data,
+        }
+    }
+
+    /// Whether the byte at `idx` differs from the compared file. Always false outside compare mode.
+    pub(crate) fn compare_differs(&self, idx: usize) -> bool {
+        self.compare
+            .as_ref()
+            .is_some_and(|compare| compare::differs(&self.data, &compare.data, idx))
+    }
+
+    /// Move the cursor to the next (or previous) difference from the compared file
+    pub(crate) fn goto_compare_diff(&mut self, forward: bool) -> bool {
+        let Some(compare) = &self.compare else {
+            return false;
+        };
+        let cursor = self.edit_state.cursor;
+        let found = if forward {
+            compare::next_diff(&self.data, &compare.data, cursor)
+        } else {
+            compare::prev_diff(&self.data, &compare.data, cursor)
+        };
+        let Some(offset) = found else {
+            return false;
+        };
+        // Differences past the end of our data are only in the compare views, so focus the
+        // compare view of the focused view, and keep the cursor inside our data
+        if offset >= self.data.len()
+            && let Some(focused) = self.hex_ui.focused_view
+            && let Some(cmp_view) = compare.paired_view(focused)
+            && !compare.is_compare_view(focused)
+        {
+            self.hex_ui.focused_view = Some(cmp_view);
+        }
+        let cursor = offset.min(self.data.len().saturating_sub(1));
+        self.edit_state.set_cursor(cursor);
+        self.center_view_on_offset(offset);
+        self.hex_ui.flash_cursor();
+        true
+    }
+
+    /// Swap the document state of `App` with `doc`
+    fn swap_document(&mut self, doc: &mut Document) {
+        std::mem::swap(&mut self.data, &mut doc.data);
+        std::mem::swap(&mut self.edit_state, &mut doc.edit_state);
+        std::mem::swap(&mut self.args, &mut doc.args);
+        std::mem::swap(&mut self.source, &mut doc.source);
+        std::mem::swap(&mut self.addr_map, &mut doc.addr_map);
+        std::mem::swap(&mut self.stream_read_recv, &mut doc.stream_read_recv);
+        std::mem::swap(&mut self.hex_ui, &mut doc.hex_ui);
+        std::mem::swap(&mut self.meta_state, &mut doc.meta_state);
+        std::mem::swap(&mut self.watch_list, &mut doc.watch_list);
+        std::mem::swap(&mut self.compression_offer, &mut doc.compression_offer);
+        std::mem::swap(&mut self.compare, &mut doc.compare);
+        std::mem::swap(&mut self.region_compare, &mut doc.region_compare);
+        std::mem::swap(&mut self.change_recorder, &mut doc.change_recorder);
+        std::mem::swap(&mut self.heatmap, &mut doc.heatmap);
+    }
+
+    /// Make the tab at `idx` the active one
+    pub(crate) fn switch_tab(&mut self, idx: usize) {
+        if idx == self.active_tab || idx >= self.tabs.len() {
+            return;
+        }
+        let mut doc = std::mem::take(&mut self.tabs[idx]);
+        self.swap_document(&mut doc);
+        self.tabs[self.active_tab] = doc;
+        self.active_tab = idx;
+        self.just_reloaded = true;
+    }
+
+    /// Open a new, empty tab and switch to it
+    pub(crate) fn new_tab(&mut self) {
+        self.tabs.push(Document::default());
+        self.switch_tab(self.tabs.len() - 1);
+    }
+
+    /// Close the tab at `idx`, discarding its document. The last tab can't be closed.
+    pub(crate) fn close_tab(&mut self, idx: usize) {
+        if self.tabs.len() <= 1 || idx >= self.tabs.len() {
+            return;
+        }
+        if idx == self.active_tab {
+            self.switch_tab(if idx == 0 { 1 } else { idx - 1 });
+        }
+        self.tabs.remove(idx);
+        if self.active_tab > idx {
+            self.active_tab -= 1;
+        }
+    }
+
+    /// Whether the document in the tab at `idx` has unsaved edits
+    pub(crate) fn tab_dirty(&self, idx: usize) -> bool {
+        if idx == self.active_tab {
+            self.edit_state.dirty_region.is_some()
+        } else {
+            self.tabs
+                .get(idx)
+                .is_some_and(|doc| doc.edit_state.dirty_region.is_some())
+        }
+    }
+
+    pub(crate) fn tab_label(&self, idx: usize) -> String {
+        if idx == self.active_tab {
+            document::tab_label(&self.args)
+        } else {
+            document::tab_label(&self.tabs[idx].args)
+        }
+    }
+
+    /// Copy the selection into the clipboard shared by all tabs
+    pub(crate) fn copy_selection(&mut self) -> bool {
+        match self.hex_ui.selection() {
+            Some(sel) => {
+                self.clipboard = self.data[sel.begin..=sel.end].to_vec();
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Overwrite the bytes at the cursor with the clipboard, as many as fit.
+    ///
+    /// Returns the number of bytes pasted.
+    pub(crate) fn paste_at_cursor(&mut self) -> usize {
+        let begin = self.edit_state.cursor;
+        let len = self
+            .clipboard
+            .len()
+            .min(self.data.len().saturating_sub(begin));
+        if len == 0 {
+            return 0;
+        }
+        self.data[begin..begin + len].copy_from_slice(&self.clipboard[..len]);
+        self.edit_state
+            .widen_dirty_region(DamageRegion::RangeInclusive(begin..=begin + len - 1));
+        len
+    }
+
+    /// Diff the data against the data of another tab
+    pub(crate) fn diff_with_tab(&mut self, idx: usize, gui: &mut Gui) -> anyhow::Result<()> {
+        if idx == self.active_tab {
+            bail!("Can't diff a tab with itself");
+        }
+        let other = self.tabs.get(idx).context("No such tab")?;
+        let win = &mut gui.file_diff_result_window;
+        win.hunks = binary_diff::diff(&self.data, &other.data);
+        win.file_data = other.data.snapshot();
+        // Refreshing reads the file the tab was opened from
+        win.path = other.args.src.file.clone().unwrap_or_default();
         gui.file_diff_result_window.open.set(true);
         Ok(())
     }
@@ -569,10 +1324,448 @@ impl App {
         return crate::windows::load_proc_memory(self, pid, start, size, is_write, font);
     }
 
+    /// The process whose memory is loaded, if the source is process memory
+    pub(crate) fn source_pid(&self) -> Option<sysinfo::Pid> {
+        match &self.source.as_ref()?.provider {
+            // Process memory is read through `/proc/<pid>/mem`, one mapping or all of them
+            #[cfg(target_os = "linux")]
+            SourceProvider::File(_) | SourceProvider::ProcAddrSpace(_) => {
+                use sysinfo::PidExt;
+                let path = self.args.src.file.as_ref()?.strip_prefix("/proc").ok()?;
+                if path.file_name()? != "mem" {
+                    return None;
+                }
+                let pid = path.parent()?.to_str()?.parse().ok()?;
+                Some(sysinfo::Pid::from_u32(pid))
+            }
+            #[cfg(windows)]
+            SourceProvider::WinProc { pid, .. } => Some(*pid),
+            _ => None,
+        }
+    }
+
+    /// Load every readable mapping of a process as one buffer, skipping the gaps between them
+    #[cfg(target_os = "linux")]
+    pub(crate) fn load_proc_addr_space(
+        &mut self,
+        pid: sysinfo::Pid,
+        ranges: &[proc_maps::MapRange],
+        font: &Font,
+    ) -> anyhow::Result<()> {
+        use std::os::unix::fs::FileExt;
+        let path = Path::new("/proc/").join(pid.to_string()).join("mem");
+        let (file, writable) = match open_file(&path, false) {
+            Ok(file) => (file, true),
+            Err(_) => (open_file(&path, true)?, false),
+        };
+        let mut ranges: Vec<&proc_maps::MapRange> =
+            ranges.iter().filter(|range| range.is_read()).collect();
+        ranges.sort_by_key(|range| range.start());
+        let mut data = Vec::new();
+        let mut map = AddrMap::default();
+        for range in ranges {
+            let offset = data.len();
+            data.resize(offset + range.size(), 0);
+            if file
+                .read_exact_at(&mut data[offset..], range.start() as u64)
+                .is_err()
+            {
+                // Some mappings (like [vvar]) can't be read, leave them out
+                data.truncate(offset);
+                continue;
+            }
+            map.push(
+                proc_mem::map_range_label(range),
+                range.start(),
+                range.size(),
+            );
+        }
+        if data.is_empty() {
+            bail!("Couldn't read any of the mappings");
+        }
+        self.load_segmented(
+            data,
+            map,
+            SourceProvider::ProcAddrSpace(file),
+            path,
+            writable,
+            font,
+        );
+        Ok(())
+    }
+
+    /// Replace the data with its decompressed form, and compress it again on save
+    pub(crate) fn decompress(&mut self, algo: Algorithm, font: &Font) -> anyhow::Result<()> {
+        let Some(src) = &mut self.source else {
+            bail!("No source opened");
+        };
+        if self.args.src.hard_seek.is_some() || self.args.src.take.is_some() {
+            bail!("Only whole files can be decompressed");
+        }
+        let level = algo.guess_level(&self.data);
+        self.data = algo.decompress(&self.data)?.into();
+        src.state.compression = Some(Compression { algo, level });
+        self.edit_state.dirty_region = None;
+        if self.should_readjust_meta() {
+            self.new_file_readjust(font);
+        }
+        Ok(())
+    }
+
+    /// Compress the data like the source was, and write it to `path`
+    pub(crate) fn save_compressed_to(&self, path: &Path) -> anyhow::Result<()> {
+        let comp = self
+            .source
+            .as_ref()
+            .and_then(|src| src.state.compression)
+            .context("Source is not decompressed")?;
+        std::fs::write(path, comp.compress(&self.data)?)?;
+        Ok(())
+    }
+
+    /// Spawn `cmd_line`, and stream its standard output in
+    pub(crate) fn load_command_stream(
+        &mut self,
+        cmd_line: &str,
+        font: &Font,
+    ) -> anyhow::Result<()> {
+        let stream = CommandStream::spawn(cmd_line)?;
+        self.load_stream(SourceProvider::Command(stream), false, font);
+        Ok(())
+    }
+
+    /// Stream in the bytes received on a connected socket.
+    ///
+    /// If `writable`, saving sends the edited bytes back.
+    pub(crate) fn load_socket_stream(&mut self, socket: Socket, writable: bool, font: &Font) {
+        self.load_stream(
+            SourceProvider::Socket(SocketStream::new(socket)),
+            writable,
+            font,
+        );
+    }
+
+    /// Send the bytes in `region` through the socket source
+    pub(crate) fn send_to_socket(&mut self, region: Region) -> anyhow::Result<()> {
+        match self.source.as_mut().map(|src| &mut src.provider) {
+            Some(SourceProvider::Socket(stream)) => {
+                stream
+                    .socket
+                    .write_all(&self.data[region.begin..=region.end])?;
+                Ok(())
+            }
+            _ => bail!("Source is not a socket"),
+        }
+    }
+
+    fn load_stream(&mut self, provider: SourceProvider, writable: bool, font: &Font) {
+        self.close_file();
+        // Don't pick up leftover data from a previous stream
+        self.stream_read_recv = None;
+        self.source = Some(Source {
+            provider,
+            attr: SourceAttributes {
+                seekable: false,
+                stream: true,
+                permissions: SourcePermissions {
+                    read: true,
+                    write: writable,
+                },
+            },
+            state: SourceState::default(),
+        });
+        self.args = Args::default();
+        if self.should_readjust_meta() {
+            self.new_file_readjust(font);
+        }
+    }
+
+    /// Use `data` as the current data, without a source to save it to
+    pub(crate) fn load_buffer(&mut self, data: Vec<u8>, font: &Font) {
+        self.close_file();
+        self.data = data.into();
+        self.args = Args::default();
+        if self.should_readjust_meta() {
+            self.new_file_readjust(font);
+        }
+    }
+
+    /// Load the `PT_LOAD` segments of an ELF core dump, laid out one after another.
+    ///
+    /// Returns the parsed core dump, for displaying the thread states.
+    pub(crate) fn load_elf_core(
+        &mut self,
+        path: PathBuf,
+        writable: bool,
+        font: &Font,
+    ) -> anyhow::Result<CoreDump> {
+        let mut file = open_file(&path, !writable)?;
+        let core = CoreDump::read(&mut file)?;
+        let mut data = Vec::new();
+        let mut map = AddrMap::default();
+        let mut file_offsets = Vec::new();
+        // Segments that weren't dumped (like read-only file mappings) have no data in the file
+        for seg in core.segments.iter().filter(|seg| seg.file_size != 0) {
+            let len = usize::try_from(seg.file_size)?;
+            let offset = map.push(core.segment_label(seg), usize::try_from(seg.vaddr)?, len);
+            data.resize(offset + len, 0);
+            file.seek(SeekFrom::Start(seg.file_offset))?;
+            file.read_exact(&mut data[offset..])
+                .context("Segment data is out of bounds")?;
+            file_offsets.push(seg.file_offset);
+        }
+        if data.is_empty() {
+            bail!("The core dump doesn't contain any memory");
+        }
+        self.load_segmented(
+            data,
+            map,
+            SourceProvider::ElfCore { file, file_offsets },
+            path,
+            writable,
+            font,
+        );
+        Ok(core)
+    }
+
+    /// Load an Intel HEX or S-record file, with a region for each block of contiguous records
+    pub(crate) fn load_record_file(&mut self, path: PathBuf, font: &Font) -> anyhow::Result<()> {
+        let (mut file, writable) = match open_file(&path, false) {
+            Ok(file) => (file, true),
+            Err(_) => (open_file(&path, true)?, false),
+        };
+        let mut text = String::new();
+        file.read_to_string(&mut text)?;
+        let format = RecordFormat::detect(&text).context("Not an Intel HEX or S-record file")?;
+        let parsed = ihex_srec::parse(&text, format)?;
+        if parsed.blocks.is_empty() {
+            bail!("The file doesn't contain any data records");
+        }
+        let mut data = Vec::new();
+        let mut map = AddrMap::default();
+        for block in parsed.blocks {
+            map.push(
+                format!("Block at {:X}", block.addr),
+                block.addr,
+                block.data.len(),
+            );
+            data.extend_from_slice(&block.data);
+        }
+        self.load_segmented(
+            data,
+            map,
+            SourceProvider::RecordFile {
+                file,
+                format,
+                record_size: parsed.record_size,
+                extra: parsed.extra,
+            },
+            path,
+            writable,
+            font,
+        );
+        Ok(())
+    }
+
+    /// Use `data`, which is made up of the segments of `map`, as the current data.
+    ///
+    /// Unless the meta is kept, there will be a region for each segment.
+    fn load_segmented(
+        &mut self,
+        data: Vec<u8>,
+        map: AddrMap,
+        provider: SourceProvider,
+        path: PathBuf,
+        writable: bool,
+        font: &Font,
+    ) {
+        self.data = data.into();
+        self.source = Some(Source {
+            provider,
+            attr: SourceAttributes {
+                seekable: false,
+                stream: false,
+                permissions: SourcePermissions {
+                    read: true,
+                    write: writable,
+                },
+            },
+            state: SourceState::default(),
+        });
+        self.args = Args {
+            src: SourceArgs {
+                file: Some(path),
+                jump: None,
+                hard_seek: None,
+                take: None,
+                read_only: !writable,
+                stream: false,
+            },
+            recent: false,
+            meta: None,
+        };
+        if self.should_readjust_meta() {
+            self.new_file_readjust(font);
+            for seg in &map.segments {
+                self.meta_state.meta.low.regions.insert(NamedRegion {
+                    name: seg.name.clone(),
+                    region: Region {
+                        begin: seg.offset,
+                        end: seg.offset + seg.len - 1,
+                    },
+                    desc: format!("{:X}..{:X}", seg.addr, seg.addr + seg.len),
+                    anchor: None,
+                });
+            }
+        }
+        self.addr_map = Some(map);
+    }
+
+    /// The sector size used for partition tables and the sector grid
+    pub fn sector_size(&self) -> usize {
+        let device = self.source.as_ref().and_then(|src| match &src.provider {
+            SourceProvider::BlockDevice { sector_size, .. } => Some(*sector_size),
+            _ => None,
+        });
+        device
+            .or(self.hex_ui.sector_grid)
+            .unwrap_or(block_dev::DEFAULT_SECTOR_SIZE)
+    }
+
+    /// Add a region for every partition in the partition table at the start of the data.
+    ///
+    /// Returns the number of regions added.
+    pub fn add_partition_regions(&mut self) -> usize {
+        let parts = block_dev::parse_partition_table(&self.data, self.sector_size());
+        for part in &parts {
+            self.meta_state.meta.low.regions.insert(NamedRegion {
+                name: part.name.clone(),
+                region: Region {
+                    begin: part.offset,
+                    end: part.offset + part.len - 1,
+                },
+                desc: part.desc.clone(),
+                anchor: None,
+            });
+        }
+        parts.len()
+    }
+
+    /// The real address of an offset.
+    ///
+    /// For sparse sources, this is the address the segment was loaded from,
+    /// otherwise it's the offset plus the hard seek.
+    pub fn offset_to_addr(&self, offset: usize) -> usize {
+        match &self.addr_map {
+            Some(map) => map.offset_to_addr(offset).unwrap_or(offset),
+            None => offset + self.args.src.hard_seek.unwrap_or(0),
+        }
+    }
+
+    /// How to show an offset in lists: the hexadecimal real address for sparse sources,
+    /// the decimal offset otherwise
+    pub fn offset_label(&self, offset: usize) -> String {
+        match &self.addr_map {
+            Some(map) => format!("{:X}", map.offset_to_addr(offset).unwrap_or(offset)),
+            None => offset.to_string(),
+        }
+    }
+
+    /// The offset of a real address, if it's loaded. Inverse of [`Self::offset_to_addr`].
+    pub fn addr_to_offset(&self, addr: usize) -> Option<usize> {
+        match &self.addr_map {
+            Some(map) => map.addr_to_offset(addr),
+            None => addr.checked_sub(self.args.src.hard_seek.unwrap_or(0)),
+        }
+    }
+
+    /// Anchor every bookmark and region that lies inside a module of the process
+    /// with the map ranges `ranges`. Returns how many were anchored.
+    pub fn anchor_to_modules(&mut self, ranges: &[proc_maps::MapRange]) -> usize {
+        let anchor_for = |offset| proc_mem::anchor_for_addr(ranges, self.offset_to_addr(offset));
+        let bm_anchors: Vec<_> = self
+            .meta_state
+            .meta
+            .bookmarks
+            .iter()
+            .map(|bm| anchor_for(bm.offset))
+            .collect();
+        let reg_anchors: Vec<_> = self
+            .meta_state
+            .meta
+            .low
+            .regions
+            .iter()
+            .map(|(key, reg)| (key, anchor_for(reg.region.begin)))
+            .collect();
+        let mut n_anchored = 0;
+        for (bm, anchor) in self.meta_state.meta.bookmarks.iter_mut().zip(bm_anchors) {
+            if anchor.is_some() {
+                bm.anchor = anchor;
+                n_anchored += 1;
+            }
+        }
+        for (key, anchor) in reg_anchors {
+            if anchor.is_some() {
+                self.meta_state.meta.low.regions[key].anchor = anchor;
+                n_anchored += 1;
+            }
+        }
+        n_anchored
+    }
+
+    /// Move anchored bookmarks and regions to where their anchors point to in the process
+    /// with the map ranges `ranges`. Returns how many anchors couldn't be resolved.
+    pub fn resolve_anchors(&mut self, ranges: &[proc_maps::MapRange]) -> usize {
+        let data_len = self.data.len();
+        let resolve = |anchor: &ModuleAnchor| {
+            proc_mem::resolve_anchor(ranges, anchor)
+                .and_then(|addr| self.addr_to_offset(addr))
+                .filter(|&off| off < data_len)
+        };
+        let bm_offsets: Vec<_> = self
+            .meta_state
+            .meta
+            .bookmarks
+            .iter()
+            .map(|bm| bm.anchor.as_ref().map(resolve))
+            .collect();
+        let reg_offsets: Vec<_> = self
+            .meta_state
+            .meta
+            .low
+            .regions
+            .iter()
+            .filter_map(|(key, reg)| Some((key, resolve(reg.anchor.as_ref()?))))
+            .collect();
+        let mut n_unresolved = 0;
+        for (bm, off) in self.meta_state.meta.bookmarks.iter_mut().zip(bm_offsets) {
+            match off {
+                Some(Some(off)) => bm.offset = off,
+                Some(None) => n_unresolved += 1,
+                None => {}
+            }
+        }
+        for (key, off) in reg_offsets {
+            let region = &mut self.meta_state.meta.low.regions[key].region;
+            match off {
+                Some(off) => {
+                    let len = region.end - region.begin;
+                    region.begin = off;
+                    region.end = (off + len).min(data_len - 1);
+                }
+                None => n_unresolved += 1,
+            }
+        }
+        n_unresolved
+    }
+
     pub fn consume_meta_from_file(&mut self, path: PathBuf) -> Result<(), anyhow::Error> {
         let data = std::fs::read(&path)?;
         let meta = rmp_serde::from_slice(&data)?;
         self.hex_ui.clear_meta_refs();
+        self.compare = None;
+        self.region_compare = None;
         self.meta_state.meta = meta;
         self.meta_state.clean_meta = self.meta_state.meta.clone();
         self.meta_state.current_meta_path = path;
@@ -635,12 +1828,6 @@ pub fn read_source_to_buf(path: &Path, args: &SourceArgs) -> Result<Vec<u8>, any
     Ok(buf)
 }
 
-pub struct FileDiffEntry {
-    pub my_val: u8,
-    pub file_val: u8,
-    pub offset: usize,
-}
-
 pub fn temp_metafile_backup_path() -> PathBuf {
     std::env::temp_dir().join("hexerator_meta_backup.meta")
 }
@@ -684,7 +1871,7 @@ fn load_file_from_src_args(
     src_args: &mut SourceArgs,
     cfg: &mut Config,
     source: &mut Option<Source>,
-    data: &mut Vec<u8>,
+    data: &mut Data,
     msg: &mut MessageDialog,
 ) -> bool {
     if let Some(file_arg) = &src_args.file {
@@ -705,7 +1892,9 @@ fn load_file_from_src_args(
         } else {
             let result: Result<(), anyhow::Error> = try {
                 let mut file = open_file(file_arg, src_args.read_only)?;
-                data.clear();
+                let sector_size =
+                    block_dev::is_block_device(&file).then(|| block_dev::sector_size(&file));
+                *data = Data::default();
                 if let Some(path) = &mut src_args.file {
                     match path.canonicalize() {
                         Ok(canon) => *path = canon,
@@ -723,10 +1912,16 @@ fn load_file_from_src_args(
                 }
                 cfg.recent.use_(src_args.clone());
                 if !src_args.stream {
-                    *data = read_contents(&*src_args, &mut file)?;
+                    *data = match sector_size {
+                        Some(sector_size) => read_block_device(&*src_args, &mut file, sector_size)?,
+                        None => read_contents(&*src_args, &mut file)?,
+                    };
                 }
                 *source = Some(Source {
-                    provider: SourceProvider::File(file),
+                    provider: match sector_size {
+                        Some(sector_size) => SourceProvider::BlockDevice { file, sector_size },
+                        None => SourceProvider::File(file),
+                    },
                     attr: SourceAttributes {
                         seekable: true,
                         stream: src_args.stream,
@@ -759,13 +1954,60 @@ fn open_file(path: &Path, read_only: bool) -> Result<File, anyhow::Error> {
         .context("Failed to open file")
 }
 
-fn read_contents(args: &SourceArgs, file: &mut File) -> anyhow::Result<Vec<u8>> {
+/// Replace the contents of the file at `path`, and open it again for writing.
+///
+/// The contents are written to a temporary file next to the original, which is then renamed
+/// over it, so the original stays intact if writing fails.
+fn replace_file(path: &Path, contents: &[u8]) -> anyhow::Result<File> {
+    let mut tmp_name = OsString::from(".");
+    tmp_name.push(path.file_name().context("Path has no file name")?);
+    tmp_name.push(".hexerator_tmp");
+    let tmp_path = path.with_file_name(tmp_name);
+    let result: anyhow::Result<()> = try {
+        let mut tmp = File::create(&tmp_path)?;
+        tmp.write_all(contents)?;
+        tmp.set_permissions(std::fs::metadata(path)?.permissions())?;
+        tmp.sync_all()?;
+        std::fs::rename(&tmp_path, path)?;
+    };
+    if let Err(e) = result {
+        let _ = std::fs::remove_file(&tmp_path);
+        return Err(e.context("Failed to replace file"));
+    }
+    open_file(path, false)
+}
+
+fn read_contents(args: &SourceArgs, file: &mut File) -> anyhow::Result<Data> {
     let seek = args.hard_seek.unwrap_or(0);
+    let meta = file.metadata()?;
+    // Special files (like the ones in /proc) report a length of 0, so they are never mapped
+    let avail = meta.len().saturating_sub(seek as u64);
+    let len = args.take.map_or(avail, |amount| avail.min(amount as u64));
+    if meta.is_file() && len >= MMAP_THRESHOLD {
+        return Ok(Data::map_file(file, seek as u64, usize::try_from(len)?)?);
+    }
     file.seek(SeekFrom::Start(seek as u64))?;
     let mut data = Vec::new();
     match args.take {
         Some(amount) => (&*file).take(amount as u64).read_to_end(&mut data)?,
         None => file.read_to_end(&mut data)?,
     };
-    Ok(data)
+    Ok(data.into())
+}
+
+fn read_block_device(
+    args: &SourceArgs,
+    file: &mut File,
+    sector_size: usize,
+) -> anyhow::Result<Data> {
+    let seek = args.hard_seek.unwrap_or(0);
+    let avail = block_dev::device_size(file)?.saturating_sub(seek as u64);
+    let len = usize::try_from(args.take.map_or(avail, |amount| avail.min(amount as u64)))?;
+    // Mapping is page aligned, so it reads whole sectors too
+    if len as u64 >= MMAP_THRESHOLD
+        && let Ok(data) = Data::map_file(file, seek as u64, len)
+    {
+        return Ok(data);
+    }
+    Ok(block_dev::read_aligned(file, seek, len, sector_size)?.into())
 }
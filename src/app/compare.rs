@@ -0,0 +1,174 @@
+use {
+    crate::meta::{region::Region, LayoutKey, PerspectiveKey, RegionKey, ViewKey},
+    std::path::PathBuf,
+};
+
+/// A second file shown next to the opened data, for comparing the two
+pub struct Compare {
+    pub path: PathBuf,
+    pub data: Vec<u8>,
+    /// Views that show `data` instead of the opened data, paired with the views they were
+    /// cloned from
+    pub views: Vec<(ViewKey, ViewKey)>,
+    /// Perspectives of the compare views, paired with the perspectives they were cloned from.
+    ///
+    /// Paired perspectives scroll together.
+    pub perspectives: Vec<(PerspectiveKey, PerspectiveKey)>,
+    /// Regions of the compare perspectives, sized to `data`, paired with the regions they
+    /// correspond to
+    pub regions: Vec<(RegionKey, RegionKey)>,
+    /// The side by side layout created for comparing
+    pub layout: LayoutKey,
+    /// The layout that was active before comparing, restored when done
+    pub prev_layout: LayoutKey,
+}
+
+impl Compare {
+    pub fn is_compare_view(&self, key: ViewKey) -> bool {
+        self.views.iter().any(|&(_, cmp)| cmp == key)
+    }
+    /// The compare view cloned from `key`, or the other way around
+    pub fn paired_view(&self, key: ViewKey) -> Option<ViewKey> {
+        paired(&self.views, key)
+    }
+    /// The perspective paired with `key`, if any
+    pub fn paired_perspective(&self, key: PerspectiveKey) -> Option<PerspectiveKey> {
+        paired(&self.perspectives, key)
+    }
+}
+
+fn paired<K: Copy + PartialEq>(pairs: &[(K, K)], key: K) -> Option<K> {
+    pairs.iter().find_map(|&(a, b)| match key {
+        _ if key == a => Some(b),
+        _ if key == b => Some(a),
+        _ => None,
+    })
+}
+
+/// The region of the compared file that corresponds to `region` of the data.
+///
+/// A region that reaches the end of the data reaches the end of the compared file too,
+/// so the bytes past the end of the shorter file can be seen.
+pub fn counterpart_region(region: Region, data_len: usize, cmp_len: usize) -> Region {
+    if region.end + 1 >= data_len {
+        Region {
+            begin: region.begin,
+            end: region.end.max(cmp_len.saturating_sub(1)),
+        }
+    } else {
+        region
+    }
+}
+
+/// Whether the byte at `idx` differs between `a` and `b`.
+///
+/// Bytes that only one side has count as different.
+pub fn differs(a: &[u8], b: &[u8], idx: usize) -> bool {
+    a.get(idx) != b.get(idx)
+}
+
+fn diff_len(a: &[u8], b: &[u8]) -> usize {
+    a.len().max(b.len())
+}
+
+fn starts_diff_run(a: &[u8], b: &[u8], idx: usize) -> bool {
+    differs(a, b, idx) && (idx == 0 || !differs(a, b, idx - 1))
+}
+
+/// Start of the first run of differing bytes after `from`
+pub fn next_diff(a: &[u8], b: &[u8], from: usize) -> Option<usize> {
+    (from + 1..diff_len(a, b)).find(|&idx| starts_diff_run(a, b, idx))
+}
+
+/// Start of the last run of differing bytes before `from`
+pub fn prev_diff(a: &[u8], b: &[u8], from: usize) -> Option<usize> {
+    (0..from.min(diff_len(a, b)))
+        .rev()
+        .find(|&idx| starts_diff_run(a, b, idx))
+}
+
+/// Number of differing bytes
+pub fn count_diffs(a: &[u8], b: &[u8]) -> usize {
+    a.iter().zip(b).filter(|(x, y)| x != y).count() + a.len().abs_diff(b.len())
+}
+
+/// Metadata created for showing two regions of the same data side by side
+pub struct RegionCompareViews {
+    pub views: Vec<ViewKey>,
+    pub perspectives: Vec<PerspectiveKey>,
+    /// Regions that were created for selections, as opposed to existing named regions
+    pub regions: Vec<RegionKey>,
+    pub layout: LayoutKey,
+    pub prev_layout: LayoutKey,
+}
+
+/// The part of `data` that `region` covers, clipped to the data
+pub fn region_slice(data: &[u8], region: Region) -> &[u8] {
+    let end = (region.end + 1).min(data.len());
+    data.get(region.begin..end).unwrap_or_default()
+}
+
+/// Offsets relative to the region starts at which regions `a` and `b` of `data` differ
+pub fn region_diffs(data: &[u8], a: Region, b: Region) -> Vec<usize> {
+    let (a, b) = (region_slice(data, a), region_slice(data, b));
+    (0..diff_len(a, b))
+        .filter(|&idx| differs(a, b, idx))
+        .collect()
+}
+
+/// Whether the byte at `idx` is in region `a` or `b`, and differs from its counterpart in the
+/// other one
+pub fn region_differs(data: &[u8], a: Region, b: Region, idx: usize) -> bool {
+    let rel = if a.contains(idx) {
+        idx - a.begin
+    } else if b.contains(idx) {
+        idx - b.begin
+    } else {
+        return false;
+    };
+    differs(region_slice(data, a), region_slice(data, b), rel)
+}
+
+#[test]
+fn test_diff_nav() {
+    let a = b"abcdefgh";
+    let b = b"aXXdeYgh12";
+    assert_eq!(next_diff(a, b, 0), Some(1));
+    // Skips the rest of the run the cursor is in
+    assert_eq!(next_diff(a, b, 1), Some(5));
+    assert_eq!(next_diff(a, b, 5), Some(8));
+    assert_eq!(next_diff(a, b, 8), None);
+    assert_eq!(prev_diff(a, b, 8), Some(5));
+    assert_eq!(prev_diff(a, b, 5), Some(1));
+    assert_eq!(prev_diff(a, b, 2), Some(1));
+    assert_eq!(prev_diff(a, b, 1), None);
+    assert_eq!(count_diffs(a, b), 5);
+    assert!(!differs(a, b, 3));
+}
+
+#[test]
+fn test_counterpart_region() {
+    let whole = Region { begin: 0, end: 9 };
+    assert_eq!(
+        counterpart_region(whole, 10, 20),
+        Region { begin: 0, end: 19 }
+    );
+    assert_eq!(counterpart_region(whole, 10, 4), whole);
+    let part = Region { begin: 2, end: 5 };
+    assert_eq!(counterpart_region(part, 10, 20), part);
+}
+
+#[test]
+fn test_region_diffs() {
+    let data = b"HEADabcdHEADaXcdef";
+    let a = Region { begin: 4, end: 7 };
+    let b = Region { begin: 12, end: 17 };
+    assert_eq!(region_diffs(data, a, b), [1, 4, 5]);
+    assert!(region_differs(data, a, b, 5));
+    assert!(region_differs(data, a, b, 13));
+    assert!(!region_differs(data, a, b, 4));
+    assert!(!region_differs(data, a, b, 0));
+    // Clipped to the data
+    let c = Region { begin: 16, end: 30 };
+    assert_eq!(region_slice(data, c), b"ef");
+}
@@ -0,0 +1,45 @@
+use {
+    super::{
+        compare::{Compare, RegionCompareViews},
+        edit_state::EditState,
+    },
+    crate::{
+        addr_map::AddrMap, args::Args, change_history::ChangeRecorder, data::Data,
+        heatmap::Heatmap, hex_ui::HexUi, meta_state::MetaState, source::Source,
+        watch_list::WatchList,
+    },
+    std::sync::mpsc::Receiver,
+};
+
+/// An open document that's not in the active tab.
+///
+/// The document of the active tab lives in [`App`](super::App) itself, and is swapped with
+/// one of these when switching tabs. Background documents are not reloaded or streamed into.
+#[derive(Default)]
+pub struct Document {
+    pub data: Data,
+    pub edit_state: EditState,
+    pub args: Args,
+    pub source: Option<Source>,
+    pub addr_map: Option<AddrMap>,
+    pub stream_read_recv: Option<Receiver<Vec<u8>>>,
+    pub hex_ui: HexUi,
+    pub meta_state: MetaState,
+    pub watch_list: WatchList,
+    pub compression_offer: bool,
+    pub compare: Option<Compare>,
+    pub region_compare: Option<RegionCompareViews>,
+    pub change_recorder: ChangeRecorder,
+    pub heatmap: Heatmap,
+}
+
+/// Name of the tab of a document opened with `args`
+pub fn tab_label(args: &Args) -> String {
+    match &args.src.file {
+        Some(path) => path.file_name().map_or_else(
+            || path.display().to_string(),
+            |name| name.to_string_lossy().into_owned(),
+        ),
+        None => "Untitled".into(),
+    }
+}
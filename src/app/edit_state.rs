@@ -10,6 +10,8 @@ pub struct EditState {
     cursor_history: Vec<usize>,
     cursor_history_current: usize,
     pub dirty_region: Option<Region>,
+    /// The length of the data was changed by applying a patch, so saving has to resize the file
+    pub resized: bool,
 }
 
 impl EditState {
@@ -24,13 +26,13 @@ impl EditState {
     pub fn set_cursor_no_history(&mut self, offset: usize) {
         self.cursor = offset;
     }
-    /// Step cursor forward without saving history
-    pub fn step_cursor_forward(&mut self) {
-        self.cursor += 1;
+    /// Step cursor forward by `step` bytes without saving history
+    pub fn step_cursor_forward(&mut self, step: usize) {
+        self.cursor += step;
     }
-    /// Step cursor back without saving history
-    pub fn step_cursor_back(&mut self) {
-        self.cursor = self.cursor.saturating_sub(1)
+    /// Step cursor back by `step` bytes without saving history
+    pub fn step_cursor_back(&mut self, step: usize) {
+        self.cursor = self.cursor.saturating_sub(step)
     }
     /// Offset cursor by amount, not saving history
     pub fn offset_cursor(&mut self, amount: usize) {
@@ -0,0 +1,50 @@
+/// Bit number of the digit at `i`, for digits written most significant bit first unless
+/// `lsb_first`
+fn bit_of_digit(i: usize, lsb_first: bool) -> usize {
+    if lsb_first {
+        i
+    } else {
+        7 - i
+    }
+}
+
+pub fn byte_to_bin_digits(byte: u8, lsb_first: bool) -> [u8; 8] {
+    std::array::from_fn(|i| {
+        if (byte >> bit_of_digit(i, lsb_first)) & 1 == 1 {
+            b'1'
+        } else {
+            b'0'
+        }
+    })
+}
+
+#[test]
+fn test_byte_to_bin_digits() {
+    assert_eq!(byte_to_bin_digits(0b1000_0110, false), *b"10000110");
+    assert_eq!(byte_to_bin_digits(0b1000_0110, true), *b"01100001");
+    assert_eq!(byte_to_bin_digits(0, false), *b"00000000");
+    assert_eq!(byte_to_bin_digits(255, true), *b"11111111");
+}
+
+pub fn bin_digits_to_byte(digits: &[u8], lsb_first: bool) -> Option<u8> {
+    if digits.len() != 8 {
+        return None;
+    }
+    let mut byte = 0;
+    for (i, digit) in digits.iter().enumerate() {
+        match digit {
+            b'0' => {}
+            b'1' => byte |= 1 << bit_of_digit(i, lsb_first),
+            _ => return None,
+        }
+    }
+    Some(byte)
+}
+
+#[test]
+fn test_bin_digits_to_byte() {
+    assert_eq!(bin_digits_to_byte(b"10000110", false), Some(0b1000_0110));
+    assert_eq!(bin_digits_to_byte(b"01100001", true), Some(0b1000_0110));
+    assert_eq!(bin_digits_to_byte(b"0110", false), None);
+    assert_eq!(bin_digits_to_byte(b"0110000x", false), None);
+}
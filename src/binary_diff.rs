@@ -0,0 +1,318 @@
+//! Binary diff that aligns the two sides, so insertions and deletions don't make
+//! everything after them different
+
+use std::{collections::HashMap, ops::Range};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HunkKind {
+    Equal,
+    /// Same length on both sides, different bytes
+    Changed,
+    /// Only in `a`
+    Inserted,
+    /// Only in `b`
+    Deleted,
+}
+
+impl HunkKind {
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Equal => "equal",
+            Self::Changed => "changed",
+            Self::Inserted => "inserted",
+            Self::Deleted => "deleted",
+        }
+    }
+}
+
+/// A range of `a` and the range of `b` it corresponds to
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Hunk {
+    pub kind: HunkKind,
+    pub a: Range<usize>,
+    pub b: Range<usize>,
+}
+
+/// Size of the blocks of `b` that are looked up in `a` to find matching data
+const BLOCK: usize = 32;
+/// Gaps between matched blocks are aligned byte by byte if they are at most this many cells
+const MAX_ALIGN_CELLS: usize = 1 << 20;
+/// Shorter equal runs inside a gap are counted as changed, to avoid fragmented hunks
+const MIN_EQUAL: usize = 4;
+
+/// Diff `a` against `b`, returning the hunks that make up both in order
+pub fn diff(a: &[u8], b: &[u8]) -> Vec<Hunk> {
+    let mut hunks = Vec::new();
+    let prefix = a.iter().zip(b).take_while(|(x, y)| x == y).count();
+    let suffix = a[prefix..]
+        .iter()
+        .rev()
+        .zip(b[prefix..].iter().rev())
+        .take_while(|(x, y)| x == y)
+        .count();
+    push(&mut hunks, HunkKind::Equal, 0..prefix, 0..prefix);
+    diff_middle(
+        a,
+        b,
+        prefix..a.len() - suffix,
+        prefix..b.len() - suffix,
+        &mut hunks,
+    );
+    push(
+        &mut hunks,
+        HunkKind::Equal,
+        a.len() - suffix..a.len(),
+        b.len() - suffix..b.len(),
+    );
+    hunks
+}
+
+/// Push a hunk, merging it with the previous one if they are of the same kind
+fn push(hunks: &mut Vec<Hunk>, kind: HunkKind, a: Range<usize>, b: Range<usize>) {
+    if a.is_empty() && b.is_empty() {
+        return;
+    }
+    if let Some(last) = hunks.last_mut()
+        && last.kind == kind
+        && last.a.end == a.start
+        && last.b.end == b.start
+    {
+        last.a.end = a.end;
+        last.b.end = b.end;
+        return;
+    }
+    hunks.push(Hunk { kind, a, b });
+}
+
+struct RollingHash {
+    value: u64,
+    /// `BASE` to the power of `BLOCK - 1`, for removing the outgoing byte
+    out_factor: u64,
+}
+
+impl RollingHash {
+    const BASE: u64 = 257;
+    fn new(block: &[u8]) -> Self {
+        let value = block.iter().fold(0u64, |acc, &b| {
+            acc.wrapping_mul(Self::BASE).wrapping_add(u64::from(b))
+        });
+        let out_factor = (1..block.len()).fold(1u64, |acc, _| acc.wrapping_mul(Self::BASE));
+        Self { value, out_factor }
+    }
+    fn roll(&mut self, out: u8, inc: u8) {
+        self.value = self
+            .value
+            .wrapping_sub(u64::from(out).wrapping_mul(self.out_factor))
+            .wrapping_mul(Self::BASE)
+            .wrapping_add(u64::from(inc));
+    }
+}
+
+/// Find blocks of `b` in `a`, keeping the matches in order, and align the gaps between them
+fn diff_middle(a: &[u8], b: &[u8], ar: Range<usize>, br: Range<usize>, hunks: &mut Vec<Hunk>) {
+    if ar.len() < BLOCK || br.len() < BLOCK {
+        diff_gap(a, b, ar, br, hunks);
+        return;
+    }
+    let mut index: HashMap<u64, Vec<usize>> = HashMap::new();
+    for pos in (br.start..=br.end - BLOCK).step_by(BLOCK) {
+        index
+            .entry(RollingHash::new(&b[pos..pos + BLOCK]).value)
+            .or_default()
+            .push(pos);
+    }
+    // Start of the gap after the last match
+    let (mut a_pos, mut b_pos) = (ar.start, br.start);
+    let mut i = ar.start;
+    let mut hash = RollingHash::new(&a[i..i + BLOCK]);
+    while i + BLOCK <= ar.end {
+        let found = index.get(&hash.value).and_then(|candidates| {
+            // Prefer the candidate closest to where the block would be if nothing moved
+            let expected = b_pos + (i - a_pos);
+            let idx = candidates.partition_point(|&pos| pos < expected);
+            [idx.checked_sub(1), Some(idx)]
+                .into_iter()
+                .flatten()
+                .filter_map(|idx| candidates.get(idx).copied())
+                .filter(|&pos| pos >= b_pos && a[i..i + BLOCK] == b[pos..pos + BLOCK])
+                .min_by_key(|pos| pos.abs_diff(expected))
+        });
+        if let Some(pos) = found {
+            let (mut start_a, mut start_b) = (i, pos);
+            while start_a > a_pos && start_b > b_pos && a[start_a - 1] == b[start_b - 1] {
+                start_a -= 1;
+                start_b -= 1;
+            }
+            let (mut end_a, mut end_b) = (i + BLOCK, pos + BLOCK);
+            while end_a < ar.end && end_b < br.end && a[end_a] == b[end_b] {
+                end_a += 1;
+                end_b += 1;
+            }
+            diff_gap(a, b, a_pos..start_a, b_pos..start_b, hunks);
+            push(hunks, HunkKind::Equal, start_a..end_a, start_b..end_b);
+            (a_pos, b_pos, i) = (end_a, end_b, end_a);
+            if i + BLOCK <= ar.end {
+                hash = RollingHash::new(&a[i..i + BLOCK]);
+            }
+            continue;
+        }
+        if i + BLOCK < ar.end {
+            hash.roll(a[i], a[i + BLOCK]);
+        }
+        i += 1;
+    }
+    diff_gap(a, b, a_pos..ar.end, b_pos..br.end, hunks);
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Op {
+    Equal,
+    Insert,
+    Delete,
+}
+
+/// Diff a gap between matches, aligning it byte by byte if it's small enough
+fn diff_gap(a: &[u8], b: &[u8], ar: Range<usize>, br: Range<usize>, hunks: &mut Vec<Hunk>) {
+    let (n, m) = (ar.len(), br.len());
+    if n == 0 || m == 0 || n * m > MAX_ALIGN_CELLS {
+        push_changed(hunks, ar, br);
+        return;
+    }
+    // Longest common subsequence table of the suffixes
+    let mut lcs = vec![0u32; (n + 1) * (m + 1)];
+    let at = |i: usize, j: usize| i * (m + 1) + j;
+    for i in (0..n).rev() {
+        for j in (0..m).rev() {
+            lcs[at(i, j)] = if a[ar.start + i] == b[br.start + j] {
+                lcs[at(i + 1, j + 1)] + 1
+            } else {
+                lcs[at(i + 1, j)].max(lcs[at(i, j + 1)])
+            };
+        }
+    }
+    let mut ops = Vec::with_capacity(n + m);
+    let (mut i, mut j) = (0, 0);
+    while i < n || j < m {
+        if i < n && j < m && a[ar.start + i] == b[br.start + j] {
+            ops.push(Op::Equal);
+            i += 1;
+            j += 1;
+        } else if j == m || (i < n && lcs[at(i + 1, j)] >= lcs[at(i, j + 1)]) {
+            ops.push(Op::Insert);
+            i += 1;
+        } else {
+            ops.push(Op::Delete);
+            j += 1;
+        }
+    }
+    // Turn the operations into hunks, with short equal runs counting as changed
+    let (mut pa, mut pb) = (ar.start, br.start);
+    let (mut ca, mut cb) = (ar.start, br.start);
+    let mut rest = &ops[..];
+    while let Some(&op) = rest.first() {
+        let len = rest.iter().take_while(|&&x| x == op).count();
+        rest = &rest[len..];
+        match op {
+            Op::Equal if len >= MIN_EQUAL => {
+                push_changed(hunks, pa..ca, pb..cb);
+                push(hunks, HunkKind::Equal, ca..ca + len, cb..cb + len);
+                ca += len;
+                cb += len;
+                (pa, pb) = (ca, cb);
+            }
+            Op::Equal => {
+                ca += len;
+                cb += len;
+            }
+            Op::Insert => ca += len,
+            Op::Delete => cb += len,
+        }
+    }
+    push_changed(hunks, pa..ca, pb..cb);
+}
+
+/// Push differing ranges as a changed hunk of the common length, and the rest as inserted or
+/// deleted
+fn push_changed(hunks: &mut Vec<Hunk>, a: Range<usize>, b: Range<usize>) {
+    let common = a.len().min(b.len());
+    let (a_mid, b_mid) = (a.start + common, b.start + common);
+    push(hunks, HunkKind::Changed, a.start..a_mid, b.start..b_mid);
+    push(hunks, HunkKind::Inserted, a_mid..a.end, b_mid..b_mid);
+    push(hunks, HunkKind::Deleted, a.end..a.end, b_mid..b.end);
+}
+
+/// The differing hunk whose `a` side contains `idx`, if any. `hunks` must be in order.
+pub fn hunk_at(hunks: &[Hunk], idx: usize) -> Option<&Hunk> {
+    let first = hunks.partition_point(|h| h.a.end <= idx);
+    hunks[first..]
+        .iter()
+        .take_while(|h| h.a.start <= idx)
+        .find(|h| h.kind != HunkKind::Equal && h.a.contains(&idx))
+}
+
+/// Start offset in `a` of the next (or previous) differing hunk from `cursor`
+pub fn next_hunk(hunks: &[Hunk], cursor: usize, forward: bool) -> Option<usize> {
+    let mut starts = hunks
+        .iter()
+        .filter(|h| h.kind != HunkKind::Equal)
+        .map(|h| h.a.start);
+    if forward {
+        starts.find(|&start| start > cursor)
+    } else {
+        starts.rev().find(|&start| start < cursor)
+    }
+}
+
+#[cfg(test)]
+fn kinds(hunks: &[Hunk]) -> Vec<(HunkKind, usize, usize)> {
+    hunks
+        .iter()
+        .map(|h| (h.kind, h.a.len(), h.b.len()))
+        .collect()
+}
+
+#[test]
+fn test_insert_delete() {
+    let b: Vec<u8> = (0..=250).cycle().step_by(7).take(2000).collect();
+    let mut a = b.clone();
+    a.insert(500, 0xFF);
+    a.drain(1500..1510);
+    a[1800] ^= 1;
+    use HunkKind::*;
+    assert_eq!(
+        kinds(&diff(&a, &b)),
+        [
+            (Equal, 500, 500),
+            (Inserted, 1, 0),
+            (Equal, 999, 999),
+            (Deleted, 0, 10),
+            (Equal, 300, 300),
+            (Changed, 1, 1),
+            (Equal, 190, 190)
+        ]
+    );
+}
+
+#[test]
+fn test_trailing() {
+    use HunkKind::*;
+    assert_eq!(
+        kinds(&diff(b"abcdef", b"abc")),
+        [(Equal, 3, 3), (Inserted, 3, 0)]
+    );
+    assert_eq!(kinds(&diff(b"", b"xy")), [(Deleted, 0, 2)]);
+    assert_eq!(kinds(&diff(b"same", b"same")), [(Equal, 4, 4)]);
+}
+
+#[test]
+fn test_hunk_nav() {
+    let hunks = diff(b"aXcdefgYYh", b"abcdefgh");
+    assert_eq!(hunk_at(&hunks, 1).map(|h| h.kind), Some(HunkKind::Changed));
+    assert!(hunk_at(&hunks, 2).is_none());
+    assert_eq!(hunk_at(&hunks, 8).map(|h| h.kind), Some(HunkKind::Inserted));
+    assert_eq!(next_hunk(&hunks, 0, true), Some(1));
+    assert_eq!(next_hunk(&hunks, 1, true), Some(7));
+    assert_eq!(next_hunk(&hunks, 7, true), None);
+    assert_eq!(next_hunk(&hunks, 7, false), Some(1));
+    assert_eq!(next_hunk(&hunks, 1, false), None);
+}
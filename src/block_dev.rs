@@ -0,0 +1,463 @@
+//! Block devices (disks, partitions, loop devices), and MBR/GPT partition tables
+
+use std::{
+    fs::File,
+    io::{Read, Seek, SeekFrom, Write},
+};
+
+/// Sector size to assume when the device can't tell
+pub const DEFAULT_SECTOR_SIZE: usize = 512;
+
+/// Amount of sectors read or written at once
+const CHUNK_SECTORS: usize = 2048;
+
+pub fn is_block_device(file: &File) -> bool {
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::FileTypeExt;
+        file.metadata()
+            .map(|meta| meta.file_type().is_block_device())
+            .unwrap_or(false)
+    }
+    #[cfg(not(unix))]
+    {
+        let _ = file;
+        false
+    }
+}
+
+/// The logical sector size of a block device
+pub fn sector_size(file: &File) -> usize {
+    #[cfg(target_os = "linux")]
+    {
+        use std::os::unix::io::AsRawFd;
+        let mut size: libc::c_int = 0;
+        // SAFETY: BLKSSZGET writes a single int to the pointer we pass
+        let ret = unsafe { libc::ioctl(file.as_raw_fd(), libc::BLKSSZGET, &mut size) };
+        if ret == 0
+            && let Ok(size) = usize::try_from(size)
+            && size != 0
+        {
+            return size;
+        }
+    }
+    #[cfg(not(target_os = "linux"))]
+    let _ = file;
+    DEFAULT_SECTOR_SIZE
+}
+
+/// The size of a block device in bytes.
+///
+/// The metadata of block devices reports a length of 0, but they can be seeked to the end.
+pub fn device_size(file: &mut File) -> std::io::Result<u64> {
+    file.seek(SeekFrom::End(0))
+}
+
+fn align_down(pos: usize, sector_size: usize) -> usize {
+    pos - pos % sector_size
+}
+
+fn align_up(pos: usize, sector_size: usize) -> usize {
+    align_down(pos + sector_size - 1, sector_size)
+}
+
+/// Read `len` bytes at `offset`, only ever reading whole sectors
+pub fn read_aligned(
+    file: &mut File,
+    offset: usize,
+    len: usize,
+    sector_size: usize,
+) -> std::io::Result<Vec<u8>> {
+    let start = align_down(offset, sector_size);
+    let end = align_up(offset + len, sector_size);
+    file.seek(SeekFrom::Start(start as u64))?;
+    let mut buf = vec![0; end - start];
+    for chunk in buf.chunks_mut(sector_size * CHUNK_SECTORS) {
+        file.read_exact(chunk)?;
+    }
+    buf.drain(..offset - start);
+    buf.truncate(len);
+    Ok(buf)
+}
+
+/// Write `data` at `offset`, only ever writing whole sectors.
+///
+/// Partially overwritten sectors at the edges are read first, so their other bytes are kept.
+pub fn write_aligned(
+    file: &mut File,
+    offset: usize,
+    data: &[u8],
+    sector_size: usize,
+) -> std::io::Result<()> {
+    let start = align_down(offset, sector_size);
+    let end = align_up(offset + data.len(), sector_size);
+    let mut buf = vec![0; end - start];
+    if start != offset {
+        file.seek(SeekFrom::Start(start as u64))?;
+        file.read_exact(&mut buf[..sector_size])?;
+    }
+    if end != offset + data.len() {
+        let last = end - sector_size;
+        file.seek(SeekFrom::Start(last as u64))?;
+        file.read_exact(&mut buf[last - start..])?;
+    }
+    buf[offset - start..offset - start + data.len()].copy_from_slice(data);
+    file.seek(SeekFrom::Start(start as u64))?;
+    for chunk in buf.chunks(sector_size * CHUNK_SECTORS) {
+        file.write_all(chunk)?;
+    }
+    file.flush()
+}
+
+/// A partition, or a partition table structure
+#[derive(Debug, PartialEq, Eq)]
+pub struct Partition {
+    pub name: String,
+    pub desc: String,
+    /// Offset in bytes
+    pub offset: usize,
+    /// Length in bytes
+    pub len: usize,
+}
+
+/// MBR partition types that point to a chain of extended boot records
+const MBR_EXTENDED: [u8; 3] = [0x05, 0x0F, 0x85];
+/// MBR partition type of the protective partition covering a GPT disk
+const MBR_GPT_PROTECTIVE: u8 = 0xEE;
+/// Upper limit on followed EBR links, in case they form a loop
+const MAX_LOGICAL: usize = 128;
+
+/// Parse the MBR or GPT partition table at the start of `data`.
+///
+/// Partitions extending past the end of `data` are cut off.
+pub fn parse_partition_table(data: &[u8], sector_size: usize) -> Vec<Partition> {
+    let mut parts = Vec::new();
+    let Some(mbr) = data.get(..512) else {
+        return parts;
+    };
+    if mbr[510..] != [0x55, 0xAA] {
+        return parts;
+    }
+    let entries = mbr_entries(mbr);
+    if entries.iter().any(|e| e.ty == MBR_GPT_PROTECTIVE) {
+        parse_gpt(data, sector_size, &mut parts);
+        if !parts.is_empty() {
+            return parts;
+        }
+    }
+    push_part(
+        &mut parts,
+        data,
+        "MBR".into(),
+        "Master boot record".into(),
+        0,
+        512,
+    );
+    let mut logical_n = 5;
+    for (i, entry) in entries.iter().enumerate() {
+        if entry.ty == 0 {
+            continue;
+        }
+        let offset = entry.lba * sector_size as u64;
+        let len = entry.n_sectors * sector_size as u64;
+        push_part(
+            &mut parts,
+            data,
+            format!("Partition {}", i + 1),
+            format!("MBR type {:02X}", entry.ty),
+            offset,
+            len,
+        );
+        if MBR_EXTENDED.contains(&entry.ty) {
+            parse_ebr_chain(data, sector_size, entry.lba, &mut logical_n, &mut parts);
+        }
+    }
+    parts
+}
+
+struct MbrEntry {
+    ty: u8,
+    lba: u64,
+    n_sectors: u64,
+}
+
+fn mbr_entries(sector: &[u8]) -> Vec<MbrEntry> {
+    sector[446..510]
+        .chunks_exact(16)
+        .map(|e| MbrEntry {
+            ty: e[4],
+            lba: u64::from(u32::from_le_bytes([e[8], e[9], e[10], e[11]])),
+            n_sectors: u64::from(u32::from_le_bytes([e[12], e[13], e[14], e[15]])),
+        })
+        .collect()
+}
+
+/// Follow the extended boot records of an extended partition starting at `ext_lba`
+fn parse_ebr_chain(
+    data: &[u8],
+    sector_size: usize,
+    ext_lba: u64,
+    logical_n: &mut usize,
+    parts: &mut Vec<Partition>,
+) {
+    let mut ebr_lba = ext_lba;
+    for _ in 0..MAX_LOGICAL {
+        let Some(ebr) = sector_at(data, ebr_lba, sector_size) else {
+            return;
+        };
+        if ebr.len() < 512 || ebr[510..512] != [0x55, 0xAA] {
+            return;
+        }
+        let entries = mbr_entries(ebr);
+        let (logical, next) = (&entries[0], &entries[1]);
+        if logical.ty != 0 {
+            push_part(
+                parts,
+                data,
+                format!("Partition {logical_n}"),
+                format!("MBR type {:02X} (logical)", logical.ty),
+                (ebr_lba + logical.lba) * sector_size as u64,
+                logical.n_sectors * sector_size as u64,
+            );
+            *logical_n += 1;
+        }
+        if next.ty == 0 {
+            return;
+        }
+        ebr_lba = ext_lba + next.lba;
+    }
+}
+
+fn parse_gpt(data: &[u8], sector_size: usize, parts: &mut Vec<Partition>) {
+    let Some(hdr) = sector_at(data, 1, sector_size) else {
+        return;
+    };
+    if hdr.len() < 92 || &hdr[..8] != b"EFI PART" {
+        return;
+    }
+    let entries_lba = read_u64(&hdr[72..]);
+    let n_entries = u64::from(read_u32(&hdr[80..]));
+    let entry_size = u64::from(read_u32(&hdr[84..]));
+    if entry_size < 128 {
+        return;
+    }
+    push_part(
+        parts,
+        data,
+        "Protective MBR".into(),
+        "Master boot record of a GPT disk".into(),
+        0,
+        512,
+    );
+    push_part(
+        parts,
+        data,
+        "GPT header".into(),
+        "GUID partition table header".into(),
+        sector_size as u64,
+        sector_size as u64,
+    );
+    let entries_offset = entries_lba.saturating_mul(sector_size as u64);
+    push_part(
+        parts,
+        data,
+        "GPT entries".into(),
+        format!("{n_entries} partition entries of {entry_size} bytes"),
+        entries_offset,
+        n_entries * entry_size,
+    );
+    for i in 0..n_entries {
+        let Ok(pos) = usize::try_from(entries_offset.saturating_add(i * entry_size)) else {
+            return;
+        };
+        let Some(entry) = data.get(pos..pos + 128) else {
+            return;
+        };
+        if entry[..16].iter().all(|&b| b == 0) {
+            continue;
+        }
+        let first = read_u64(&entry[32..]);
+        let last = read_u64(&entry[40..]);
+        if last < first {
+            continue;
+        }
+        let units: Vec<u16> = entry[56..128]
+            .chunks_exact(2)
+            .map(|pair| u16::from_le_bytes([pair[0], pair[1]]))
+            .take_while(|&u| u != 0)
+            .collect();
+        let name = match String::from_utf16_lossy(&units) {
+            name if name.is_empty() => format!("Partition {}", i + 1),
+            name => name,
+        };
+        push_part(
+            parts,
+            data,
+            name,
+            format!(
+                "GPT partition {}, type {}",
+                i + 1,
+                format_guid(&entry[..16])
+            ),
+            first.saturating_mul(sector_size as u64),
+            (last - first)
+                .saturating_add(1)
+                .saturating_mul(sector_size as u64),
+        );
+    }
+}
+
+fn sector_at(data: &[u8], lba: u64, sector_size: usize) -> Option<&[u8]> {
+    let pos = usize::try_from(lba).ok()?.checked_mul(sector_size)?;
+    data.get(pos..pos.checked_add(sector_size)?.min(data.len()))
+}
+
+/// Add a partition, cutting it off at the end of `data`. Empty results are skipped.
+fn push_part(
+    parts: &mut Vec<Partition>,
+    data: &[u8],
+    name: String,
+    desc: String,
+    offset: u64,
+    len: u64,
+) {
+    let Ok(offset) = usize::try_from(offset) else {
+        return;
+    };
+    if offset >= data.len() || len == 0 {
+        return;
+    }
+    let len = usize::try_from(len).map_or(data.len() - offset, |len| len.min(data.len() - offset));
+    parts.push(Partition {
+        name,
+        desc,
+        offset,
+        len,
+    });
+}
+
+fn read_u32(bytes: &[u8]) -> u32 {
+    u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]])
+}
+
+fn read_u64(bytes: &[u8]) -> u64 {
+    u64::from(read_u32(bytes)) | (u64::from(read_u32(&bytes[4..])) << 32)
+}
+
+/// Format a GUID stored in the mixed endian on-disk layout
+fn format_guid(b: &[u8]) -> String {
+    format!(
+        "{:08X}-{:04X}-{:04X}-{:02X}{:02X}-{:02X}{:02X}{:02X}{:02X}{:02X}{:02X}",
+        read_u32(b),
+        u16::from_le_bytes([b[4], b[5]]),
+        u16::from_le_bytes([b[6], b[7]]),
+        b[8],
+        b[9],
+        b[10],
+        b[11],
+        b[12],
+        b[13],
+        b[14],
+        b[15]
+    )
+}
+
+#[cfg(test)]
+fn mbr_entry(ty: u8, lba: u32, n_sectors: u32) -> [u8; 16] {
+    let mut e = [0; 16];
+    e[4] = ty;
+    e[8..12].copy_from_slice(&lba.to_le_bytes());
+    e[12..].copy_from_slice(&n_sectors.to_le_bytes());
+    e
+}
+
+#[test]
+fn test_parse_mbr() {
+    let mut disk = vec![0; 512 * 16];
+    disk[446..462].copy_from_slice(&mbr_entry(0x83, 1, 4));
+    disk[462..478].copy_from_slice(&mbr_entry(0x05, 8, 8));
+    disk[510..512].copy_from_slice(&[0x55, 0xAA]);
+    // EBR at sector 8, with a logical partition at sector 9 and no further links
+    let ebr = 512 * 8;
+    disk[ebr + 446..ebr + 462].copy_from_slice(&mbr_entry(0x83, 1, 2));
+    disk[ebr + 510..ebr + 512].copy_from_slice(&[0x55, 0xAA]);
+    let parts = parse_partition_table(&disk, 512);
+    let layout: Vec<_> = parts
+        .iter()
+        .map(|p| (p.name.as_str(), p.offset, p.len))
+        .collect();
+    assert_eq!(
+        layout,
+        [
+            ("MBR", 0, 512),
+            ("Partition 1", 512, 2048),
+            ("Partition 2", 4096, 4096),
+            ("Partition 5", 4608, 1024),
+        ]
+    );
+}
+
+#[test]
+fn test_parse_gpt() {
+    let mut disk = vec![0; 512 * 8];
+    disk[446..462].copy_from_slice(&mbr_entry(MBR_GPT_PROTECTIVE, 1, 7));
+    disk[510..512].copy_from_slice(&[0x55, 0xAA]);
+    let hdr = 512;
+    disk[hdr..hdr + 8].copy_from_slice(b"EFI PART");
+    disk[hdr + 72..hdr + 80].copy_from_slice(&2u64.to_le_bytes());
+    disk[hdr + 80..hdr + 84].copy_from_slice(&4u32.to_le_bytes());
+    disk[hdr + 84..hdr + 88].copy_from_slice(&128u32.to_le_bytes());
+    // Second entry is used, the rest are empty
+    let entry = 512 * 2 + 128;
+    disk[entry] = 0xAF;
+    disk[entry + 32..entry + 40].copy_from_slice(&4u64.to_le_bytes());
+    disk[entry + 40..entry + 48].copy_from_slice(&5u64.to_le_bytes());
+    for (i, c) in "boot".encode_utf16().enumerate() {
+        disk[entry + 56 + i * 2..entry + 58 + i * 2].copy_from_slice(&c.to_le_bytes());
+    }
+    let parts = parse_partition_table(&disk, 512);
+    let layout: Vec<_> = parts
+        .iter()
+        .map(|p| (p.name.as_str(), p.offset, p.len))
+        .collect();
+    assert_eq!(
+        layout,
+        [
+            ("Protective MBR", 0, 512),
+            ("GPT header", 512, 512),
+            ("GPT entries", 1024, 512),
+            ("boot", 2048, 1024),
+        ]
+    );
+    assert_eq!(
+        parts[3].desc,
+        "GPT partition 2, type 000000AF-0000-0000-0000-000000000000"
+    );
+}
+
+#[test]
+#[allow(clippy::unwrap_used)]
+fn test_aligned_io() {
+    let path = std::env::temp_dir().join("hexerator_test_aligned_io");
+    let mut file = std::fs::OpenOptions::new()
+        .read(true)
+        .write(true)
+        .create(true)
+        .truncate(true)
+        .open(&path)
+        .unwrap();
+    let orig: Vec<u8> = (0..2048u32)
+        .map(|i| u8::try_from(i % 251).unwrap())
+        .collect();
+    file.write_all(&orig).unwrap();
+    assert_eq!(
+        read_aligned(&mut file, 500, 30, 512).unwrap(),
+        orig[500..530]
+    );
+    write_aligned(&mut file, 510, &[0xFF; 4], 512).unwrap();
+    let mut expected = orig;
+    expected[510..514].fill(0xFF);
+    assert_eq!(read_aligned(&mut file, 0, 2048, 512).unwrap(), expected);
+    drop(file);
+    std::fs::remove_file(path).unwrap();
+}
@@ -0,0 +1,142 @@
+//! Timeline of the changes between consecutive reloads of the data
+
+use std::{
+    fmt::Write,
+    time::{Duration, Instant},
+};
+
+/// A run of bytes that changed between two reloads
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Change {
+    pub offset: usize,
+    pub old: Vec<u8>,
+    pub new: Vec<u8>,
+    /// Time since recording started
+    pub time: Duration,
+    /// Number of the reload this change was seen in
+    pub reload: u32,
+}
+
+/// Records the changes between reloads, while enabled
+pub struct ChangeRecorder {
+    pub enabled: bool,
+    pub changes: Vec<Change>,
+    /// Oldest changes are dropped when there are more than this many
+    pub max_changes: usize,
+    /// Number of changes dropped because of `max_changes`
+    pub dropped: usize,
+    reloads: u32,
+    start: Instant,
+}
+
+impl Default for ChangeRecorder {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            changes: Vec::new(),
+            max_changes: 100_000,
+            dropped: 0,
+            reloads: 0,
+            start: Instant::now(),
+        }
+    }
+}
+
+impl ChangeRecorder {
+    pub fn clear(&mut self) {
+        self.changes.clear();
+        self.dropped = 0;
+        self.reloads = 0;
+        self.start = Instant::now();
+    }
+    /// Record the differences between the data before and after a reload
+    pub fn record(&mut self, old: &[u8], new: &[u8]) {
+        if self.changes.is_empty() && self.reloads == 0 {
+            self.start = Instant::now();
+        }
+        self.reloads += 1;
+        let time = self.start.elapsed();
+        let len = old.len().min(new.len());
+        let mut i = 0;
+        while i < len {
+            if old[i] == new[i] {
+                i += 1;
+                continue;
+            }
+            let begin = i;
+            while i < len && old[i] != new[i] {
+                i += 1;
+            }
+            self.changes.push(Change {
+                offset: begin,
+                old: old[begin..i].to_vec(),
+                new: new[begin..i].to_vec(),
+                time,
+                reload: self.reloads,
+            });
+        }
+        if self.changes.len() > self.max_changes {
+            let excess = self.changes.len() - self.max_changes;
+            self.changes.drain(..excess);
+            self.dropped += excess;
+        }
+    }
+    /// The timeline as CSV, one change per line
+    pub fn to_csv(&self) -> String {
+        let mut out = String::from("time_s,reload,offset,old,new\n");
+        // Writing to a String can't fail, so the results are ignored
+        for change in &self.changes {
+            let _ = writeln!(
+                out,
+                "{:.3},{},{:#x},{},{}",
+                change.time.as_secs_f64(),
+                change.reload,
+                change.offset,
+                hex(&change.old),
+                hex(&change.new)
+            );
+        }
+        out
+    }
+}
+
+pub fn hex(bytes: &[u8]) -> String {
+    let mut s = String::with_capacity(bytes.len() * 3);
+    for (i, b) in bytes.iter().enumerate() {
+        if i != 0 {
+            s.push(' ');
+        }
+        let _ = write!(s, "{b:02X}");
+    }
+    s
+}
+
+#[test]
+fn test_record() {
+    let mut rec = ChangeRecorder::default();
+    rec.record(b"abcdef", b"aXYdeZ");
+    rec.record(b"aXYdeZ", b"aXYdeZ");
+    rec.record(b"aXYdeZ", b"bXYdeZ");
+    let summary: Vec<_> = rec
+        .changes
+        .iter()
+        .map(|c| (c.offset, c.old.clone(), c.new.clone(), c.reload))
+        .collect();
+    assert_eq!(
+        summary,
+        [
+            (1, b"bc".to_vec(), b"XY".to_vec(), 1),
+            (5, b"f".to_vec(), b"Z".to_vec(), 1),
+            (0, b"a".to_vec(), b"b".to_vec(), 3),
+        ]
+    );
+    assert!(rec
+        .to_csv()
+        .lines()
+        .nth(1)
+        .unwrap_or("")
+        .ends_with(",1,0x1,62 63,58 59"));
+    rec.max_changes = 2;
+    rec.record(b"bXYdeZ", b"cXYdeZ");
+    assert_eq!((rec.changes.len(), rec.dropped), (2, 2));
+}
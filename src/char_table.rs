@@ -0,0 +1,166 @@
+//! Custom character tables (`.tbl` files), commonly used for the text of old games
+
+use {
+    crate::text_conv::Decoded,
+    anyhow::{bail, Context},
+    serde::{Deserialize, Serialize},
+    std::{collections::HashMap, path::Path},
+};
+
+/// How far back to start decoding to find out where the character under an offset begins
+const LOOKBACK: usize = 32;
+
+/// Maps byte sequences to text
+#[derive(Debug, PartialEq, Eq, Serialize, Deserialize, Clone)]
+pub struct CharTable {
+    /// Usually the name of the file the table was loaded from
+    pub name: String,
+    entries: HashMap<Vec<u8>, String>,
+    /// Length of the longest byte sequence
+    max_len: usize,
+}
+
+impl CharTable {
+    pub fn load(path: &Path) -> anyhow::Result<Self> {
+        let text = std::fs::read_to_string(path).context("Table files need to be UTF-8")?;
+        let name = path.file_name().map_or_else(
+            || path.display().to_string(),
+            |name| name.to_string_lossy().into_owned(),
+        );
+        Self::parse(name, &text)
+    }
+    /// Parse the contents of a table file.
+    ///
+    /// Besides `XX=text` entries, `*XX` line break and `/XX` end of string entries are
+    /// supported. Line breaks decode to `\n`, and end of string entries without text to `\0`.
+    pub fn parse(name: String, text: &str) -> anyhow::Result<Self> {
+        let mut entries = HashMap::new();
+        for (i, line) in text.trim_start_matches('\u{FEFF}').lines().enumerate() {
+            let line = line.trim_end_matches('\r');
+            let (line, default) = match line.as_bytes().first() {
+                Some(b'*') => (&line[1..], Some("\n")),
+                Some(b'/') => (&line[1..], Some("\0")),
+                // Comments, and table switching or other features that aren't supported
+                Some(b'#' | b';' | b'$' | b'@' | b'!' | b'(') | None => continue,
+                Some(_) => (line, None),
+            };
+            let (hex, value) = match (line.split_once('='), default) {
+                (Some((hex, _)), Some("\n")) => (hex, "\n"),
+                (Some((hex, value)), _) => (hex, value),
+                (None, Some(default)) => (line, default),
+                (None, None) => bail!("Line {}: Expected `=`", i + 1),
+            };
+            let bytes = parse_hex(hex.trim()).with_context(|| format!("Line {}", i + 1))?;
+            if value.is_empty() {
+                continue;
+            }
+            entries.insert(bytes, value.to_owned());
+        }
+        let max_len = entries.keys().map(Vec::len).max().unwrap_or(0);
+        if max_len == 0 {
+            bail!("The table has no entries");
+        }
+        Ok(Self {
+            name,
+            entries,
+            max_len,
+        })
+    }
+    /// The longest entry at the start of `data`, and its length in bytes
+    fn entry_at(&self, data: &[u8]) -> Option<(&str, usize)> {
+        (1..=self.max_len.min(data.len()))
+            .rev()
+            .find_map(|len| Some((self.entries.get(&data[..len])?.as_str(), len)))
+    }
+    /// Decode the entry at `idx`. Entries with more than one character show their first one.
+    pub fn decode_at(&self, data: &[u8], idx: usize) -> Decoded {
+        // Decode from a bit earlier to find out where the entries begin
+        let mut pos = if self.max_len == 1 {
+            idx
+        } else {
+            idx.saturating_sub(LOOKBACK)
+        };
+        loop {
+            let Some((text, len)) = self.entry_at(&data[pos..]) else {
+                if pos == idx {
+                    return Decoded::Invalid;
+                }
+                pos += 1;
+                continue;
+            };
+            if pos == idx {
+                // Entries with empty text are skipped when parsing
+                return text.chars().next().map_or(Decoded::Invalid, Decoded::Char);
+            }
+            if pos + len > idx {
+                return Decoded::Continuation;
+            }
+            pos += len;
+        }
+    }
+    /// Encode `text` by repeatedly taking the longest entry it starts with.
+    ///
+    /// Returns `None` if some part of `text` isn't in the table.
+    pub fn encode(&self, mut text: &str) -> Option<Vec<u8>> {
+        let mut out = Vec::new();
+        while !text.is_empty() {
+            let (bytes, value) = self
+                .entries
+                .iter()
+                .filter(|(_, value)| text.starts_with(value.as_str()))
+                // Prefer the shortest byte sequence (and then the lowest) for the same text
+                .max_by(|(b1, v1), (b2, v2)| {
+                    v1.len()
+                        .cmp(&v2.len())
+                        .then_with(|| b2.len().cmp(&b1.len()).then(b2.cmp(b1)))
+                })?;
+            out.extend_from_slice(bytes);
+            text = &text[value.len()..];
+        }
+        Some(out)
+    }
+}
+
+fn parse_hex(hex: &str) -> anyhow::Result<Vec<u8>> {
+    if hex.is_empty() || hex.len() % 2 == 1 || !hex.is_ascii() {
+        bail!("Invalid byte sequence `{hex}`");
+    }
+    (0..hex.len())
+        .step_by(2)
+        .map(|i| {
+            u8::from_str_radix(&hex[i..i + 2], 16)
+                .with_context(|| format!("Invalid byte sequence `{hex}`"))
+        })
+        .collect()
+}
+
+#[test]
+#[allow(clippy::unwrap_used)]
+fn test_char_table() {
+    let table = CharTable::parse(
+        "test".into(),
+        "\u{FEFF}00=A\r\n01=B\n; comment\n0102=th\n*FE\n/FF=<END>\n$80=ignored\n10==\n",
+    )
+    .unwrap();
+    let data = [0x00, 0x01, 0x02, 0x01, 0xFE, 0x33, 0xFF];
+    let decoded: Vec<_> = (0..data.len()).map(|i| table.decode_at(&data, i)).collect();
+    assert_eq!(
+        decoded,
+        [
+            Decoded::Char('A'),
+            Decoded::Char('t'),
+            Decoded::Continuation,
+            Decoded::Char('B'),
+            Decoded::Char('\n'),
+            Decoded::Invalid,
+            Decoded::Char('<')
+        ]
+    );
+    assert_eq!(
+        table.encode("ABth\n=<END>").unwrap(),
+        [0x00, 0x01, 0x01, 0x02, 0xFE, 0x10, 0xFF]
+    );
+    assert_eq!(table.encode("C"), None);
+    assert!(CharTable::parse("bad".into(), "0=A").is_err());
+    assert!(CharTable::parse("empty".into(), "").is_err());
+}
@@ -0,0 +1,150 @@
+use std::{
+    io::{Read, Write},
+    ops::RangeInclusive,
+};
+
+/// A compression format that sources can be transparently decompressed from
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Algorithm {
+    Gzip,
+    Zstd,
+    Xz,
+    Bzip2,
+}
+
+/// How a decompressed source should be compressed again when saving
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Compression {
+    pub algo: Algorithm,
+    pub level: u32,
+}
+
+impl Algorithm {
+    /// Detect the compression format from the magic bytes at the start of `data`
+    pub fn detect(data: &[u8]) -> Option<Self> {
+        if data.starts_with(&[0x1F, 0x8B]) {
+            Some(Self::Gzip)
+        } else if data.starts_with(&[0x28, 0xB5, 0x2F, 0xFD]) {
+            Some(Self::Zstd)
+        } else if data.starts_with(&[0xFD, b'7', b'z', b'X', b'Z', 0x00]) {
+            Some(Self::Xz)
+        } else if data.starts_with(b"BZh") && data.get(3).is_some_and(|b| (b'1'..=b'9').contains(b))
+        {
+            Some(Self::Bzip2)
+        } else {
+            None
+        }
+    }
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Gzip => "gzip",
+            Self::Zstd => "zstd",
+            Self::Xz => "xz",
+            Self::Bzip2 => "bzip2",
+        }
+    }
+    pub fn levels(self) -> RangeInclusive<u32> {
+        match self {
+            Self::Gzip | Self::Xz => 0..=9,
+            Self::Zstd => 1..=22,
+            Self::Bzip2 => 1..=9,
+        }
+    }
+    /// Guess the level `data` was compressed with.
+    ///
+    /// Gzip and bzip2 record it (roughly) in their header. For zstd and xz, the default
+    /// level of their command line tools is returned.
+    pub fn guess_level(self, data: &[u8]) -> u32 {
+        match self {
+            // The XFL header field
+            Self::Gzip => match data.get(8) {
+                Some(2) => 9,
+                Some(4) => 1,
+                _ => 6,
+            },
+            Self::Zstd => 3,
+            Self::Xz => 6,
+            // The block size digit after "BZh"
+            Self::Bzip2 => data
+                .get(3)
+                .and_then(|digit| digit.checked_sub(b'0'))
+                .filter(|level| (1..=9).contains(level))
+                .map_or(9, u32::from),
+        }
+    }
+    pub fn decompress(self, data: &[u8]) -> std::io::Result<Vec<u8>> {
+        let mut out = Vec::new();
+        match self {
+            Self::Gzip => flate2::read::MultiGzDecoder::new(data).read_to_end(&mut out)?,
+            Self::Zstd => zstd::stream::read::Decoder::new(data)?.read_to_end(&mut out)?,
+            Self::Xz => xz2::read::XzDecoder::new_multi_decoder(data).read_to_end(&mut out)?,
+            Self::Bzip2 => bzip2::read::MultiBzDecoder::new(data).read_to_end(&mut out)?,
+        };
+        Ok(out)
+    }
+}
+
+impl Compression {
+    pub fn compress(self, data: &[u8]) -> std::io::Result<Vec<u8>> {
+        let level = self
+            .level
+            .clamp(*self.algo.levels().start(), *self.algo.levels().end());
+        match self.algo {
+            Algorithm::Gzip => {
+                let mut enc =
+                    flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::new(level));
+                enc.write_all(data)?;
+                enc.finish()
+            }
+            Algorithm::Zstd => {
+                // Levels only go up to 22, so this always fits
+                let level = i32::try_from(level).unwrap_or(3);
+                zstd::stream::encode_all(data, level)
+            }
+            Algorithm::Xz => {
+                let mut enc = xz2::write::XzEncoder::new(Vec::new(), level);
+                enc.write_all(data)?;
+                enc.finish()
+            }
+            Algorithm::Bzip2 => {
+                let mut enc =
+                    bzip2::write::BzEncoder::new(Vec::new(), bzip2::Compression::new(level));
+                enc.write_all(data)?;
+                enc.finish()
+            }
+        }
+    }
+}
+
+#[test]
+#[allow(clippy::unwrap_used)]
+fn test_roundtrip() {
+    let data: Vec<u8> = b"Hexerator compression roundtrip. ".repeat(64);
+    for algo in [
+        Algorithm::Gzip,
+        Algorithm::Zstd,
+        Algorithm::Xz,
+        Algorithm::Bzip2,
+    ] {
+        let comp = Compression { algo, level: 9 };
+        let compressed = comp.compress(&data).unwrap();
+        assert_eq!(Algorithm::detect(&compressed), Some(algo));
+        assert_eq!(algo.decompress(&compressed).unwrap(), data);
+    }
+    assert_eq!(Algorithm::detect(b"plain data"), None);
+}
+
+#[test]
+#[allow(clippy::unwrap_used)]
+fn test_guess_level() {
+    let data = [0u8; 256];
+    for (algo, level, expected) in [
+        (Algorithm::Gzip, 9, 9),
+        (Algorithm::Gzip, 1, 1),
+        (Algorithm::Bzip2, 4, 4),
+        (Algorithm::Zstd, 19, 3),
+    ] {
+        let compressed = Compression { algo, level }.compress(&data).unwrap();
+        assert_eq!(algo.guess_level(&compressed), expected);
+    }
+}
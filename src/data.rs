@@ -0,0 +1,87 @@
+use {
+    memmap2::MmapMut,
+    std::{
+        fs::File,
+        ops::{Deref, DerefMut},
+    },
+};
+
+/// The data being viewed and edited.
+///
+/// Large files are memory mapped copy-on-write, so only the pages that are actually
+/// looked at are read from disk, and edits stay in memory until they are saved.
+pub enum Data {
+    Owned(Vec<u8>),
+    Mmap(MmapMut),
+}
+
+/// Files at least this large are memory mapped instead of read into memory
+pub const MMAP_THRESHOLD: u64 = 64 * 1024 * 1024;
+
+impl Data {
+    /// Map `len` bytes of `file` starting at `offset`, copy-on-write
+    pub fn map_file(file: &File, offset: u64, len: usize) -> std::io::Result<Self> {
+        // SAFETY: The mapping is private, so our edits don't affect the file.
+        // If the file is truncated by someone else while it's mapped, accessing the
+        // missing pages will crash, which is the price to pay for not reading everything.
+        let map = unsafe {
+            memmap2::MmapOptions::new()
+                .offset(offset)
+                .len(len)
+                .map_copy(file)?
+        };
+        Ok(Self::Mmap(map))
+    }
+    pub fn is_mapped(&self) -> bool {
+        matches!(self, Self::Mmap(_))
+    }
+    /// Access the data as a `Vec`, for growing it.
+    ///
+    /// Returns `None` if the data is memory mapped, because that would mean copying it
+    /// into memory.
+    pub fn vec_mut(&mut self) -> Option<&mut Vec<u8>> {
+        match self {
+            Self::Owned(vec) => Some(vec),
+            Self::Mmap(_) => None,
+        }
+    }
+    /// A copy of the data as it is now, for comparing against later.
+    ///
+    /// This is always copied into memory, even for memory mapped data, because a mapping
+    /// would show later changes to the file.
+    pub fn snapshot(&self) -> Self {
+        Self::Owned(self.to_vec())
+    }
+}
+
+impl Default for Data {
+    fn default() -> Self {
+        Self::Owned(Vec::new())
+    }
+}
+
+impl From<Vec<u8>> for Data {
+    fn from(vec: Vec<u8>) -> Self {
+        Self::Owned(vec)
+    }
+}
+
+impl Deref for Data {
+    type Target = [u8];
+
+    fn deref(&self) -> &[u8] {
+        match self {
+            Self::Owned(vec) => vec,
+            Self::Mmap(map) => map,
+        }
+    }
+}
+
+impl DerefMut for Data {
+    fn deref_mut(&mut self) -> &mut [u8] {
+        match self {
+            Self::Owned(vec) => vec,
+            Self::Mmap(map) => map,
+        }
+    }
+}
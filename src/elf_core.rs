@@ -0,0 +1,381 @@
+//! Parsing of ELF core dumps, like the ones written by the kernel or `gcore`
+
+use {
+    anyhow::{bail, Context},
+    std::{
+        io::{Read, Seek, SeekFrom},
+        path::PathBuf,
+    },
+};
+
+const PT_LOAD: u32 = 1;
+const PT_NOTE: u32 = 4;
+const NT_PRSTATUS: u32 = 1;
+const NT_FILE: u32 = 0x4649_4c45;
+const EM_386: u16 = 3;
+const EM_X86_64: u16 = 62;
+const EM_AARCH64: u16 = 183;
+
+/// A `PT_LOAD` segment of a core dump
+#[derive(Debug, Clone)]
+pub struct LoadSegment {
+    pub vaddr: u64,
+    /// Offset of the segment's data in the core file
+    pub file_offset: u64,
+    /// How many bytes of the segment are stored in the core file
+    pub file_size: u64,
+    /// `PF_*` flags
+    pub flags: u32,
+}
+
+/// A file mapped into the address space of the dumped process, from the `NT_FILE` note
+#[derive(Debug)]
+pub struct FileMapping {
+    pub start: u64,
+    pub end: u64,
+    pub path: PathBuf,
+}
+
+/// State of a thread at the time of the dump, from an `NT_PRSTATUS` note
+#[derive(Debug)]
+pub struct ThreadStatus {
+    pub pid: u32,
+    /// The signal that caused the dump
+    pub signal: u16,
+    /// Register names and values. Empty if the architecture isn't known.
+    pub regs: Vec<(&'static str, u64)>,
+}
+
+#[derive(Debug)]
+pub struct CoreDump {
+    pub segments: Vec<LoadSegment>,
+    pub files: Vec<FileMapping>,
+    pub threads: Vec<ThreadStatus>,
+}
+
+/// Little endian reader for 32 or 64 bit ELF structures
+struct Reader<'a> {
+    data: &'a [u8],
+    is_64: bool,
+}
+
+impl Reader<'_> {
+    fn bytes<const N: usize>(&self, offset: usize) -> anyhow::Result<[u8; N]> {
+        let bytes = offset
+            .checked_add(N)
+            .and_then(|end| self.data.get(offset..end))
+            .with_context(|| format!("Unexpected end of data at {offset:X}"))?;
+        Ok(bytes.try_into()?)
+    }
+    fn u16(&self, offset: usize) -> anyhow::Result<u16> {
+        self.bytes(offset).map(u16::from_le_bytes)
+    }
+    fn u32(&self, offset: usize) -> anyhow::Result<u32> {
+        self.bytes(offset).map(u32::from_le_bytes)
+    }
+    fn u64(&self, offset: usize) -> anyhow::Result<u64> {
+        self.bytes(offset).map(u64::from_le_bytes)
+    }
+    fn word_size(&self) -> usize {
+        if self.is_64 {
+            8
+        } else {
+            4
+        }
+    }
+    /// Read a word, which is 32 or 64 bits depending on the ELF class
+    fn word(&self, offset: usize) -> anyhow::Result<u64> {
+        if self.is_64 {
+            self.u64(offset)
+        } else {
+            self.u32(offset).map(u64::from)
+        }
+    }
+    fn usize_word(&self, offset: usize) -> anyhow::Result<usize> {
+        Ok(usize::try_from(self.word(offset)?)?)
+    }
+}
+
+impl CoreDump {
+    /// Read the headers and notes of a core dump.
+    ///
+    /// The memory in the `PT_LOAD` segments isn't read.
+    pub fn read<F: Read + Seek>(file: &mut F) -> anyhow::Result<Self> {
+        // The 64 bit header is the larger one
+        let header = read_at(file, 0, 64)?;
+        if header.get(..4) != Some(b"\x7fELF") {
+            bail!("Not an ELF file");
+        }
+        let is_64 = match header.get(4) {
+            Some(1) => false,
+            Some(2) => true,
+            _ => bail!("Unknown ELF class"),
+        };
+        if header.get(5) != Some(&1) {
+            bail!("Only little endian core files are supported");
+        }
+        let r = Reader {
+            data: &header,
+            is_64,
+        };
+        if r.u16(16)? != 4 {
+            bail!("Not a core file");
+        }
+        let machine = r.u16(18)?;
+        let (phoff, phentsize, phnum) = if is_64 {
+            (r.word(32)?, r.u16(54)?, r.u16(56)?)
+        } else {
+            (r.word(28)?, r.u16(42)?, r.u16(44)?)
+        };
+        let table = read_at(file, phoff, u64::from(phentsize) * u64::from(phnum))?;
+        let r = Reader {
+            data: &table,
+            is_64,
+        };
+        let mut core = Self {
+            segments: Vec::new(),
+            files: Vec::new(),
+            threads: Vec::new(),
+        };
+        for i in 0..usize::from(phnum) {
+            let ph = i * usize::from(phentsize);
+            // (type, flags, offset, vaddr, filesz)
+            let (p_type, flags, offset, vaddr, file_size) = if is_64 {
+                (
+                    r.u32(ph)?,
+                    r.u32(ph + 4)?,
+                    r.u64(ph + 8)?,
+                    r.u64(ph + 16)?,
+                    r.u64(ph + 32)?,
+                )
+            } else {
+                let word = |off| r.u32(ph + off).map(u64::from);
+                (r.u32(ph)?, r.u32(ph + 24)?, word(4)?, word(8)?, word(16)?)
+            };
+            match p_type {
+                PT_LOAD => core.segments.push(LoadSegment {
+                    vaddr,
+                    file_offset: offset,
+                    file_size,
+                    flags,
+                }),
+                PT_NOTE => {
+                    let notes = read_at(file, offset, file_size)?;
+                    let r = Reader {
+                        data: &notes,
+                        is_64,
+                    };
+                    core.parse_notes(&r, 0, notes.len(), machine)?;
+                }
+                _ => {}
+            }
+        }
+        Ok(core)
+    }
+    fn parse_notes(
+        &mut self,
+        r: &Reader,
+        mut pos: usize,
+        end: usize,
+        machine: u16,
+    ) -> anyhow::Result<()> {
+        let align4 = |n: usize| {
+            n.checked_add(3)
+                .map(|n| n & !3)
+                .context("Note is truncated")
+        };
+        while pos + 12 <= end {
+            let name_size = usize::try_from(r.u32(pos)?)?;
+            let desc_size = usize::try_from(r.u32(pos + 4)?)?;
+            let note_type = r.u32(pos + 8)?;
+            let desc = (pos + 12)
+                .checked_add(align4(name_size)?)
+                .context("Note is truncated")?;
+            let desc_end = desc
+                .checked_add(desc_size)
+                .filter(|&desc_end| desc_end <= end)
+                .context("Note is truncated")?;
+            pos = align4(desc_end)?;
+            match note_type {
+                NT_PRSTATUS => self
+                    .threads
+                    .push(parse_prstatus(r, desc, desc_size, machine)?),
+                NT_FILE => self.parse_nt_file(r, desc, desc_end)?,
+                _ => {}
+            }
+        }
+        Ok(())
+    }
+    fn parse_nt_file(&mut self, r: &Reader, desc: usize, end: usize) -> anyhow::Result<()> {
+        let truncated = "NT_FILE note is truncated";
+        let ws = r.word_size();
+        let entry_size = 3 * ws;
+        let count = r.usize_word(desc)?;
+        // Skip count and page size
+        let entries = desc.checked_add(2 * ws).context(truncated)?;
+        // Every entry needs room for its addresses and offset, and the names come after
+        if entries > end || count > (end - entries) / entry_size {
+            bail!(truncated);
+        }
+        let mut name_pos = count
+            .checked_mul(entry_size)
+            .and_then(|size| entries.checked_add(size))
+            .context(truncated)?;
+        for i in 0..count {
+            let entry = entries + i * entry_size;
+            let name_bytes = r.data.get(name_pos..end).context(truncated)?;
+            let len = name_bytes
+                .iter()
+                .position(|&b| b == 0)
+                .context("Unterminated file name in NT_FILE note")?;
+            self.files.push(FileMapping {
+                start: r.word(entry)?,
+                end: r.word(entry + ws)?,
+                path: PathBuf::from(String::from_utf8_lossy(&name_bytes[..len]).into_owned()),
+            });
+            name_pos += len + 1;
+        }
+        Ok(())
+    }
+    /// The file mapped at `addr`, if any
+    pub fn file_at(&self, addr: u64) -> Option<&FileMapping> {
+        self.files.iter().find(|f| f.start <= addr && addr < f.end)
+    }
+    /// Label for a segment, like `/usr/lib/libc.so.6 r-x`
+    pub fn segment_label(&self, seg: &LoadSegment) -> String {
+        format!(
+            "{} {}{}{}",
+            self.file_at(seg.vaddr)
+                .map_or_else(|| String::from("[anon]"), |f| f.path.display().to_string()),
+            if seg.flags & 4 != 0 { 'r' } else { '-' },
+            if seg.flags & 2 != 0 { 'w' } else { '-' },
+            if seg.flags & 1 != 0 { 'x' } else { '-' },
+        )
+    }
+}
+
+/// Read up to `len` bytes at `offset`. Near the end of the file, fewer bytes are returned.
+fn read_at<F: Read + Seek>(file: &mut F, offset: u64, len: u64) -> std::io::Result<Vec<u8>> {
+    file.seek(SeekFrom::Start(offset))?;
+    let mut buf = Vec::new();
+    file.by_ref().take(len).read_to_end(&mut buf)?;
+    Ok(buf)
+}
+
+fn parse_prstatus(
+    r: &Reader,
+    desc: usize,
+    desc_size: usize,
+    machine: u16,
+) -> anyhow::Result<ThreadStatus> {
+    // Offsets into `struct elf_prstatus`
+    let (pid_off, regs_off) = if r.is_64 { (32, 112) } else { (24, 72) };
+    let names: &[&'static str] = match machine {
+        EM_X86_64 => &[
+            "r15", "r14", "r13", "r12", "rbp", "rbx", "r11", "r10", "r9", "r8", "rax", "rcx",
+            "rdx", "rsi", "rdi", "orig_rax", "rip", "cs", "eflags", "rsp", "ss", "fs_base",
+            "gs_base", "ds", "es", "fs", "gs",
+        ],
+        EM_AARCH64 => &[
+            "x0", "x1", "x2", "x3", "x4", "x5", "x6", "x7", "x8", "x9", "x10", "x11", "x12", "x13",
+            "x14", "x15", "x16", "x17", "x18", "x19", "x20", "x21", "x22", "x23", "x24", "x25",
+            "x26", "x27", "x28", "x29", "x30", "sp", "pc", "pstate",
+        ],
+        EM_386 => &[
+            "ebx", "ecx", "edx", "esi", "edi", "ebp", "eax", "ds", "es", "fs", "gs", "orig_eax",
+            "eip", "cs", "eflags", "esp", "ss",
+        ],
+        _ => &[],
+    };
+    let ws = r.word_size();
+    if regs_off + names.len() * ws > desc_size {
+        bail!("NT_PRSTATUS note is too small");
+    }
+    let mut regs = Vec::with_capacity(names.len());
+    for (i, &name) in names.iter().enumerate() {
+        regs.push((name, r.word(desc + regs_off + i * ws)?));
+    }
+    Ok(ThreadStatus {
+        pid: r.u32(desc + pid_off)?,
+        signal: r.u16(desc + 12)?,
+        regs,
+    })
+}
+
+/// Build a minimal 64 bit x86_64 core file with one load segment
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+fn test_core() -> Vec<u8> {
+    fn note(out: &mut Vec<u8>, ty: u32, desc: &[u8]) {
+        out.extend_from_slice(&5u32.to_le_bytes());
+        out.extend_from_slice(&u32::try_from(desc.len()).unwrap().to_le_bytes());
+        out.extend_from_slice(&ty.to_le_bytes());
+        out.extend_from_slice(b"CORE\0\0\0\0");
+        out.extend_from_slice(desc);
+        out.resize((out.len() + 3) & !3, 0);
+    }
+    let mut notes = Vec::new();
+    let mut prstatus = vec![0; 112 + 27 * 8];
+    prstatus[12..14].copy_from_slice(&11u16.to_le_bytes());
+    prstatus[32..36].copy_from_slice(&1234u32.to_le_bytes());
+    // rip
+    prstatus[112 + 16 * 8..112 + 17 * 8].copy_from_slice(&0x40_1000u64.to_le_bytes());
+    note(&mut notes, NT_PRSTATUS, &prstatus);
+    let mut nt_file = Vec::new();
+    for word in [1u64, 0x1000, 0x40_0000, 0x40_2000, 0] {
+        nt_file.extend_from_slice(&word.to_le_bytes());
+    }
+    nt_file.extend_from_slice(b"/bin/test\0");
+    note(&mut notes, NT_FILE, &nt_file);
+    let phoff = 64;
+    let notes_off = phoff + 2 * 56;
+    let load_off = notes_off + notes.len();
+    let mut out = vec![0; 64];
+    out[..6].copy_from_slice(b"\x7fELF\x02\x01");
+    out[16..18].copy_from_slice(&4u16.to_le_bytes());
+    out[18..20].copy_from_slice(&EM_X86_64.to_le_bytes());
+    out[32..40].copy_from_slice(&(phoff as u64).to_le_bytes());
+    out[54..56].copy_from_slice(&56u16.to_le_bytes());
+    out[56..58].copy_from_slice(&2u16.to_le_bytes());
+    for (ty, flags, offset, vaddr, size) in [
+        (PT_NOTE, 0u32, notes_off, 0u64, notes.len()),
+        (PT_LOAD, 5, load_off, 0x40_1000, 4),
+    ] {
+        let mut ph = [0; 56];
+        ph[..4].copy_from_slice(&ty.to_le_bytes());
+        ph[4..8].copy_from_slice(&flags.to_le_bytes());
+        ph[8..16].copy_from_slice(&(offset as u64).to_le_bytes());
+        ph[16..24].copy_from_slice(&vaddr.to_le_bytes());
+        ph[32..40].copy_from_slice(&(size as u64).to_le_bytes());
+        out.extend_from_slice(&ph);
+    }
+    out.extend_from_slice(&notes);
+    out.extend_from_slice(&[0xDE, 0xAD, 0xBE, 0xEF]);
+    out
+}
+
+#[test]
+#[allow(clippy::unwrap_used)]
+fn test_parse_core() {
+    let core = CoreDump::read(&mut std::io::Cursor::new(test_core())).unwrap();
+    assert_eq!(core.segments.len(), 1);
+    let seg = &core.segments[0];
+    assert_eq!((seg.vaddr, seg.file_size), (0x40_1000, 4));
+    assert_eq!(core.segment_label(seg), "/bin/test r-x");
+    assert_eq!(core.threads.len(), 1);
+    let thread = &core.threads[0];
+    assert_eq!((thread.pid, thread.signal), (1234, 11));
+    assert!(thread.regs.contains(&("rip", 0x40_1000)));
+    assert!(CoreDump::read(&mut std::io::Cursor::new(b"\x7fELF\x02\x01")).is_err());
+}
+
+#[test]
+#[allow(clippy::unwrap_used)]
+fn test_damaged_nt_file() {
+    let mut core = test_core();
+    let name = core.windows(9).position(|w| w == b"/bin/test").unwrap();
+    // The count of file mappings, 5 words before the first name
+    let count = name - 5 * 8;
+    core[count..count + 8].copy_from_slice(&(u64::MAX / 8).to_le_bytes());
+    assert!(CoreDump::read(&mut std::io::Cursor::new(core)).is_err());
+}
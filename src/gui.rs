@@ -14,8 +14,16 @@ use {
 mod advanced_open_window;
 mod bookmarks_window;
 mod bottom_panel;
+mod change_history_window;
+mod command_stream_window;
+mod compare_window;
+mod compression_window;
+mod core_dump_window;
+mod create_patch_window;
 mod debug_window;
 pub mod dialogs;
+mod dump_export_window;
+mod dump_import_window;
 mod external_command_window;
 mod file_diff_result_window;
 mod find_dialog;
@@ -28,21 +36,33 @@ mod open_process_window;
 mod ops;
 mod perspectives_window;
 mod preferences_window;
+mod record_export_window;
+mod region_compare_window;
 mod regions_window;
+mod snapshot_analysis_window;
+mod socket_window;
+mod tab_bar;
 mod top_menu;
 mod top_panel;
 mod util;
 mod views_window;
+mod watch_window;
 mod window_open;
 
 use {
     self::{
         advanced_open_window::AdvancedOpenWindow, bookmarks_window::BookmarksWindow,
+        change_history_window::ChangeHistoryWindow, command_stream_window::CommandStreamWindow,
+        compare_window::CompareWindow, compression_window::CompressionWindow,
+        core_dump_window::CoreDumpWindow, create_patch_window::CreatePatchWindow,
+        dump_export_window::DumpExportWindow, dump_import_window::DumpImportWindow,
         file_diff_result_window::FileDiffResultWindow, find_dialog::FindDialog,
         find_memory_pointers_window::FindMemoryPointersWindow, inspect_panel::InspectPanel,
         layouts_window::LayoutsWindow, meta_diff_window::MetaDiffWindow,
         open_process_window::OpenProcessWindow, perspectives_window::PerspectivesWindow,
-        regions_window::RegionsWindow, views_window::ViewsWindow,
+        record_export_window::RecordExportWindow, region_compare_window::RegionCompareWindow,
+        regions_window::RegionsWindow, snapshot_analysis_window::SnapshotAnalysisWindow,
+        socket_window::SocketWindow, views_window::ViewsWindow, watch_window::WatchWindow,
     },
     crate::{
         app::App,
@@ -59,6 +79,7 @@ use {
         sfml::graphics::Font,
         SfEgui,
     },
+    slotmap::Key,
 };
 
 #[derive(Default)]
@@ -81,6 +102,21 @@ pub struct Gui {
     pub advanced_open_window: AdvancedOpenWindow,
     pub external_command_window: ExternalCommandWindow,
     pub preferences_window: PreferencesWindow,
+    pub watch_window: WatchWindow,
+    pub core_dump_window: CoreDumpWindow,
+    pub command_stream_window: CommandStreamWindow,
+    pub socket_window: SocketWindow,
+    pub compression_window: CompressionWindow,
+    pub record_export_window: RecordExportWindow,
+    pub dump_import_window: DumpImportWindow,
+    pub dump_export_window: DumpExportWindow,
+    pub create_patch_window: CreatePatchWindow,
+    pub compare_window: CompareWindow,
+    pub snapshot_analysis_window: SnapshotAnalysisWindow,
+    pub change_history_window: ChangeHistoryWindow,
+    pub region_compare_window: RegionCompareWindow,
+    /// Tab with unsaved changes that is waiting for confirmation to be closed
+    pub close_tab_prompt: Option<usize>,
     pub msg_dialog: MessageDialog,
 }
 
@@ -112,6 +148,19 @@ impl Gui {
     pub fn add_dialog<D: Dialog + 'static>(&mut self, dialog: D) {
         self.dialogs.push(Box::new(dialog));
     }
+    /// Clear references into the meta and the data, which become invalid when the document
+    /// is switched out
+    pub fn clear_meta_refs(&mut self) {
+        self.regions_window.selected_key = None;
+        self.bookmarks_window.selected = None;
+        self.views_window.selected = ViewKey::null();
+        self.layouts_window.clear_meta_refs();
+        self.region_compare_window.clear_meta_refs();
+        self.context_menu = None;
+        // Find results and diff hunks are offsets into the data
+        self.find_dialog.clear_results();
+        self.file_diff_result_window.hunks.clear();
+    }
 }
 
 #[must_use = "Returns false if application should quit"]
@@ -132,6 +181,10 @@ pub fn do_egui(
             gamedebug_core::toggle();
         }
         gui.msg_dialog.show(ctx);
+        open_process_window::update_auto_attach(gui, app, font);
+        if std::mem::take(&mut app.compression_offer) {
+            gui.compression_window.open.set(true);
+        }
         macro_rules! windows {
             ($($title:expr, $field:ident, $ty:ty: $($arg:ident)+;)*) => {
                 $(
@@ -154,7 +207,20 @@ pub fn do_egui(
             "Diff against clean meta", meta_diff_window,            MetaDiffWindow: app;
             "Open process",            open_process_window,         OpenProcessWindow: gui app font;
             "Find memory pointers",    find_memory_pointers_window, FindMemoryPointersWindow: gui app font;
+            "Watch list",              watch_window,                WatchWindow: gui app;
+            "Core dump threads",       core_dump_window,            CoreDumpWindow: gui app font;
             "Advanced open",           advanced_open_window,        AdvancedOpenWindow: gui app font;
+            "Stream command",          command_stream_window,       CommandStreamWindow: gui app font;
+            "Socket",                  socket_window,               SocketWindow: gui app font;
+            "Compression",             compression_window,          CompressionWindow: gui app font;
+            "Export HEX/SREC",         record_export_window,        RecordExportWindow: gui app;
+            "Import text dump",        dump_import_window,          DumpImportWindow: gui app font;
+            "Export text dump",        dump_export_window,          DumpExportWindow: gui app;
+            "Create patch",            create_patch_window,         CreatePatchWindow: gui app;
+            "Compare",                 compare_window,              CompareWindow: gui app;
+            "Snapshot analysis",       snapshot_analysis_window,    SnapshotAnalysisWindow: gui app;
+            "Change history",          change_history_window,       ChangeHistoryWindow: gui app;
+            "Compare regions",         region_compare_window,       RegionCompareWindow: gui app;
             "External command",        external_command_window,     ExternalCommandWindow: gui app;
             "Preferences",             preferences_window,          PreferencesWindow: gui app;
         }
@@ -187,6 +253,7 @@ pub fn do_egui(
                                         label: format!("New @ offset {}", byte_off),
                                         desc: String::new(),
                                         value_type: ValueType::None,
+                                        anchor: None,
                                     });
                                     gui.bookmarks_window.open.set(true);
                                     gui.bookmarks_window.selected = Some(idx);
@@ -208,6 +275,13 @@ pub fn do_egui(
         // Panels
         let top_re =
             TopBottomPanel::top("top_panel").show(ctx, |ui| top_panel::ui(ui, gui, app, font));
+        let mut top_rect = top_re.response.rect;
+        if app.tabs.len() > 1 {
+            top_rect = TopBottomPanel::top("tab_bar")
+                .show(ctx, |ui| tab_bar::ui(ui, gui, app))
+                .response
+                .rect;
+        }
         let bot_re = TopBottomPanel::bottom("bottom_panel")
             .show(ctx, |ui| bottom_panel::ui(ui, app, mouse_pos));
         let right_re = egui::SidePanel::right("right_panel")
@@ -220,7 +294,7 @@ pub fn do_egui(
             reason = "Window size can't exceed i16"
         )]
         {
-            app.hex_ui.hex_iface_rect.y = top_re.response.rect.bottom() as ViewportScalar + padding;
+            app.hex_ui.hex_iface_rect.y = top_rect.bottom() as ViewportScalar + padding;
         }
         #[expect(
             clippy::cast_possible_truncation,
@@ -247,6 +321,7 @@ pub fn do_egui(
             retain
         });
         gui.dialogs = dialogs;
+        tab_bar::close_prompt_ui(ctx, gui, app);
     });
     if let Err(e) = result {
         match e {
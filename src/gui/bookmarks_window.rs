@@ -1,11 +1,12 @@
 use {
-    super::{window_open::WindowOpen, Gui},
+    super::{util::anchor_ui, watch_window::add_to_watch_list, window_open::WindowOpen, Gui},
     crate::{
         app::App,
         damage_region::DamageRegion,
         meta::{find_most_specific_region_for_offset, Bookmark, ValueType},
         region_context_menu,
         shell::msg_if_fail,
+        watch_list::WatchType,
     },
     anyhow::Context,
     egui_extras::{Size, TableBuilder},
@@ -69,7 +70,7 @@ impl BookmarksWindow {
                     });
                     row.col(|ui| {
                         if ui
-                            .link(app.meta_state.meta.bookmarks[idx].offset.to_string())
+                            .link(app.offset_label(app.meta_state.meta.bookmarks[idx].offset))
                             .clicked()
                         {
                             action = Action::Goto(app.meta_state.meta.bookmarks[idx].offset);
@@ -157,6 +158,8 @@ impl BookmarksWindow {
             });
         if let Some(idx) = win.selected {
             ui.separator();
+            let addr = app.offset_to_addr(app.meta_state.meta.bookmarks[idx].offset);
+            let proc_mem = app.source_pid().is_some();
             let mark = &mut app.meta_state.meta.bookmarks[idx];
             ui.horizontal(|ui| {
                 if win.edit_name {
@@ -174,6 +177,12 @@ impl BookmarksWindow {
                 ui.label("Offset");
                 ui.add(egui::DragValue::new(&mut mark.offset));
             });
+            anchor_ui(
+                ui,
+                &mut mark.anchor,
+                addr,
+                &gui.open_process_window.map_ranges,
+            );
             egui::ComboBox::new("type_combo", "value type")
                 .selected_text(mark.value_type.label())
                 .show_ui(ui, |ui| {
@@ -228,10 +237,18 @@ impl BookmarksWindow {
             }
             ui.heading("Description");
             ui.text_edit_multiline(&mut mark.desc);
-            if ui.button("Delete").clicked() {
-                app.meta_state.meta.bookmarks.remove(idx);
-                win.selected = None;
-            }
+            ui.horizontal(|ui| {
+                if ui
+                    .add_enabled(proc_mem, egui::Button::new("Add to watch list"))
+                    .clicked()
+                {
+                    action = Action::AddToWatchList(idx);
+                }
+                if ui.button("Delete").clicked() {
+                    app.meta_state.meta.bookmarks.remove(idx);
+                    win.selected = None;
+                }
+            });
         }
         ui.separator();
         if ui.button("Add new at cursor").clicked() {
@@ -240,6 +257,7 @@ impl BookmarksWindow {
                 label: format!("New bookmark at {}", app.edit_state.cursor),
                 desc: String::new(),
                 value_type: ValueType::None,
+                anchor: None,
             })
         }
         match action {
@@ -249,6 +267,15 @@ impl BookmarksWindow {
                 app.center_view_on_offset(off);
                 app.hex_ui.flash_cursor();
             }
+            Action::AddToWatchList(idx) => {
+                let mark = &app.meta_state.meta.bookmarks[idx];
+                let ty = match mark.value_type {
+                    ValueType::U16Le => WatchType::U16,
+                    _ => WatchType::U8,
+                };
+                let (offset, label) = (mark.offset, mark.label.clone());
+                add_to_watch_list(gui, app, offset, ty, label);
+            }
         }
     }
 }
@@ -267,4 +294,5 @@ impl ValueType {
 enum Action {
     None,
     Goto(usize),
+    AddToWatchList(usize),
 }
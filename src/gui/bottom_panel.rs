@@ -61,8 +61,21 @@ pub fn ui(ui: &mut Ui, app: &mut App, mouse_pos: ViewportVec) {
             "cursor: {} ({:x})",
             app.edit_state.cursor, app.edit_state.cursor
         ));
+        if app.addr_map.is_some() {
+            ui.label(format!(
+                "addr: {:x}",
+                app.offset_to_addr(app.edit_state.cursor)
+            ));
+        }
+        if let Some(sector) = app.hex_ui.sector_grid && sector != 0 {
+            let pos = app.edit_state.cursor + app.args.src.hard_seek.unwrap_or(0);
+            ui.label(format!("sector: {} +{}", pos / sector, pos % sector));
+        }
         if !app.hex_ui.current_layout.is_null() && let Some((offset, _view_idx)) = app.byte_offset_at_pos(mouse_pos.x, mouse_pos.y) {
             ui.label(format!("mouse: {} ({:x})", offset, offset));
+            if app.addr_map.is_some() {
+                ui.label(format!("addr: {:x}", app.offset_to_addr(offset)));
+            }
         }
     });
 }
@@ -0,0 +1,143 @@
+use {
+    super::{window_open::WindowOpen, Gui},
+    crate::{
+        app::App,
+        change_history::{hex, Change},
+        meta::find_most_specific_region_for_offset,
+        parse_radix::parse_guess_radix,
+        shell::msg_if_fail,
+    },
+    egui_extras::{Size, TableBuilder},
+    egui_sfml::egui,
+};
+
+#[derive(Default)]
+pub struct ChangeHistoryWindow {
+    pub open: WindowOpen,
+    /// Only show changes at or after this offset
+    filter_begin: String,
+    /// Only show changes at or before this offset
+    filter_end: String,
+    /// Only show changes that touch the selection
+    only_selection: bool,
+}
+
+impl ChangeHistoryWindow {
+    pub fn ui(ui: &mut egui::Ui, gui: &mut Gui, app: &mut App) {
+        let win = &mut gui.change_history_window;
+        let rec = &mut app.change_recorder;
+        ui.horizontal(|ui| {
+            ui.checkbox(&mut rec.enabled, "Record changes")
+                .on_hover_text("Compare the data with the previous version on every reload");
+            ui.checkbox(&mut app.preferences.auto_reload, "Auto reload");
+            ui.label("Interval (ms)");
+            ui.add(egui::DragValue::new(
+                &mut app.preferences.auto_reload_interval_ms,
+            ));
+        });
+        ui.horizontal(|ui| {
+            if ui.button("Clear").clicked() {
+                rec.clear();
+            }
+            if ui.button("Save...").clicked()
+                && let Some(path) = rfd::FileDialog::new()
+                    .add_filter("CSV", &["csv"])
+                    .save_file()
+            {
+                msg_if_fail(
+                    std::fs::write(path, rec.to_csv()),
+                    "Failed to save change history",
+                    &mut gui.msg_dialog,
+                );
+            }
+            ui.label("Keep at most");
+            ui.add(egui::DragValue::new(&mut rec.max_changes).clamp_range(1..=10_000_000));
+        });
+        if rec.dropped != 0 {
+            ui.label(format!("{} oldest changes were dropped", rec.dropped));
+        }
+        ui.horizontal(|ui| {
+            ui.label("Offsets from");
+            ui.add(egui::TextEdit::singleline(&mut win.filter_begin).desired_width(80.0));
+            ui.label("to");
+            ui.add(egui::TextEdit::singleline(&mut win.filter_end).desired_width(80.0));
+            ui.checkbox(&mut win.only_selection, "Only selection");
+        });
+        let begin = parse_guess_radix::<usize>(win.filter_begin.trim()).unwrap_or(0);
+        let end = parse_guess_radix::<usize>(win.filter_end.trim()).unwrap_or(usize::MAX);
+        let sel = app.hex_ui.selection().filter(|_| win.only_selection);
+        let shown: Vec<&Change> = rec
+            .changes
+            .iter()
+            .rev()
+            .filter(|change| {
+                let last = change.offset + change.new.len().saturating_sub(1);
+                let in_range = change.offset <= end && last >= begin;
+                let in_sel = match sel {
+                    Some(sel) => change.offset <= sel.end && last >= sel.begin,
+                    None => true,
+                };
+                in_range && in_sel
+            })
+            .collect();
+        ui.label(format!(
+            "{} of {} changes, newest first",
+            shown.len(),
+            rec.changes.len()
+        ));
+        ui.separator();
+        let mut goto = None;
+        TableBuilder::new(ui)
+            .column(Size::initial(70.0))
+            .column(Size::initial(50.0))
+            .column(Size::initial(90.0))
+            .column(Size::initial(160.0))
+            .column(Size::initial(160.0))
+            .column(Size::remainder())
+            .striped(true)
+            .header(20.0, |mut row| {
+                for label in ["Time (s)", "Reload", "Offset", "Old", "New", "Region"] {
+                    row.col(|ui| {
+                        ui.label(label);
+                    });
+                }
+            })
+            .body(|body| {
+                body.rows(20.0, shown.len(), |idx, mut row| {
+                    let change = shown[idx];
+                    row.col(|ui| {
+                        ui.label(format!("{:.3}", change.time.as_secs_f64()));
+                    });
+                    row.col(|ui| {
+                        ui.label(change.reload.to_string());
+                    });
+                    row.col(|ui| {
+                        if ui.link(format!("{:X}", change.offset)).clicked() {
+                            goto = Some(change.offset);
+                        }
+                    });
+                    row.col(|ui| {
+                        ui.label(hex(&change.old));
+                    });
+                    row.col(|ui| {
+                        ui.label(hex(&change.new));
+                    });
+                    row.col(|ui| {
+                        match find_most_specific_region_for_offset(
+                            &app.meta_state.meta.low.regions,
+                            change.offset,
+                        ) {
+                            Some(key) => ui.label(&app.meta_state.meta.low.regions[key].name),
+                            None => ui.label("-"),
+                        };
+                    });
+                });
+            });
+        if let Some(offset) = goto
+            && offset < app.data.len()
+        {
+            app.search_focus(offset);
+        }
+        gui.change_history_window.open.post_ui();
+    }
+}
@@ -0,0 +1,68 @@
+use {
+    super::{window_open::WindowOpen, Gui},
+    crate::{app::App, shell::msg_if_fail, source::SourceProvider},
+    egui_sfml::{egui, sfml::graphics::Font},
+};
+
+#[derive(Default)]
+pub struct CommandStreamWindow {
+    pub open: WindowOpen,
+    cmd_str: String,
+}
+
+impl CommandStreamWindow {
+    pub fn ui(ui: &mut egui::Ui, gui: &mut Gui, app: &mut App, font: &Font) {
+        let win = &mut gui.command_stream_window;
+        ui.horizontal(|ui| {
+            let re = ui
+                .add(
+                    egui::TextEdit::singleline(&mut win.cmd_str)
+                        .hint_text("Example: adb exec-out cat /dev/mem"),
+                )
+                .on_hover_text("Quote arguments that contain spaces");
+            if ui.button("Start").clicked()
+                || (re.lost_focus() && ui.input().key_pressed(egui::Key::Enter))
+            {
+                msg_if_fail(
+                    app.load_command_stream(&win.cmd_str, font),
+                    "Failed to spawn command",
+                    &mut gui.msg_dialog,
+                );
+            }
+        });
+        let Some(SourceProvider::Command(stream)) = app.source.as_ref().map(|src| &src.provider)
+        else {
+            ui.label("Standard output of the command is streamed in as the data.");
+            win.open.post_ui();
+            return;
+        };
+        ui.separator();
+        ui.label(format!("Command: {}", stream.cmd_line));
+        ui.horizontal(|ui| {
+            match (stream.try_status(), stream.pid()) {
+                (Some(status), _) => ui.label(format!("Exited: {status}")),
+                (None, Some(pid)) => ui.label(format!("Running (pid {pid})")),
+                (None, None) => ui.label("Unknown status"),
+            };
+            if ui.button("Kill").clicked() {
+                msg_if_fail(stream.kill(), "Failed to kill command", &mut gui.msg_dialog);
+            }
+        });
+        ui.label("stderr");
+        let stderr = match stream.stderr.lock() {
+            Ok(stderr) => stderr.clone(),
+            Err(_) => String::from("<stderr lock poisoned>"),
+        };
+        egui::ScrollArea::vertical()
+            .max_height(300.0)
+            .stick_to_bottom(true)
+            .show(ui, |ui| {
+                ui.add(
+                    egui::TextEdit::multiline(&mut stderr.as_str())
+                        .code_editor()
+                        .desired_width(f32::INFINITY),
+                );
+            });
+        gui.command_stream_window.open.post_ui();
+    }
+}
@@ -0,0 +1,99 @@
+use {
+    super::{message_dialog::Icon, window_open::WindowOpen, Gui},
+    crate::{
+        app::{compare, App},
+        shell::msg_if_fail,
+    },
+    egui_sfml::egui,
+};
+
+#[derive(Default)]
+pub struct CompareWindow {
+    pub open: WindowOpen,
+    /// Number of differing bytes, counted on request since it's a full pass over both files
+    diff_count: Option<usize>,
+}
+
+impl CompareWindow {
+    pub fn ui(ui: &mut egui::Ui, gui: &mut Gui, app: &mut App) {
+        let win = &mut gui.compare_window;
+        let Some(cmp) = &app.compare else {
+            ui.label("Not comparing");
+            if ui.button("Compare with file...").clicked()
+                && let Some(path) = rfd::FileDialog::new().pick_file()
+            {
+                win.diff_count = None;
+                msg_if_fail(
+                    app.enter_compare(&path),
+                    "Failed to compare",
+                    &mut gui.msg_dialog,
+                );
+            }
+            gui.compare_window.open.post_ui();
+            return;
+        };
+        ui.label(format!("Comparing with {}", cmp.path.display()));
+        if cmp.data.len() != app.data.len() {
+            ui.label(format!(
+                "Sizes differ: {} vs {} bytes",
+                app.data.len(),
+                cmp.data.len()
+            ));
+        }
+        ui.horizontal(|ui| {
+            match win.diff_count {
+                Some(count) => ui.label(format!("{count} differing bytes")),
+                None => ui.label("Differing bytes not counted"),
+            };
+            if ui.button("Count").clicked() {
+                win.diff_count = Some(compare::count_diffs(&app.data, &cmp.data));
+            }
+        });
+        let mut goto = None;
+        let mut reload = false;
+        let mut stop = false;
+        ui.horizontal(|ui| {
+            if ui.button("⏴ Previous difference").clicked() {
+                goto = Some(false);
+            }
+            if ui.button("Next difference ⏵").clicked() {
+                goto = Some(true);
+            }
+        });
+        ui.horizontal(|ui| {
+            if ui
+                .button("Reload file")
+                .on_hover_text("Read the compared file again")
+                .clicked()
+            {
+                reload = true;
+            }
+            stop = ui.button("Stop comparing").clicked();
+        });
+        if reload {
+            win.diff_count = None;
+            msg_if_fail(
+                app.reload_compare(),
+                "Failed to reload",
+                &mut gui.msg_dialog,
+            );
+        }
+        if stop {
+            app.exit_compare();
+        }
+        if let Some(forward) = goto
+            && !app.goto_compare_diff(forward)
+        {
+            gui.msg_dialog.open(
+                Icon::Info,
+                "No more differences",
+                if forward {
+                    "There are no differences after the cursor"
+                } else {
+                    "There are no differences before the cursor"
+                },
+            );
+        }
+        gui.compare_window.open.post_ui();
+    }
+}
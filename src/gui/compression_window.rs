@@ -0,0 +1,65 @@
+use {
+    super::{window_open::WindowOpen, Gui},
+    crate::{app::App, compression::Algorithm, shell::msg_if_fail, source::SourceProvider},
+    egui_sfml::{egui, sfml::graphics::Font},
+};
+
+#[derive(Default)]
+pub struct CompressionWindow {
+    pub open: WindowOpen,
+}
+
+impl CompressionWindow {
+    pub fn ui(ui: &mut egui::Ui, gui: &mut Gui, app: &mut App, font: &Font) {
+        let is_file = app
+            .source
+            .as_ref()
+            .is_some_and(|src| matches!(src.provider, SourceProvider::File(_)));
+        let compression = app
+            .source
+            .as_mut()
+            .and_then(|src| src.state.compression.as_mut());
+        match compression {
+            Some(comp) => {
+                ui.label(format!(
+                    "Showing the decompressed {} data. Saving compresses it again.",
+                    comp.algo.name()
+                ));
+                ui.horizontal(|ui| {
+                    ui.label("Level");
+                    ui.add(egui::DragValue::new(&mut comp.level).clamp_range(comp.algo.levels()));
+                });
+                ui.horizontal(|ui| {
+                    if ui.button("Save compressed").clicked() {
+                        msg_if_fail(app.save(), "Failed to save", &mut gui.msg_dialog);
+                    }
+                    if ui.button("Save compressed as...").clicked()
+                        && let Some(path) = rfd::FileDialog::new().save_file()
+                    {
+                        msg_if_fail(
+                            app.save_compressed_to(&path),
+                            "Failed to save",
+                            &mut gui.msg_dialog,
+                        );
+                    }
+                });
+            }
+            None => match Algorithm::detect(&app.data).filter(|_| is_file) {
+                Some(algo) => {
+                    ui.label(format!("This file is {} compressed.", algo.name()));
+                    if ui.button("Decompress").clicked() {
+                        msg_if_fail(
+                            app.decompress(algo, font),
+                            "Failed to decompress",
+                            &mut gui.msg_dialog,
+                        );
+                    }
+                }
+                None => {
+                    ui.label("The opened file is not compressed");
+                }
+            },
+        }
+        gui.compression_window.open.post_ui();
+    }
+}
@@ -0,0 +1,86 @@
+use {
+    super::{window_open::WindowOpen, Gui},
+    crate::{app::App, elf_core::ThreadStatus, shell::msg_fail, source::SourceProvider},
+    egui_sfml::{egui, sfml::graphics::Font},
+};
+
+#[derive(Default)]
+pub struct CoreDumpWindow {
+    pub open: WindowOpen,
+    /// Threads of the loaded core dump
+    pub threads: Vec<ThreadStatus>,
+}
+
+impl CoreDumpWindow {
+    pub fn ui(ui: &mut egui::Ui, gui: &mut Gui, app: &mut App, font: &Font) {
+        let win = &mut gui.core_dump_window;
+        if win.threads.is_empty() {
+            ui.label("No thread information. Open a core dump with File -> Open core dump...");
+            win.open.post_ui();
+            return;
+        }
+        // Core dumps are opened read-only
+        if let Some(src) = &app.source
+            && matches!(src.provider, SourceProvider::ElfCore { .. })
+            && !src.attr.permissions.write
+            && let Some(path) = app.args.src.file.clone()
+            && ui
+                .button("Reopen writable")
+                .on_hover_text("Open the core dump again, so edits can be saved to it")
+                .clicked()
+        {
+            match app.load_elf_core(path, true, font) {
+                Ok(core) => win.threads = core.threads,
+                Err(e) => msg_fail(&e, "Failed to reopen core dump", &mut gui.msg_dialog),
+            }
+            gui.core_dump_window.open.post_ui();
+            return;
+        }
+        let mut goto = None;
+        egui::ScrollArea::vertical().show(ui, |ui| {
+            for (i, thread) in win.threads.iter().enumerate() {
+                egui::CollapsingHeader::new(format!(
+                    "Thread {} (signal {})",
+                    thread.pid, thread.signal
+                ))
+                .id_source(i)
+                .default_open(i == 0)
+                .show(ui, |ui| {
+                    if thread.regs.is_empty() {
+                        ui.label("Registers of this architecture aren't supported");
+                    }
+                    egui::Grid::new(("regs_grid", i))
+                        .striped(true)
+                        .show(ui, |ui| {
+                            for &(name, val) in &thread.regs {
+                                ui.label(name);
+                                // Registers pointing into the loaded memory can be jumped to
+                                let off = usize::try_from(val)
+                                    .ok()
+                                    .and_then(|addr| app.addr_to_offset(addr));
+                                let text = egui::RichText::new(format!("{val:016X}")).monospace();
+                                match off {
+                                    Some(off) => {
+                                        if ui.link(text).on_hover_text("Jump to address").clicked()
+                                        {
+                                            goto = Some(off);
+                                        }
+                                    }
+                                    None => {
+                                        ui.label(text);
+                                    }
+                                }
+                                ui.end_row();
+                            }
+                        });
+                });
+            }
+        });
+        if let Some(off) = goto {
+            app.center_view_on_offset(off);
+            app.edit_state.set_cursor(off);
+            app.hex_ui.flash_cursor();
+        }
+        win.open.post_ui();
+    }
+}
@@ -0,0 +1,72 @@
+use {
+    super::{window_open::WindowOpen, Gui},
+    crate::{app::App, patch::PatchFormat, shell::msg_if_fail},
+    egui_sfml::egui,
+};
+
+pub struct CreatePatchWindow {
+    pub open: WindowOpen,
+    format: PatchFormat,
+    base: PatchBase,
+}
+
+/// What the patch is created against
+#[derive(PartialEq, Eq, Clone, Copy)]
+enum PatchBase {
+    SourceFile,
+    Backup,
+}
+
+impl Default for CreatePatchWindow {
+    fn default() -> Self {
+        Self {
+            open: WindowOpen::default(),
+            format: PatchFormat::Ips,
+            base: PatchBase::SourceFile,
+        }
+    }
+}
+
+impl CreatePatchWindow {
+    pub fn ui(ui: &mut egui::Ui, gui: &mut Gui, app: &mut App) {
+        let win = &mut gui.create_patch_window;
+        ui.label("Create a patch that turns the original data into the current data");
+        ui.horizontal(|ui| {
+            ui.label("Original");
+            ui.selectable_value(&mut win.base, PatchBase::SourceFile, "Source file")
+                .on_hover_text("The file on disk, as it was before the unsaved edits");
+            ui.selectable_value(&mut win.base, PatchBase::Backup, "Backup")
+                .on_hover_text("The backup created with Edit -> Create backup");
+        });
+        ui.horizontal(|ui| {
+            ui.label("Format");
+            for format in PatchFormat::ALL {
+                ui.selectable_value(&mut win.format, format, format.name());
+            }
+        });
+        let base_path = match win.base {
+            PatchBase::SourceFile => app.source_file().map(|path| path.to_owned()),
+            PatchBase::Backup => app.backup_path().filter(|path| path.exists()),
+        };
+        let Some(base_path) = base_path else {
+            ui.label(match win.base {
+                PatchBase::SourceFile => "There is no source file",
+                PatchBase::Backup => "There is no backup",
+            });
+            win.open.post_ui();
+            return;
+        };
+        if ui.button("Create patch...").clicked()
+            && let Some(path) = rfd::FileDialog::new()
+                .add_filter(win.format.name(), &[win.format.extension()])
+                .save_file()
+        {
+            let result: anyhow::Result<()> = try {
+                let patch = app.create_patch(win.format, &base_path)?;
+                std::fs::write(path, patch)?;
+            };
+            msg_if_fail(result, "Failed to create patch", &mut gui.msg_dialog);
+        }
+        gui.create_patch_window.open.post_ui();
+    }
+}
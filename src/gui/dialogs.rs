@@ -45,13 +45,26 @@ impl Dialog for JumpDialog {
             match parse_offset_maybe_relative(&self.string_buf) {
                 Ok((offset, relativity)) => {
                     let offset = match relativity {
-                        Relativity::Absolute => {
-                            if let Some(hard_seek) = app.args.src.hard_seek {
-                                offset.saturating_sub(hard_seek)
-                            } else {
-                                offset
+                        Relativity::Absolute => match &app.addr_map {
+                            Some(map) => match map.addr_to_offset(offset) {
+                                Some(offset) => offset,
+                                None => {
+                                    msg.open(
+                                        Icon::Error,
+                                        "Error",
+                                        format!("Address {offset:X} is not loaded"),
+                                    );
+                                    return true;
+                                }
+                            },
+                            None => {
+                                if let Some(hard_seek) = app.args.src.hard_seek {
+                                    offset.saturating_sub(hard_seek)
+                                } else {
+                                    offset
+                                }
                             }
-                        }
+                        },
                         Relativity::RelAdd => app.edit_state.cursor.saturating_add(offset),
                         Relativity::RelSub => app.edit_state.cursor.saturating_sub(offset),
                     };
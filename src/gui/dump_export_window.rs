@@ -0,0 +1,118 @@
+use {
+    super::{window_open::WindowOpen, Gui},
+    crate::{
+        app::App,
+        meta::RegionKey,
+        shell::msg_if_fail,
+        text_dump::{self, DumpFormat},
+    },
+    egui_sfml::{egui, sfml::window::clipboard},
+};
+
+pub struct DumpExportWindow {
+    pub open: WindowOpen,
+    format: DumpFormat,
+    range: ExportRange,
+    /// Write region and bookmark names as comment lines
+    annotate: bool,
+}
+
+#[derive(PartialEq, Eq, Clone, Copy)]
+enum ExportRange {
+    Selection,
+    Region(RegionKey),
+    All,
+}
+
+impl Default for DumpExportWindow {
+    fn default() -> Self {
+        Self {
+            open: WindowOpen::default(),
+            format: DumpFormat::Xxd,
+            range: ExportRange::Selection,
+            annotate: false,
+        }
+    }
+}
+
+impl DumpExportWindow {
+    pub fn ui(ui: &mut egui::Ui, gui: &mut Gui, app: &mut App) {
+        let win = &mut gui.dump_export_window;
+        ui.horizontal(|ui| {
+            for format in DumpFormat::ALL {
+                ui.selectable_value(&mut win.format, format, format.name());
+            }
+        });
+        let regions = &app.meta_state.meta.low.regions;
+        let range_label = |range| match range {
+            ExportRange::Selection => "Selection".to_string(),
+            ExportRange::Region(key) => match regions.get(key) {
+                Some(reg) => format!("Region: {}", reg.name),
+                None => "<deleted region>".to_string(),
+            },
+            ExportRange::All => "Everything".to_string(),
+        };
+        egui::ComboBox::new("dump_range_combo", "Range")
+            .selected_text(range_label(win.range))
+            .show_ui(ui, |ui| {
+                ui.selectable_value(&mut win.range, ExportRange::Selection, "Selection");
+                ui.selectable_value(&mut win.range, ExportRange::All, "Everything");
+                for (key, reg) in regions.iter() {
+                    ui.selectable_value(
+                        &mut win.range,
+                        ExportRange::Region(key),
+                        format!("Region: {}", reg.name),
+                    );
+                }
+            });
+        ui.checkbox(&mut win.annotate, "Annotate with region and bookmark names");
+        let range = match win.range {
+            ExportRange::Selection => app.hex_ui.selection().map(|sel| (sel.begin, sel.end)),
+            ExportRange::Region(key) => regions
+                .get(key)
+                .map(|reg| (reg.region.begin, reg.region.end)),
+            ExportRange::All => Some((0, app.data.len().saturating_sub(1))),
+        }
+        .filter(|&(begin, end)| begin <= end && end < app.data.len());
+        let Some((begin, end)) = range else {
+            ui.label("Nothing to export");
+            win.open.post_ui();
+            return;
+        };
+        let mut copy = false;
+        let mut save = false;
+        ui.horizontal(|ui| {
+            copy = ui.button("Copy to clipboard").clicked();
+            save = ui.button("Save...").clicked();
+        });
+        if copy || save {
+            let base = app.offset_to_addr(begin);
+            let mut annotations = Vec::new();
+            if win.annotate {
+                let to_addr = |offset| base + (offset - begin);
+                for reg in regions.values() {
+                    if (begin..=end).contains(&reg.region.begin) {
+                        annotations.push((to_addr(reg.region.begin), reg.name.clone()));
+                    }
+                }
+                for bm in &app.meta_state.meta.bookmarks {
+                    if (begin..=end).contains(&bm.offset) {
+                        annotations.push((to_addr(bm.offset), bm.label.clone()));
+                    }
+                }
+            }
+            let text = text_dump::write(win.format, &app.data[begin..=end], base, &annotations);
+            if copy {
+                clipboard::set_string(&text);
+            }
+            if save && let Some(path) = rfd::FileDialog::new().save_file() {
+                msg_if_fail(
+                    std::fs::write(path, text),
+                    "Failed to save dump",
+                    &mut gui.msg_dialog,
+                );
+            }
+        }
+        gui.dump_export_window.open.post_ui();
+    }
+}
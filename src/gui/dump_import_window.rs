@@ -0,0 +1,72 @@
+use {
+    super::{message_dialog::Icon, window_open::WindowOpen, Gui},
+    crate::{
+        app::App,
+        shell::{msg_fail, msg_if_fail},
+        text_dump::{self, DumpFormat},
+    },
+    egui_sfml::{egui, sfml::graphics::Font},
+};
+
+#[derive(Default)]
+pub struct DumpImportWindow {
+    pub open: WindowOpen,
+    text: String,
+}
+
+impl DumpImportWindow {
+    pub fn ui(ui: &mut egui::Ui, gui: &mut Gui, app: &mut App, font: &Font) {
+        let win = &mut gui.dump_import_window;
+        ui.label("Paste the output of xxd, hexdump -C, od -An -tx1, or plain hex");
+        egui::ScrollArea::vertical()
+            .max_height(300.0)
+            .show(ui, |ui| {
+                ui.add(
+                    egui::TextEdit::multiline(&mut win.text)
+                        .code_editor()
+                        .desired_width(f32::INFINITY),
+                );
+            });
+        if !win.text.trim().is_empty() {
+            let format = match DumpFormat::detect(&win.text) {
+                DumpFormat::Plain => "plain hex or od",
+                format => format.name(),
+            };
+            ui.label(format!("Detected format: {format}"));
+        }
+        ui.horizontal(|ui| {
+            if ui.button("Load from file...").clicked()
+                && let Some(path) = rfd::FileDialog::new().pick_file()
+            {
+                match std::fs::read_to_string(path) {
+                    Ok(text) => win.text = text,
+                    Err(e) => msg_fail(&e, "Failed to read file", &mut gui.msg_dialog),
+                }
+            }
+            if ui.button("Import").clicked() {
+                match text_dump::parse(&win.text) {
+                    Ok(data) if data.is_empty() => gui.msg_dialog.open(
+                        Icon::Warn,
+                        "Nothing to import",
+                        "The dump contains no bytes",
+                    ),
+                    Ok(data) => app.load_buffer(data, font),
+                    Err(e) => msg_fail(&e, "Failed to import dump", &mut gui.msg_dialog),
+                }
+            }
+            // The imported data isn't backed by a file until it's saved
+            if app.source.is_none()
+                && !app.data.is_empty()
+                && ui.button("Save imported data as...").clicked()
+                && let Some(path) = rfd::FileDialog::new().save_file()
+            {
+                msg_if_fail(
+                    std::fs::write(path, &app.data[..]),
+                    "Failed to save",
+                    &mut gui.msg_dialog,
+                );
+            }
+        });
+        gui.dump_import_window.open.post_ui();
+    }
+}
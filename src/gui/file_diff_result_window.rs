@@ -1,6 +1,8 @@
 use {
     crate::{
-        app::{read_source_to_buf, FileDiffEntry},
+        app::{read_source_to_buf, App},
+        binary_diff::{self, Hunk, HunkKind},
+        data::Data,
         gui::window_open::WindowOpen,
         meta::{find_most_specific_region_for_offset, Bookmark, RegionKey, ValueType},
         region_context_menu,
@@ -9,69 +11,148 @@ use {
     },
     egui_extras::Size,
     egui_sfml::egui,
-    std::{path::PathBuf, time::Instant},
+    std::{fmt::Write, path::PathBuf, time::Instant},
 };
 
 pub struct FileDiffResultWindow {
-    pub diff_entries: Vec<FileDiffEntry>,
+    pub hunks: Vec<Hunk>,
+    /// Contents of the file when it was last read, which the `b` side of the hunks refers to
+    pub file_data: Data,
     pub open: WindowOpen,
     pub path: PathBuf,
     pub auto_refresh: bool,
     pub auto_refresh_interval_ms: u32,
     pub last_refresh: Instant,
+    show_equal: bool,
+    /// Highlight the differing bytes in the views
+    pub overlay: bool,
+    pub overlay_color: [u8; 3],
 }
 
 impl Default for FileDiffResultWindow {
     fn default() -> Self {
         Self {
-            diff_entries: Default::default(),
+            hunks: Default::default(),
+            file_data: Default::default(),
             open: Default::default(),
             path: Default::default(),
             auto_refresh: Default::default(),
             auto_refresh_interval_ms: Default::default(),
             last_refresh: Instant::now(),
+            show_equal: false,
+            overlay: true,
+            overlay_color: [40, 80, 150],
         }
     }
 }
+
+/// Up to the first 8 bytes of `bytes` in hex
+fn preview(bytes: &[u8]) -> String {
+    if bytes.is_empty() {
+        return "-".into();
+    }
+    let mut s = String::new();
+    for b in bytes.iter().take(8) {
+        let _ = write!(s, "{b:02X} ");
+    }
+    if bytes.len() > 8 {
+        s.push('…');
+    }
+    s
+}
+
 impl FileDiffResultWindow {
-    pub(crate) fn ui(ui: &mut egui_sfml::egui::Ui, gui: &mut Gui, app: &mut crate::app::App) {
-        if gui.file_diff_result_window.diff_entries.is_empty() {
+    /// Whether the byte at `idx` is highlighted as a difference in the views
+    pub fn overlay_contains(&self, idx: usize) -> bool {
+        self.open.is() && self.overlay && binary_diff::hunk_at(&self.hunks, idx).is_some()
+    }
+    /// Whether the window is open with differences to go to
+    pub fn has_diffs(&self) -> bool {
+        self.open.is() && self.hunks.iter().any(|h| h.kind != HunkKind::Equal)
+    }
+    /// Move the cursor to the next (or previous) difference. Returns false if there is none.
+    pub fn goto_diff(&self, app: &mut App, forward: bool) -> bool {
+        match binary_diff::next_hunk(&self.hunks, app.edit_state.cursor, forward) {
+            Some(offset) => {
+                goto(app, offset);
+                true
+            }
+            None => false,
+        }
+    }
+    pub(crate) fn ui(ui: &mut egui_sfml::egui::Ui, gui: &mut Gui, app: &mut App) {
+        if gui
+            .file_diff_result_window
+            .hunks
+            .iter()
+            .all(|hunk| hunk.kind == HunkKind::Equal)
+        {
             ui.label("No difference");
             return;
         }
         ui.label(gui.file_diff_result_window.path.display().to_string());
+        let (mut changed, mut inserted, mut deleted) = (0, 0, 0);
+        for hunk in &gui.file_diff_result_window.hunks {
+            match hunk.kind {
+                HunkKind::Equal => {}
+                HunkKind::Changed => changed += hunk.a.len(),
+                HunkKind::Inserted => inserted += hunk.a.len(),
+                HunkKind::Deleted => deleted += hunk.b.len(),
+            }
+        }
+        ui.label(format!(
+            "{changed} bytes changed, {inserted} inserted, {deleted} deleted"
+        ));
         ui.horizontal(|ui| {
             if ui
                 .button("Filter unchanged")
-                .on_hover_text("Keep only the unchanged values")
+                .on_hover_text(
+                    "Keep only the hunks where the file didn't change since it was last read",
+                )
                 .clicked()
             {
                 let result: anyhow::Result<()> = try {
                     let file_data =
                         read_source_to_buf(&gui.file_diff_result_window.path, &app.args.src)?;
-                    gui.file_diff_result_window
-                        .diff_entries
-                        .retain(|en| en.file_val == file_data[en.offset]);
+                    let win = &mut gui.file_diff_result_window;
+                    win.hunks
+                        .retain(|h| file_data.get(h.b.clone()) == win.file_data.get(h.b.clone()));
                 };
                 msg_if_fail(result, "Filter unchanged failed", &mut gui.msg_dialog);
             }
             if ui
                 .button("Filter changed")
-                .on_hover_text("Keep only the values that changed")
+                .on_hover_text("Keep only the hunks where the file changed since it was last read")
                 .clicked()
             {
                 let result: anyhow::Result<()> = try {
                     let file_data =
                         read_source_to_buf(&gui.file_diff_result_window.path, &app.args.src)?;
-                    gui.file_diff_result_window
-                        .diff_entries
-                        .retain(|en| en.file_val != file_data[en.offset]);
+                    let win = &mut gui.file_diff_result_window;
+                    win.hunks
+                        .retain(|h| file_data.get(h.b.clone()) != win.file_data.get(h.b.clone()));
                 };
                 msg_if_fail(result, "Filter unchanged failed", &mut gui.msg_dialog);
             }
+            if ui
+                .button("Diff again")
+                .on_hover_text("Diff the current data against the file again, clearing filters")
+                .clicked()
+            {
+                let result: anyhow::Result<()> = try {
+                    let file_data =
+                        read_source_to_buf(&gui.file_diff_result_window.path, &app.args.src)?;
+                    gui.file_diff_result_window.hunks = binary_diff::diff(&app.data, &file_data);
+                    gui.file_diff_result_window.file_data = file_data.into();
+                };
+                msg_if_fail(result, "Diff failed", &mut gui.msg_dialog);
+            }
         });
         ui.horizontal(|ui| {
-            if ui.button("Refresh").clicked()
+            if ui
+                .button("Refresh")
+                .on_hover_text("Read the file values again")
+                .clicked()
                 || (gui.file_diff_result_window.auto_refresh
                     && gui
                         .file_diff_result_window
@@ -82,11 +163,9 @@ impl FileDiffResultWindow {
             {
                 gui.file_diff_result_window.last_refresh = Instant::now();
                 let result: anyhow::Result<()> = try {
-                    let file_data =
-                        read_source_to_buf(&gui.file_diff_result_window.path, &app.args.src)?;
-                    for en in &mut gui.file_diff_result_window.diff_entries {
-                        en.file_val = file_data[en.offset];
-                    }
+                    gui.file_diff_result_window.file_data =
+                        read_source_to_buf(&gui.file_diff_result_window.path, &app.args.src)?
+                            .into();
                 };
                 msg_if_fail(result, "Refresh failed", &mut gui.msg_dialog);
             }
@@ -98,22 +177,55 @@ impl FileDiffResultWindow {
             ui.add(egui::DragValue::new(
                 &mut gui.file_diff_result_window.auto_refresh_interval_ms,
             ));
+            ui.checkbox(&mut gui.file_diff_result_window.show_equal, "Show equal");
+        });
+        ui.horizontal(|ui| {
+            let win = &mut gui.file_diff_result_window;
+            ui.checkbox(&mut win.overlay, "Highlight in views");
+            ui.color_edit_button_srgb(&mut win.overlay_color);
+            ui.separator();
+            if ui
+                .button("⏴")
+                .on_hover_text("Previous difference (Alt+Up)")
+                .clicked()
+            {
+                win.goto_diff(app, false);
+            }
+            if ui
+                .button("⏵")
+                .on_hover_text("Next difference (Alt+Down)")
+                .clicked()
+            {
+                win.goto_diff(app, true);
+            }
         });
         ui.separator();
         let mut action = Action::None;
+        let win = &gui.file_diff_result_window;
+        let shown: Vec<&Hunk> = win
+            .hunks
+            .iter()
+            .filter(|hunk| win.show_equal || hunk.kind != HunkKind::Equal)
+            .collect();
         egui_extras::TableBuilder::new(ui)
-            .columns(Size::initial(100.0), 5)
+            .columns(Size::initial(100.0), 7)
             .resizable(true)
             .striped(true)
             .header(32.0, |mut row| {
                 row.col(|ui| {
-                    ui.label("My value");
+                    ui.label("Kind");
+                });
+                row.col(|ui| {
+                    ui.label("Offset");
                 });
                 row.col(|ui| {
-                    ui.label("File value");
+                    ui.label("File offset");
                 });
                 row.col(|ui| {
-                    ui.label("Offset");
+                    ui.label("Length");
+                });
+                row.col(|ui| {
+                    ui.label("My bytes / file bytes");
                 });
                 row.col(|ui| {
                     ui.label("Region");
@@ -123,97 +235,103 @@ impl FileDiffResultWindow {
                 });
             })
             .body(|body| {
-                body.rows(
-                    20.0,
-                    gui.file_diff_result_window.diff_entries.len(),
-                    |idx, mut row| {
-                        let entry = &gui.file_diff_result_window.diff_entries[idx];
-                        row.col(|ui| {
-                            ui.label(entry.my_val.to_string());
-                        });
-                        row.col(|ui| {
-                            ui.label(entry.file_val.to_string());
-                        });
-                        row.col(|ui| {
-                            if ui
-                                .link(entry.offset.to_string())
-                                .context_menu(|ui| {
-                                    if ui.button("Add bookmark").clicked() {
-                                        let idx = app.meta_state.meta.bookmarks.len();
-                                        app.meta_state.meta.bookmarks.push(Bookmark {
-                                            offset: entry.offset,
-                                            label: "New bookmark".into(),
-                                            desc: String::new(),
-                                            value_type: ValueType::None,
-                                        });
-                                        gui.bookmarks_window.open.set(true);
-                                        gui.bookmarks_window.selected = Some(idx);
+                body.rows(20.0, shown.len(), |idx, mut row| {
+                    let hunk = shown[idx];
+                    let offset = hunk.a.start;
+                    row.col(|ui| {
+                        ui.label(hunk.kind.label());
+                    });
+                    row.col(|ui| {
+                        if ui
+                            .link(offset.to_string())
+                            .context_menu(|ui| {
+                                if ui.button("Add bookmark").clicked() {
+                                    let idx = app.meta_state.meta.bookmarks.len();
+                                    app.meta_state.meta.bookmarks.push(Bookmark {
+                                        offset,
+                                        label: format!("{} bytes", hunk.kind.label()),
+                                        desc: String::new(),
+                                        value_type: ValueType::None,
+                                        anchor: None,
+                                    });
+                                    gui.bookmarks_window.open.set(true);
+                                    gui.bookmarks_window.selected = Some(idx);
+                                }
+                            })
+                            .clicked()
+                        {
+                            action = Action::Goto(offset);
+                        }
+                    });
+                    row.col(|ui| {
+                        ui.label(hunk.b.start.to_string());
+                    });
+                    row.col(|ui| {
+                        if hunk.a.len() == hunk.b.len() {
+                            ui.label(hunk.a.len().to_string());
+                        } else {
+                            ui.label(format!("{} / {}", hunk.a.len(), hunk.b.len()));
+                        }
+                    });
+                    row.col(|ui| {
+                        let mine = app.data.get(hunk.a.clone()).unwrap_or_default();
+                        let file = win.file_data.get(hunk.b.clone()).unwrap_or_default();
+                        ui.label(format!("{}/ {}", preview(mine), preview(file)));
+                    });
+                    row.col(|ui| {
+                        match find_most_specific_region_for_offset(
+                            &app.meta_state.meta.low.regions,
+                            offset,
+                        ) {
+                            Some(reg_key) => {
+                                let reg = &app.meta_state.meta.low.regions[reg_key];
+                                ui.menu_button(&reg.name, |ui| {
+                                    if ui.button("Remove region from results").clicked() {
+                                        action = Action::RemoveRegion(reg_key);
+                                        ui.close_menu();
                                     }
                                 })
-                                .clicked()
-                            {
-                                action = Action::Goto(entry.offset);
+                                .response
+                                .context_menu(|ui| region_context_menu!(ui, app, reg, action));
                             }
-                        });
-                        row.col(|ui| {
-                            match find_most_specific_region_for_offset(
-                                &app.meta_state.meta.low.regions,
-                                entry.offset,
-                            ) {
-                                Some(reg_key) => {
-                                    let reg = &app.meta_state.meta.low.regions[reg_key];
-                                    ui.menu_button(&reg.name, |ui| {
-                                        if ui.button("Remove region from results").clicked() {
-                                            action = Action::RemoveRegion(reg_key);
-                                            ui.close_menu();
-                                        }
-                                    })
-                                    .response
-                                    .context_menu(|ui| region_context_menu!(ui, app, reg, action));
-                                }
-                                None => {
-                                    ui.label("[no region]");
-                                }
+                            None => {
+                                ui.label("[no region]");
                             }
-                        });
-                        row.col(|ui| {
-                            match app
-                                .meta_state
-                                .meta
-                                .bookmarks
-                                .iter()
-                                .enumerate()
-                                .find(|(_i, b)| b.offset == entry.offset)
-                            {
-                                Some((idx, bookmark)) => {
-                                    if ui
-                                        .link(&bookmark.label)
-                                        .on_hover_text(&bookmark.desc)
-                                        .clicked()
-                                    {
-                                        gui.bookmarks_window.open.set(true);
-                                        gui.bookmarks_window.selected = Some(idx);
-                                    }
-                                }
-                                None => {
-                                    ui.label("-");
+                        }
+                    });
+                    row.col(|ui| {
+                        match app
+                            .meta_state
+                            .meta
+                            .bookmarks
+                            .iter()
+                            .enumerate()
+                            .find(|(_i, b)| b.offset == offset || hunk.a.contains(&b.offset))
+                        {
+                            Some((idx, bookmark)) => {
+                                if ui
+                                    .link(&bookmark.label)
+                                    .on_hover_text(&bookmark.desc)
+                                    .clicked()
+                                {
+                                    gui.bookmarks_window.open.set(true);
+                                    gui.bookmarks_window.selected = Some(idx);
                                 }
                             }
-                        });
-                    },
-                );
+                            None => {
+                                ui.label("-");
+                            }
+                        }
+                    });
+                });
             });
         match action {
             Action::None => {}
-            Action::Goto(off) => {
-                app.center_view_on_offset(off);
-                app.edit_state.set_cursor(off);
-                app.hex_ui.flash_cursor();
-            }
-            Action::RemoveRegion(key) => gui.file_diff_result_window.diff_entries.retain(|en| {
+            Action::Goto(off) => goto(app, off),
+            Action::RemoveRegion(key) => gui.file_diff_result_window.hunks.retain(|hunk| {
                 let reg = find_most_specific_region_for_offset(
                     &app.meta_state.meta.low.regions,
-                    en.offset,
+                    hunk.a.start,
                 );
                 reg != Some(key)
             }),
@@ -221,6 +339,14 @@ impl FileDiffResultWindow {
     }
 }
 
+fn goto(app: &mut App, off: usize) {
+    // Deletions at the end point past the data
+    let off = off.min(app.data.len().saturating_sub(1));
+    app.center_view_on_offset(off);
+    app.edit_state.set_cursor(off);
+    app.hex_ui.flash_cursor();
+}
+
 enum Action {
     None,
     Goto(usize),
@@ -1,13 +1,20 @@
 use {
     super::{
         message_dialog::{Icon, MessageDialog},
+        watch_window::add_to_watch_list,
         window_open::WindowOpen,
     },
     crate::{
         app::App,
+        char_table::CharTable,
+        data::Data,
         meta::{find_most_specific_region_for_offset, Bookmark, Meta, ValueType},
         parse_radix::parse_guess_radix,
         region_context_menu,
+        shell::msg_fail,
+        text_conv,
+        view::{TextKind, ViewKind},
+        watch_list::WatchType,
     },
     egui_extras::{Size, StripBuilder, TableBuilder},
     egui_sfml::egui::{self, Align, Ui},
@@ -19,6 +26,8 @@ pub enum FindType {
     #[default]
     U8,
     Ascii,
+    /// Text in the chosen text kind
+    Text,
 }
 
 impl FindType {
@@ -26,6 +35,7 @@ impl FindType {
         match self {
             FindType::U8 => "u8",
             FindType::Ascii => "ascii",
+            FindType::Text => "text",
         }
     }
 }
@@ -43,12 +53,22 @@ pub struct FindDialog {
     /// When Some, the results list should be scrolled to the offset of that result
     pub scroll_to: Option<usize>,
     pub find_type: FindType,
+    /// The encoding to search text in
+    pub text_kind: TextKind,
     pub filter_results: bool,
     /// Used for increased/decreased unknown value search
-    pub data_snapshot: Vec<u8>,
+    pub data_snapshot: Data,
 }
 
 impl FindDialog {
+    /// Forget the results and the snapshot, which belong to the data they were found in
+    pub fn clear_results(&mut self) {
+        self.results_vec.clear();
+        self.results_set.clear();
+        self.result_cursor = 0;
+        self.scroll_to = None;
+        self.data_snapshot = Data::default();
+    }
     pub fn ui(ui: &mut Ui, gui: &mut crate::gui::Gui, app: &mut App) {
         egui::ComboBox::new("type_combo", "Data type")
             .selected_text(gui.find_dialog.find_type.label())
@@ -63,10 +83,41 @@ impl FindDialog {
                     FindType::Ascii,
                     FindType::Ascii.label(),
                 );
+                ui.selectable_value(
+                    &mut gui.find_dialog.find_type,
+                    FindType::Text,
+                    FindType::Text.label(),
+                );
             });
+        if gui.find_dialog.find_type == FindType::Text {
+            egui::ComboBox::new("find_text_kind_combo", "Text kind")
+                .selected_text(gui.find_dialog.text_kind.name())
+                .show_ui(ui, |ui| {
+                    for kind in TextKind::all() {
+                        let name = kind.name();
+                        ui.selectable_value(&mut gui.find_dialog.text_kind, kind, name);
+                    }
+                    ui.selectable_value(&mut gui.find_dialog.text_kind, TextKind::ShiftJis, TextKind::ShiftJis.name())
+                        .on_hover_text("Text views can't show Shift-JIS yet, because the font has no Japanese glyphs");
+                });
+            if ui.button("Load table...").on_hover_text("Search using a custom character table (.tbl)").clicked()
+                && let Some(path) = rfd::FileDialog::new().add_filter("Character table", &["tbl"]).pick_file()
+            {
+                match CharTable::load(&path) {
+                    Ok(table) => gui.find_dialog.text_kind = TextKind::Table(table),
+                    Err(e) => msg_fail(&e, "Failed to load table", &mut gui.msg_dialog),
+                }
+            }
+        }
         let re = ui.text_edit_singleline(&mut gui.find_dialog.input);
         if gui.find_dialog.open.just_now() {
             re.request_focus();
+            // Search in the same encoding as the text view being looked at
+            if let Some(key) = app.hex_ui.focused_view
+                && let ViewKind::Text(text) = &app.meta_state.meta.views[key].view.kind
+            {
+                gui.find_dialog.text_kind = text.text_kind.clone();
+            }
         }
         if re.lost_focus() && ui.input().key_pressed(egui::Key::Enter) {
             do_search(app, gui);
@@ -100,14 +151,23 @@ impl FindDialog {
                         |i, mut row| {
                             let off = gui.find_dialog.results_vec[i];
                             let col1_re = row.col(|ui| {
+                                let label = app.offset_label(off);
                                 if ui.selectable_label(
                                     gui.find_dialog.result_cursor == i,
-                                    off.to_string(),
+                                    label,
                                 ).context_menu(|ui| {
                                     if ui.button("Remove from results").clicked() {
                                         action = Action::RemoveIdxFromResults(i);
                                         ui.close_menu();
                                     }
+                                    ui.menu_button("Add to watch list", |ui| {
+                                        for ty in WatchType::ALL {
+                                            if ui.button(ty.name()).clicked() {
+                                                action = Action::AddToWatchList(i, ty);
+                                                ui.close_menu();
+                                            }
+                                        }
+                                    });
                                 }).clicked() {
                                     app.search_focus(off);
                                     gui.find_dialog.result_cursor = i;
@@ -153,6 +213,7 @@ impl FindDialog {
                                             label: "New bookmark".into(),
                                             desc: String::new(),
                                             value_type: ValueType::None,
+                                            anchor: None,
                                         });
                                         gui.bookmarks_window.open.set(true);
                                         gui.bookmarks_window.selected = Some(idx);
@@ -185,6 +246,11 @@ impl FindDialog {
                         gui.find_dialog.results_vec.remove(idx);
                         gui.find_dialog.results_set.remove(&idx);
                     },
+                    Action::AddToWatchList(idx, ty) => {
+                        let off = gui.find_dialog.results_vec[idx];
+                        let label = format!("Find result {}", idx + 1);
+                        add_to_watch_list(gui, app, off, ty, label);
+                    },
                 }
             });
             strip.cell(|ui| {
@@ -220,6 +286,7 @@ enum Action {
     None,
     RemoveRegionFromResults(crate::meta::RegionKey),
     RemoveIdxFromResults(usize),
+    AddToWatchList(usize, WatchType),
 }
 
 fn do_search(app: &mut App, gui: &mut crate::gui::Gui) {
@@ -236,16 +303,34 @@ fn do_search(app: &mut App, gui: &mut crate::gui::Gui) {
                 dia.results_set.insert(offset);
             }
         }
+        FindType::Text => match text_conv::encode(&dia.text_kind, &dia.input) {
+            Some(needle) => {
+                for offset in memchr::memmem::find_iter(&app.data, &needle) {
+                    dia.results_vec.push(offset);
+                    dia.results_set.insert(offset);
+                }
+            }
+            None => gui.msg_dialog.open(
+                Icon::Error,
+                "Find error",
+                format!("The text can't be encoded as {}", dia.text_kind.name()),
+            ),
+        },
     }
     if let Some(&off) = dia.results_vec.first() {
         app.search_focus(off);
     }
 }
 
+/// Remember the current data for the increased/decreased searches
+fn take_snapshot(dia: &mut FindDialog, app: &App) {
+    dia.data_snapshot = app.data.snapshot();
+}
+
 fn find_u8(dia: &mut FindDialog, app: &mut App, msg: &mut MessageDialog) {
     match dia.input.as_str() {
         "?" => {
-            dia.data_snapshot = app.data.clone();
+            take_snapshot(dia, app);
             dia.results_vec.clear();
             dia.results_set.clear();
             for i in 0..app.data.len() {
@@ -266,7 +351,7 @@ fn find_u8(dia: &mut FindDialog, app: &mut App, msg: &mut MessageDialog) {
                     }
                 }
             }
-            dia.data_snapshot = app.data.clone();
+            take_snapshot(dia, app);
         }
         "=" => {
             if dia.filter_results {
@@ -281,7 +366,7 @@ fn find_u8(dia: &mut FindDialog, app: &mut App, msg: &mut MessageDialog) {
                     }
                 }
             }
-            dia.data_snapshot = app.data.clone();
+            take_snapshot(dia, app);
         }
         "!=" => {
             if dia.filter_results {
@@ -296,7 +381,7 @@ fn find_u8(dia: &mut FindDialog, app: &mut App, msg: &mut MessageDialog) {
                     }
                 }
             }
-            dia.data_snapshot = app.data.clone();
+            take_snapshot(dia, app);
         }
         "<" => {
             if dia.filter_results {
@@ -311,7 +396,7 @@ fn find_u8(dia: &mut FindDialog, app: &mut App, msg: &mut MessageDialog) {
                     }
                 }
             }
-            dia.data_snapshot = app.data.clone();
+            take_snapshot(dia, app);
         }
         _ => match parse_guess_radix(&dia.input) {
             Ok(needle) => {
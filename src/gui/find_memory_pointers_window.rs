@@ -1,14 +1,59 @@
 use {
-    super::{window_open::WindowOpen, Gui},
-    crate::{app::App, shell::msg_fail},
+    super::{message_dialog::Icon, window_open::WindowOpen, Gui},
+    crate::{
+        app::App,
+        meta::PointerPath,
+        parse_radix::parse_guess_radix,
+        proc_mem::{self, PointerScanParams, ProcMem, ScanProgress},
+        shell::{msg_fail, msg_if_fail},
+    },
     egui_extras::{Size, TableBuilder},
     egui_sfml::{egui, sfml::graphics::Font},
+    std::{
+        fmt::Write,
+        sync::{atomic::Ordering, Arc},
+        thread::JoinHandle,
+    },
 };
 
-#[derive(Default)]
 pub struct FindMemoryPointersWindow {
     pub open: WindowOpen,
     pointers: Vec<PtrEntry>,
+    tab: Tab,
+    target_string: String,
+    max_depth: u8,
+    max_offset: usize,
+    found_paths: Vec<PointerPath>,
+    /// The pointer path scan running in the background, if any
+    scan: Option<Scan>,
+}
+
+struct Scan {
+    progress: Arc<ScanProgress>,
+    handle: JoinHandle<Vec<PointerPath>>,
+}
+
+impl Default for FindMemoryPointersWindow {
+    fn default() -> Self {
+        Self {
+            open: WindowOpen::default(),
+            pointers: Vec::new(),
+            tab: Tab::default(),
+            target_string: String::new(),
+            max_depth: 3,
+            max_offset: 0x1000,
+            found_paths: Vec::new(),
+            scan: None,
+        }
+    }
+}
+
+#[derive(Default, PartialEq, Eq)]
+enum Tab {
+    #[default]
+    Pointers,
+    PathScan,
+    SavedPaths,
 }
 
 struct PtrEntry {
@@ -17,6 +62,9 @@ struct PtrEntry {
     range_idx: usize,
 }
 
+/// Stop the pointer path scan after finding this many paths
+const MAX_PATH_RESULTS: usize = 10_000;
+
 impl FindMemoryPointersWindow {
     pub fn ui(ui: &mut egui::Ui, gui: &mut Gui, app: &mut App, font: &Font) {
         let Some(pid) = gui.open_process_window.selected_pid else {
@@ -25,6 +73,7 @@ impl FindMemoryPointersWindow {
         };
         let win = &mut gui.find_memory_pointers_window;
         if win.open.just_now() {
+            win.pointers.clear();
             for (i, wnd) in app
                 .data
                 .array_windows::<{ (usize::BITS / 8) as usize }>()
@@ -41,74 +90,304 @@ impl FindMemoryPointersWindow {
                     });
                 }
             }
+            win.target_string = format!("{:X}", app.offset_to_addr(app.edit_state.cursor));
         }
+        ui.horizontal(|ui| {
+            ui.selectable_value(&mut win.tab, Tab::Pointers, "Pointers in view");
+            ui.selectable_value(&mut win.tab, Tab::PathScan, "Pointer path scan");
+            ui.selectable_value(
+                &mut win.tab,
+                Tab::SavedPaths,
+                format!("Saved paths ({})", app.meta_state.meta.pointer_paths.len()),
+            );
+        });
+        ui.separator();
         let mut action = Action::None;
-        TableBuilder::new(ui)
-            .column(Size::initial(100.))
-            .column(Size::remainder())
-            .column(Size::initial(100.))
-            .striped(true)
-            .header(20.0, |mut row| {
-                row.col(|ui| {
-                    ui.label("Location");
-                });
-                row.col(|ui| {
-                    ui.label("Region");
+        match win.tab {
+            Tab::Pointers => {
+                TableBuilder::new(ui)
+                    .column(Size::initial(100.))
+                    .column(Size::remainder())
+                    .column(Size::initial(100.))
+                    .striped(true)
+                    .header(20.0, |mut row| {
+                        row.col(|ui| {
+                            ui.label("Location");
+                        });
+                        row.col(|ui| {
+                            ui.label("Region");
+                        });
+                        row.col(|ui| {
+                            ui.label("Pointer");
+                        });
+                    })
+                    .body(|body| {
+                        body.rows(20.0, win.pointers.len(), |idx, mut row| {
+                            let en = &win.pointers[idx];
+                            row.col(|ui| {
+                                if ui.link(format!("{:X}", en.src_idx)).clicked() {
+                                    action = Action::Goto(en.src_idx);
+                                }
+                            });
+                            row.col(|ui| {
+                                let range = &gui.open_process_window.map_ranges[en.range_idx];
+                                ui.label(
+                                    range
+                                        .filename()
+                                        .map(|p| p.display().to_string())
+                                        .unwrap_or_else(|| String::from("<unnamed>")),
+                                );
+                            });
+                            row.col(|ui| {
+                                if ui.link(format!("{:X}", en.ptr)).clicked() {
+                                    action = Action::GotoAddr(en.ptr);
+                                }
+                            });
+                        });
+                    });
+            }
+            Tab::PathScan => {
+                ui.horizontal(|ui| {
+                    ui.label("Target address");
+                    ui.text_edit_singleline(&mut win.target_string);
+                    if ui
+                        .button("Cursor")
+                        .on_hover_text("Use address of cursor")
+                        .clicked()
+                    {
+                        win.target_string =
+                            format!("{:X}", app.offset_to_addr(app.edit_state.cursor));
+                    }
                 });
-                row.col(|ui| {
-                    ui.label("Pointer");
+                ui.horizontal(|ui| {
+                    ui.label("Max depth");
+                    ui.add(egui::DragValue::new(&mut win.max_depth).clamp_range(1..=8));
+                    ui.label("Max offset");
+                    ui.add(egui::DragValue::new(&mut win.max_offset));
                 });
-            })
-            .body(|body| {
-                body.rows(20.0, win.pointers.len(), |idx, mut row| {
-                    let en = &win.pointers[idx];
-                    row.col(|ui| {
-                        if ui.link(format!("{:X}", en.src_idx)).clicked() {
-                            action = Action::Goto(en.src_idx);
+                if win
+                    .scan
+                    .as_ref()
+                    .is_some_and(|scan| scan.handle.is_finished())
+                    && let Some(scan) = win.scan.take()
+                {
+                    match scan.handle.join() {
+                        Ok(paths) => win.found_paths = paths,
+                        Err(_) => gui.msg_dialog.open(
+                            Icon::Error,
+                            "Pointer path scan failed",
+                            "The scan thread panicked",
+                        ),
+                    }
+                }
+                match &win.scan {
+                    Some(scan) => {
+                        ui.horizontal(|ui| {
+                            ui.add(
+                                egui::ProgressBar::new(scan.progress.fraction())
+                                    .desired_width(200.0)
+                                    .show_percentage(),
+                            );
+                            if ui
+                                .button("Cancel")
+                                .on_hover_text("Stop the scan, keeping the paths found so far")
+                                .clicked()
+                            {
+                                scan.progress.cancel.store(true, Ordering::Relaxed);
+                            }
+                        });
+                    }
+                    None => {
+                        if ui.button("Scan").clicked() {
+                            let result: anyhow::Result<()> = try {
+                                let target = parse_guess_radix(&win.target_string)?;
+                                let mem = ProcMem::open(pid)?;
+                                let ranges = gui.open_process_window.map_ranges.clone();
+                                let params = PointerScanParams {
+                                    target,
+                                    max_depth: win.max_depth,
+                                    max_offset: win.max_offset,
+                                    max_results: MAX_PATH_RESULTS,
+                                };
+                                let progress = Arc::new(ScanProgress::default());
+                                let thread_progress = Arc::clone(&progress);
+                                let handle = std::thread::spawn(move || {
+                                    proc_mem::scan_pointer_paths(
+                                        &mem,
+                                        &ranges,
+                                        &params,
+                                        &thread_progress,
+                                    )
+                                });
+                                win.scan = Some(Scan { progress, handle });
+                            };
+                            msg_if_fail(result, "Pointer path scan failed", &mut gui.msg_dialog);
                         }
+                        ui.label(format!("{} paths found", win.found_paths.len()));
+                    }
+                }
+                TableBuilder::new(ui)
+                    .column(Size::remainder())
+                    .column(Size::initial(60.))
+                    .striped(true)
+                    .header(20.0, |mut row| {
+                        row.col(|ui| {
+                            ui.label("Path");
+                        });
+                        row.col(|ui| {
+                            ui.label("Action");
+                        });
+                    })
+                    .body(|body| {
+                        body.rows(20.0, win.found_paths.len(), |idx, mut row| {
+                            let path = &win.found_paths[idx];
+                            row.col(|ui| {
+                                ui.label(path_string(path));
+                            });
+                            row.col(|ui| {
+                                if ui.button("Save").clicked() {
+                                    action = Action::Save(idx);
+                                }
+                            });
+                        });
                     });
-                    row.col(|ui| {
-                        let range = &gui.open_process_window.map_ranges[en.range_idx];
-                        ui.label(
-                            range
-                                .filename()
-                                .map(|p| p.display().to_string())
-                                .unwrap_or_else(|| String::from("<unnamed>")),
-                        );
-                    });
-                    row.col(|ui| {
-                        let range = &gui.open_process_window.map_ranges[en.range_idx];
-                        if ui.link(format!("{:X}", en.ptr)).clicked() {
-                            match app.load_proc_memory(
-                                pid,
-                                range.start(),
-                                range.size(),
-                                range.is_write(),
-                                font,
-                                &mut gui.msg_dialog,
-                            ) {
-                                Ok(()) => action = Action::Goto(en.ptr - range.start()),
-                                Err(e) => {
-                                    msg_fail(&e, "failed to load proc memory", &mut gui.msg_dialog)
+            }
+            Tab::SavedPaths => {
+                let paths = &mut app.meta_state.meta.pointer_paths;
+                TableBuilder::new(ui)
+                    .column(Size::initial(140.))
+                    .column(Size::remainder())
+                    .column(Size::initial(100.))
+                    .striped(true)
+                    .header(20.0, |mut row| {
+                        row.col(|ui| {
+                            ui.label("Label");
+                        });
+                        row.col(|ui| {
+                            ui.label("Path");
+                        });
+                        row.col(|ui| {
+                            ui.label("Action");
+                        });
+                    })
+                    .body(|body| {
+                        body.rows(20.0, paths.len(), |idx, mut row| {
+                            let path = &mut paths[idx];
+                            row.col(|ui| {
+                                ui.text_edit_singleline(&mut path.label);
+                            });
+                            row.col(|ui| {
+                                ui.label(path_string(path));
+                            });
+                            row.col(|ui| {
+                                if ui
+                                    .button("Go")
+                                    .on_hover_text("Resolve path and jump to its target")
+                                    .clicked()
+                                {
+                                    action = Action::Resolve(idx);
                                 }
-                            }
-                        }
+                                if ui.button("🗑").clicked() {
+                                    action = Action::Remove(idx);
+                                }
+                            });
+                        });
                     });
-                });
-            });
+            }
+        }
         match action {
             Action::Goto(off) => {
                 app.center_view_on_offset(off);
                 app.edit_state.set_cursor(off);
                 app.hex_ui.flash_cursor();
             }
+            Action::GotoAddr(addr) => goto_addr(addr, pid, gui, app, font),
+            Action::Save(idx) => {
+                let mut path = gui.find_memory_pointers_window.found_paths[idx].clone();
+                path.label = format!("Path {}", app.meta_state.meta.pointer_paths.len() + 1);
+                app.meta_state.meta.pointer_paths.push(path);
+            }
+            Action::Resolve(idx) => {
+                let result: anyhow::Result<usize> = try {
+                    let mem = ProcMem::open(pid)?;
+                    proc_mem::resolve_pointer_path(
+                        &mem,
+                        &gui.open_process_window.map_ranges,
+                        &app.meta_state.meta.pointer_paths[idx],
+                    )?
+                };
+                match result {
+                    Ok(addr) => goto_addr(addr, pid, gui, app, font),
+                    Err(e) => msg_fail(&e, "Failed to resolve pointer path", &mut gui.msg_dialog),
+                }
+            }
+            Action::Remove(idx) => {
+                app.meta_state.meta.pointer_paths.remove(idx);
+            }
             Action::None => {}
         }
-        win.open.post_ui();
+        gui.find_memory_pointers_window.open.post_ui();
+    }
+}
+
+/// Jump to `addr`, loading the map range containing it if it's not loaded
+fn goto_addr(addr: usize, pid: sysinfo::Pid, gui: &mut Gui, app: &mut App, font: &Font) {
+    if let Some(off) = app
+        .addr_map
+        .as_ref()
+        .and_then(|map| map.addr_to_offset(addr))
+    {
+        app.center_view_on_offset(off);
+        app.edit_state.set_cursor(off);
+        app.hex_ui.flash_cursor();
+        return;
+    }
+    let ranges = &gui.open_process_window.map_ranges;
+    let Some(range) = proc_mem::range_idx_for_addr(ranges, addr).map(|idx| &ranges[idx]) else {
+        msg_fail(
+            &anyhow::anyhow!("No readable map range contains {addr:X}"),
+            "Failed to jump to address",
+            &mut gui.msg_dialog,
+        );
+        return;
+    };
+    match app.load_proc_memory(
+        pid,
+        range.start(),
+        range.size(),
+        range.is_write(),
+        font,
+        &mut gui.msg_dialog,
+    ) {
+        Ok(()) => {
+            let off = addr - range.start();
+            app.center_view_on_offset(off);
+            app.edit_state.set_cursor(off);
+            app.hex_ui.flash_cursor();
+        }
+        Err(e) => msg_fail(&e, "failed to load proc memory", &mut gui.msg_dialog),
+    }
+}
+
+/// Human readable representation of a pointer path, like `game.exe+1F0 → +10 → +8`
+fn path_string(path: &PointerPath) -> String {
+    let mut s = format!(
+        "{}+{:X}",
+        proc_mem::module_name(&path.module),
+        path.module_offset
+    );
+    for off in &path.offsets {
+        // Writing to a String can't fail
+        let _ = write!(s, " → +{off:X}");
     }
+    s
 }
 
 enum Action {
     Goto(usize),
+    GotoAddr(usize),
+    Save(usize),
+    Resolve(usize),
+    Remove(usize),
     None,
 }
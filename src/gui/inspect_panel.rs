@@ -39,7 +39,8 @@ pub struct InspectPanel {
     changed_one: bool,
     big_endian: bool,
     format: Format,
-    /// If true, go to offset action is relative to the hard seek argument
+    /// If true, offsets are shown as real addresses (hard seek applied, or process address),
+    /// and go to offset action takes real addresses
     offset_relative: bool,
     /// The value of the cursor on the previous frame. Used to determine when the cursor changes
     pub prev_frame_inspect_offset: usize,
@@ -428,14 +429,15 @@ pub fn ui(ui: &mut Ui, app: &mut App, gui: &mut crate::gui::Gui, mouse_pos: View
     let offset = match app.hex_ui.interact_mode {
         InteractMode::View => {
             if let Some((off, _view_idx)) = app.byte_offset_at_pos(mouse_pos.x, mouse_pos.y) {
-                let mut add = 0;
-                if gui.inspect_panel.offset_relative {
-                    add = app.args.src.hard_seek.unwrap_or(0);
-                }
-                ui.link(format!("offset: {} (0x{:x})", off + add, off + add))
+                let shown = if gui.inspect_panel.offset_relative {
+                    app.offset_to_addr(off)
+                } else {
+                    off
+                };
+                ui.link(format!("offset: {} (0x{:x})", shown, shown))
                     .context_menu(|ui| {
                         if ui.button("Copy to clipboard").clicked() {
-                            clipboard::set_string(&format!("{:x}", off + add));
+                            clipboard::set_string(&format!("{:x}", shown));
                             ui.close_menu();
                         }
                     });
@@ -447,7 +449,7 @@ pub fn ui(ui: &mut Ui, app: &mut App, gui: &mut crate::gui::Gui, mouse_pos: View
         InteractMode::Edit => edit_offset(app, gui, ui),
     };
     ui.checkbox(&mut gui.inspect_panel.offset_relative, "Relative offset")
-        .on_hover_text("Offset relative to --hard-seek");
+        .on_hover_text("Offset relative to --hard-seek, or real address for process memory");
     if app.data.is_empty() {
         return;
     }
@@ -549,8 +551,10 @@ pub fn ui(ui: &mut Ui, app: &mut App, gui: &mut crate::gui::Gui, mouse_pos: View
         match action {
             Action::GoToOffset(offset) => {
                 if gui.inspect_panel.offset_relative {
-                    app.edit_state
-                        .set_cursor(offset - app.args.src.hard_seek.unwrap_or(0));
+                    let Some(offset) = app.addr_to_offset(offset) else {
+                        continue;
+                    };
+                    app.edit_state.set_cursor(offset);
                 } else {
                     app.edit_state.set_cursor(offset);
                 }
@@ -571,7 +575,7 @@ pub fn ui(ui: &mut Ui, app: &mut App, gui: &mut crate::gui::Gui, mouse_pos: View
 fn edit_offset(app: &mut App, gui: &mut crate::gui::Gui, ui: &mut Ui) -> usize {
     let mut off = app.edit_state.cursor;
     if gui.inspect_panel.offset_relative {
-        off += app.args.src.hard_seek.unwrap_or(0);
+        off = app.offset_to_addr(off);
     }
     ui.link(format!("offset: {} ({:x}h)", off, off))
         .context_menu(|ui| {
@@ -18,6 +18,11 @@ pub struct LayoutsWindow {
     edit_name: bool,
 }
 impl LayoutsWindow {
+    /// Clear existing meta references
+    pub fn clear_meta_refs(&mut self) {
+        self.selected = LayoutKey::null();
+        self.swap_a = ViewKey::null();
+    }
     pub(crate) fn ui(
         ui: &mut egui_sfml::egui::Ui,
         gui: &mut crate::gui::Gui,
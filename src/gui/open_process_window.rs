@@ -1,8 +1,19 @@
 use {
-    super::window_open::WindowOpen,
-    crate::shell::{msg_fail, msg_if_fail},
+    super::{
+        message_dialog::{Icon, MessageDialog},
+        window_open::WindowOpen,
+    },
+    crate::{
+        app::App,
+        meta::{AttachTarget, AutoAttach},
+        proc_mem,
+        shell::{msg_fail, msg_if_fail},
+        timer::Timer,
+    },
+    anyhow::Context,
     egui_extras::{Size, TableBuilder},
     egui_sfml::{egui, sfml::graphics::Font},
+    std::time::Duration,
     sysinfo::{ProcessExt, Signal, SystemExt},
 };
 
@@ -19,6 +30,9 @@ pub struct OpenProcessWindow {
     addr_sort: Sort,
     size_sort: Sort,
     maps_sort_col: MapsSortColumn,
+    /// The process that was last attached to, for auto re-attach
+    attached_pid: Option<sysinfo::Pid>,
+    attach_poll_timer: Timer,
 }
 
 #[derive(Default, Clone, Copy)]
@@ -85,6 +99,88 @@ impl OpenProcessWindow {
                     if ui.button("Kill").clicked() {
                         proc.kill();
                     }
+                    #[cfg(target_os = "linux")]
+                    if ui
+                        .button("Open address space")
+                        .on_hover_text(
+                            "Open all readable mappings, with offsets shown as addresses",
+                        )
+                        .clicked()
+                        && msg_if_fail(
+                            app.load_proc_addr_space(pid, &win.map_ranges, font),
+                            "Failed to load process address space",
+                            &mut gui.msg_dialog,
+                        )
+                        .is_none()
+                    {
+                        app.resolve_anchors(&win.map_ranges);
+                    }
+                });
+                ui.horizontal(|ui| {
+                    if ui
+                        .button("Anchor to modules")
+                        .on_hover_text(
+                            "Anchor bookmarks and regions to the modules they lie in,\n\
+                             so they can be found again after the process restarts",
+                        )
+                        .clicked()
+                    {
+                        let n = app.anchor_to_modules(&win.map_ranges);
+                        gui.msg_dialog.open(
+                            Icon::Info,
+                            "Anchored",
+                            format!("Anchored {n} bookmarks and regions"),
+                        );
+                    }
+                    if ui
+                        .button("Resolve anchors")
+                        .on_hover_text("Move anchored bookmarks and regions to their anchors")
+                        .clicked()
+                    {
+                        match app.resolve_anchors(&win.map_ranges) {
+                            0 => gui.msg_dialog.open(
+                                Icon::Success,
+                                "Anchors resolved",
+                                "All anchors were resolved",
+                            ),
+                            n => gui.msg_dialog.open(
+                                Icon::Warn,
+                                "Unresolved anchors",
+                                format!(
+                                    "{n} anchors couldn't be resolved.\n\
+                                     Their module isn't loaded, or they lie outside the data."
+                                ),
+                            ),
+                        }
+                    }
+                    let mut auto_attach = app.meta_state.meta.auto_attach.is_some();
+                    if ui
+                        .checkbox(&mut auto_attach, "Auto re-attach")
+                        .on_hover_text(
+                            "When the process exits, attach to the next process with\n\
+                             the same executable name, and load the same memory again",
+                        )
+                        .changed()
+                    {
+                        if auto_attach {
+                            match attach_target(app, &win.map_ranges) {
+                                Some(target) => {
+                                    app.meta_state.meta.auto_attach = Some(AutoAttach {
+                                        exe_name: proc.name().to_owned(),
+                                        target,
+                                    });
+                                    win.attached_pid = Some(pid);
+                                }
+                                None => gui.msg_dialog.open(
+                                    Icon::Error,
+                                    "Can't re-attach",
+                                    "The loaded data doesn't belong to a module of this process",
+                                ),
+                            }
+                        } else {
+                            app.meta_state.meta.auto_attach = None;
+                        }
+                    }
                 });
             }
             TableBuilder::new(ui)
@@ -175,7 +271,7 @@ impl OpenProcessWindow {
                                 )
                                 .clicked()
                             {
-                                msg_if_fail(
+                                if msg_if_fail(
                                     app.load_proc_memory(
                                         pid,
                                         map_range.start(),
@@ -186,7 +282,11 @@ impl OpenProcessWindow {
                                     ),
                                     "Failed to load process memory",
                                     &mut gui.msg_dialog,
-                                );
+                                )
+                                .is_none()
+                                {
+                                    app.resolve_anchors(&win.map_ranges);
+                                }
                                 if let Ok(off) = usize::from_str_radix(&win.addr_filter_string, 16)
                                 {
                                     let off = off - app.args.src.hard_seek.unwrap_or(0);
@@ -282,3 +382,97 @@ impl OpenProcessWindow {
         win.open.post_ui();
     }
 }
+
+/// How often to look for a process to re-attach to
+const ATTACH_POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+/// What to load when re-attaching, based on what's currently loaded
+fn attach_target(app: &App, ranges: &[proc_maps::MapRange]) -> Option<AttachTarget> {
+    if app.addr_map.is_some() {
+        return Some(AttachTarget::AddrSpace);
+    }
+    proc_mem::anchor_for_addr(ranges, app.offset_to_addr(0)).map(AttachTarget::Mapping)
+}
+
+/// If auto re-attach is enabled and the attached process is gone, look for a new process
+/// with the same executable name, load its memory, and resolve the module anchors.
+pub fn update_auto_attach(gui: &mut crate::gui::Gui, app: &mut App, font: &Font) {
+    let Some(attach) = &app.meta_state.meta.auto_attach else {
+        return;
+    };
+    let win = &mut gui.open_process_window;
+    if win.attach_poll_timer.overtime().is_some() {
+        return;
+    }
+    win.attach_poll_timer = Timer::set(ATTACH_POLL_INTERVAL);
+    if let Some(pid) = win.attached_pid
+        && win.sys.refresh_process(pid)
+    {
+        return;
+    }
+    win.sys.refresh_processes();
+    let Some(pid) = win
+        .sys
+        .processes()
+        .iter()
+        .find_map(|(pid, proc)| (proc.name() == attach.exe_name).then_some(*pid))
+    else {
+        return;
+    };
+    let target = attach.target.clone();
+    win.attached_pid = Some(pid);
+    let result: anyhow::Result<()> = try {
+        win.map_ranges = proc_maps::get_process_maps(pid.to_string().parse()?)?;
+        win.selected_pid = Some(pid);
+        // The whole point of re-attaching is to continue with the same meta
+        let keep_meta = std::mem::replace(&mut app.preferences.keep_meta, true);
+        let load_result = load_attach_target(
+            app,
+            pid,
+            &target,
+            &win.map_ranges,
+            font,
+            &mut gui.msg_dialog,
+        );
+        app.preferences.keep_meta = keep_meta;
+        load_result?;
+        app.resolve_anchors(&win.map_ranges);
+    };
+    msg_if_fail(
+        result,
+        "Failed to re-attach to process",
+        &mut gui.msg_dialog,
+    );
+}
+
+fn load_attach_target(
+    app: &mut App,
+    pid: sysinfo::Pid,
+    target: &AttachTarget,
+    ranges: &[proc_maps::MapRange],
+    font: &Font,
+    msg: &mut MessageDialog,
+) -> anyhow::Result<()> {
+    match target {
+        #[cfg(target_os = "linux")]
+        AttachTarget::AddrSpace => app.load_proc_addr_space(pid, ranges, font),
+        #[cfg(not(target_os = "linux"))]
+        AttachTarget::AddrSpace => {
+            anyhow::bail!("Opening the whole address space is only supported on Linux")
+        }
+        AttachTarget::Mapping(anchor) => {
+            let range = proc_mem::resolve_anchor(ranges, anchor)
+                .and_then(|addr| proc_mem::range_idx_for_addr(ranges, addr))
+                .map(|idx| &ranges[idx])
+                .context("Anchored mapping is not loaded")?;
+            app.load_proc_memory(
+                pid,
+                range.start(),
+                range.size(),
+                range.is_write(),
+                font,
+                msg,
+            )
+        }
+    }
+}
@@ -0,0 +1,106 @@
+use {
+    super::{window_open::WindowOpen, Gui},
+    crate::{
+        app::App,
+        ihex_srec::{self, RecordFormat},
+        parse_radix::parse_guess_radix,
+        shell::msg_if_fail,
+    },
+    anyhow::Context,
+    egui_sfml::egui,
+};
+
+pub struct RecordExportWindow {
+    pub open: WindowOpen,
+    format: RecordFormat,
+    /// Address of the first exported byte. Empty means the address it's shown at.
+    base_addr: String,
+    record_size: usize,
+    selection_only: bool,
+    /// Export every segment of a sparse source at its own address
+    keep_segment_addrs: bool,
+}
+
+impl Default for RecordExportWindow {
+    fn default() -> Self {
+        Self {
+            open: WindowOpen::default(),
+            format: RecordFormat::IntelHex,
+            base_addr: String::new(),
+            record_size: 16,
+            selection_only: false,
+            keep_segment_addrs: true,
+        }
+    }
+}
+
+impl RecordExportWindow {
+    pub fn ui(ui: &mut egui::Ui, gui: &mut Gui, app: &mut App) {
+        let win = &mut gui.record_export_window;
+        ui.horizontal(|ui| {
+            for format in [RecordFormat::IntelHex, RecordFormat::Srec] {
+                ui.selectable_value(&mut win.format, format, format.name());
+            }
+        });
+        let sel = app.hex_ui.selection();
+        ui.add_enabled(
+            sel.is_some(),
+            egui::Checkbox::new(&mut win.selection_only, "Selection only"),
+        );
+        let (begin, end) = match sel.filter(|_| win.selection_only) {
+            Some(sel) => (sel.begin, sel.end),
+            None => (0, app.data.len().saturating_sub(1)),
+        };
+        if app.addr_map.is_some() {
+            ui.checkbox(&mut win.keep_segment_addrs, "Keep segment addresses")
+                .on_hover_text("Write each loaded segment at the address it was loaded from");
+        }
+        let keep_segments = win.keep_segment_addrs && app.addr_map.is_some();
+        ui.add_enabled_ui(!keep_segments, |ui| {
+            ui.horizontal(|ui| {
+                ui.label("Base address");
+                ui.add(
+                    egui::TextEdit::singleline(&mut win.base_addr)
+                        .hint_text(format!("{:X}", app.offset_to_addr(begin))),
+                );
+            });
+        });
+        ui.horizontal(|ui| {
+            ui.label("Record size");
+            ui.add(egui::DragValue::new(&mut win.record_size).clamp_range(1..=250));
+        });
+        if ui
+            .add_enabled(!app.data.is_empty(), egui::Button::new("Export..."))
+            .clicked()
+            && let Some(path) = rfd::FileDialog::new().save_file()
+        {
+            let result: anyhow::Result<()> = try {
+                let blocks: Vec<(usize, &[u8])> = match &app.addr_map {
+                    Some(map) if keep_segments => map
+                        .split_range(begin, end)
+                        .map(|(seg, range)| {
+                            (seg.addr + (range.start - seg.offset), &app.data[range])
+                        })
+                        .collect(),
+                    _ => {
+                        let base = if win.base_addr.trim().is_empty() {
+                            app.offset_to_addr(begin)
+                        } else {
+                            parse_guess_radix(win.base_addr.trim())
+                                .context("Invalid base address")?
+                        };
+                        vec![(base, &app.data[begin..=end])]
+                    }
+                };
+                let extra = ihex_srec::Extra {
+                    header: Some(b"hexerator".to_vec()),
+                    start: None,
+                };
+                let text = ihex_srec::write(win.format, &blocks, win.record_size, &extra)?;
+                std::fs::write(path, text)?;
+            };
+            msg_if_fail(result, "Failed to export", &mut gui.msg_dialog);
+        }
+        gui.record_export_window.open.post_ui();
+    }
+}
@@ -0,0 +1,202 @@
+use {
+    super::{window_open::WindowOpen, Gui},
+    crate::{
+        app::{compare, App},
+        meta::{region::Region, RegionKey},
+        shell::msg_fail,
+    },
+    egui_extras::{Size, TableBuilder},
+    egui_sfml::egui,
+};
+
+#[derive(Default)]
+pub struct RegionCompareWindow {
+    pub open: WindowOpen,
+    a: Option<Side>,
+    b: Option<Side>,
+    /// Offsets relative to the region starts where the sides differ, from the last comparison
+    diffs: Option<Vec<usize>>,
+    /// Also list the bytes that are the same on both sides
+    show_equal: bool,
+}
+
+/// One of the two compared ranges of the data
+#[derive(Clone)]
+struct Side {
+    /// The named region this side follows, if it's not a selection
+    key: Option<RegionKey>,
+    region: Region,
+    name: String,
+}
+
+impl RegionCompareWindow {
+    /// Whether the byte at `idx` is in one of the compared ranges, and differs from the other one
+    pub fn differs(&self, data: &[u8], idx: usize) -> bool {
+        match (&self.a, &self.b) {
+            (Some(a), Some(b)) if self.open.is() => {
+                compare::region_differs(data, a.region, b.region, idx)
+            }
+            _ => false,
+        }
+    }
+    /// Forget the regions of the current document, like when switching tabs
+    pub fn clear_meta_refs(&mut self) {
+        self.a = None;
+        self.b = None;
+        self.diffs = None;
+    }
+    pub fn ui(ui: &mut egui::Ui, gui: &mut Gui, app: &mut App) {
+        let win = &mut gui.region_compare_window;
+        let regions = &app.meta_state.meta.low.regions;
+        let selection = app.hex_ui.selection();
+        ui.label("Compare two regions, or two selections of the data byte by byte");
+        egui::Grid::new("region_compare_sides").show(ui, |ui| {
+            for (label, side) in [("A", &mut win.a), ("B", &mut win.b)] {
+                // Follow changes to the named region
+                if let Some(s) = side.as_mut()
+                    && let Some(key) = s.key
+                    && let Some(reg) = regions.get(key)
+                {
+                    s.region = reg.region;
+                }
+                ui.label(label);
+                egui::ComboBox::new(("region_compare", label), "")
+                    .selected_text(side.as_ref().map_or("-", |s| &s.name))
+                    .show_ui(ui, |ui| {
+                        for (key, reg) in regions.iter() {
+                            let selected = side.as_ref().is_some_and(|s| s.key == Some(key));
+                            if ui.selectable_label(selected, &reg.name).clicked() {
+                                *side = Some(Side {
+                                    key: Some(key),
+                                    region: reg.region,
+                                    name: reg.name.clone(),
+                                });
+                            }
+                        }
+                    });
+                if ui
+                    .add_enabled(selection.is_some(), egui::Button::new("Use selection"))
+                    .clicked()
+                    && let Some(sel) = selection
+                {
+                    *side = Some(Side {
+                        key: None,
+                        region: sel,
+                        name: format!("Selection {:X}..={:X}", sel.begin, sel.end),
+                    });
+                }
+                match &side {
+                    Some(s) => ui.label(format!(
+                        "{:X}..={:X} ({} bytes)",
+                        s.region.begin,
+                        s.region.end,
+                        s.region.len()
+                    )),
+                    None => ui.label("Not set"),
+                };
+                ui.end_row();
+            }
+        });
+        let sides = match (&win.a, &win.b) {
+            (Some(a), Some(b)) => Some((a.clone(), b.clone())),
+            _ => None,
+        };
+        ui.horizontal(|ui| {
+            if ui
+                .add_enabled(sides.is_some(), egui::Button::new("Compare"))
+                .clicked()
+                && let Some((a, b)) = &sides
+            {
+                win.diffs = Some(compare::region_diffs(&app.data, a.region, b.region));
+            }
+            if ui
+                .add_enabled(sides.is_some(), egui::Button::new("Side by side"))
+                .on_hover_text("Show both regions next to each other in a new layout")
+                .clicked()
+                && let Some((a, b)) = &sides
+                && let Err(e) =
+                    app.show_regions_side_by_side([(a.key, a.region), (b.key, b.region)])
+            {
+                msg_fail(&e, "Failed to show regions", &mut gui.msg_dialog);
+            }
+            if app.region_compare.is_some()
+                && ui
+                    .button("Close side by side")
+                    .on_hover_text("Remove the layout, views and regions created for comparing")
+                    .clicked()
+            {
+                app.remove_region_compare_views();
+            }
+        });
+        let (Some((a, b)), Some(diffs)) = (&sides, &win.diffs) else {
+            gui.region_compare_window.open.post_ui();
+            return;
+        };
+        if a.region.len() != b.region.len() {
+            ui.label(format!(
+                "Sizes differ: {} vs {} bytes",
+                a.region.len(),
+                b.region.len()
+            ));
+        }
+        ui.horizontal(|ui| {
+            ui.label(format!("{} differing bytes", diffs.len()));
+            ui.checkbox(&mut win.show_equal, "Show equal");
+        });
+        ui.separator();
+        let a_data = compare::region_slice(&app.data, a.region);
+        let b_data = compare::region_slice(&app.data, b.region);
+        let row_count = if win.show_equal {
+            a_data.len().max(b_data.len())
+        } else {
+            diffs.len()
+        };
+        let mut goto = None;
+        TableBuilder::new(ui)
+            .columns(Size::initial(80.0), 4)
+            .column(Size::remainder())
+            .striped(true)
+            .header(20.0, |mut row| {
+                for label in ["Offset", "A offset", "A", "B offset", "B"] {
+                    row.col(|ui| {
+                        ui.label(label);
+                    });
+                }
+            })
+            .body(|body| {
+                body.rows(20.0, row_count, |idx, mut row| {
+                    let rel = if win.show_equal { idx } else { diffs[idx] };
+                    row.col(|ui| {
+                        ui.label(format!("{rel:X}"));
+                    });
+                    for (side, data) in [(a, a_data), (b, b_data)] {
+                        let offset = side.region.begin + rel;
+                        match data.get(rel) {
+                            Some(byte) => {
+                                row.col(|ui| {
+                                    if ui.link(format!("{offset:X}")).clicked() {
+                                        goto = Some(offset);
+                                    }
+                                });
+                                row.col(|ui| {
+                                    ui.label(format!("{byte:02X}"));
+                                });
+                            }
+                            None => {
+                                row.col(|ui| {
+                                    ui.label("-");
+                                });
+                                row.col(|ui| {
+                                    ui.label("-");
+                                });
+                            }
+                        }
+                    }
+                });
+            });
+        if let Some(offset) = goto {
+            app.search_focus(offset);
+        }
+        gui.region_compare_window.open.post_ui();
+    }
+}
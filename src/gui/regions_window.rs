@@ -1,5 +1,5 @@
 use {
-    super::window_open::WindowOpen,
+    super::{util::anchor_ui, window_open::WindowOpen},
     crate::{app::App, meta::RegionKey},
     egui_extras::{Size, TableBuilder},
     egui_sfml::egui::{self, Ui},
@@ -99,12 +99,12 @@ impl RegionsWindow {
                             }
                         });
                         row.col(|ui| {
-                            if ui.link(reg.region.begin.to_string()).clicked() {
+                            if ui.link(app.offset_label(reg.region.begin)).clicked() {
                                 action = Action::Goto(reg.region.begin);
                             }
                         });
                         row.col(|ui| {
-                            if ui.link(reg.region.end.to_string()).clicked() {
+                            if ui.link(app.offset_label(reg.region.end)).clicked() {
                                 action = Action::Goto(reg.region.end);
                             }
                         });
@@ -124,6 +124,7 @@ impl RegionsWindow {
             });
         ui.separator();
         if let &Some(key) = &gui.regions_window.selected_key {
+            let addr = app.offset_to_addr(app.meta_state.meta.low.regions[key].region.begin);
             let reg = &mut app.meta_state.meta.low.regions[key];
             ui.horizontal(|ui| {
                 if gui.regions_window.rename_active {
@@ -143,6 +144,12 @@ impl RegionsWindow {
                 ui.label("Last byte");
                 ui.add(egui::DragValue::new(&mut reg.region.end));
             });
+            anchor_ui(
+                ui,
+                &mut reg.anchor,
+                addr,
+                &gui.open_process_window.map_ranges,
+            );
             if gui.regions_window.select_active {
                 app.hex_ui.select_a = Some(reg.region.begin);
                 app.hex_ui.select_b = Some(reg.region.end);
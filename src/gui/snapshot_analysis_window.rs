@@ -0,0 +1,275 @@
+use {
+    super::{window_open::WindowOpen, Gui},
+    crate::{
+        app::{read_source_to_buf, App},
+        data::Data,
+        meta::{Bookmark, ValueType},
+        shell::msg_if_fail,
+        snapshot_analysis::{self, Criterion, Interp, Match},
+        watch_list::WatchType,
+    },
+    egui_extras::{Size, TableBuilder},
+    egui_sfml::egui,
+    std::fmt::Write,
+};
+
+/// At most this many matches are listed
+const MATCH_LIMIT: usize = 10_000;
+
+pub struct SnapshotAnalysisWindow {
+    pub open: WindowOpen,
+    snapshots: Vec<Snapshot>,
+    criterion: Criterion,
+    types: Vec<WatchType>,
+    little_endian: bool,
+    big_endian: bool,
+    /// Only check offsets that are a multiple of this
+    align: usize,
+    matches: Vec<Match>,
+    total: usize,
+}
+
+struct Snapshot {
+    label: String,
+    data: Data,
+    /// The known value in this snapshot, as typed by the user
+    known: String,
+}
+
+impl Default for SnapshotAnalysisWindow {
+    fn default() -> Self {
+        Self {
+            open: WindowOpen::default(),
+            snapshots: Vec::new(),
+            criterion: Criterion::EqualsKnown,
+            types: vec![WatchType::U8, WatchType::U16, WatchType::U32],
+            little_endian: true,
+            big_endian: false,
+            align: 1,
+            matches: Vec::new(),
+            total: 0,
+        }
+    }
+}
+
+impl SnapshotAnalysisWindow {
+    pub fn ui(ui: &mut egui::Ui, gui: &mut Gui, app: &mut App) {
+        let win = &mut gui.snapshot_analysis_window;
+        ui.label("Find the values that behave the same way across snapshots of the same data");
+        ui.horizontal(|ui| {
+            if ui.button("Add files...").clicked()
+                && let Some(paths) = rfd::FileDialog::new().pick_files()
+            {
+                for path in paths {
+                    let result: anyhow::Result<()> = try {
+                        let data = read_source_to_buf(&path, &app.args.src)?;
+                        win.snapshots.push(Snapshot {
+                            label: path.display().to_string(),
+                            data: data.into(),
+                            known: String::new(),
+                        });
+                    };
+                    msg_if_fail(result, "Failed to add snapshot", &mut gui.msg_dialog);
+                }
+            }
+            if ui
+                .button("Add current data")
+                .on_hover_text("Take a snapshot of the data as it is now")
+                .clicked()
+            {
+                win.snapshots.push(Snapshot {
+                    label: format!("Current data #{}", win.snapshots.len() + 1),
+                    data: app.data.snapshot(),
+                    known: String::new(),
+                });
+            }
+            if ui.button("Clear").clicked() {
+                win.snapshots.clear();
+            }
+        });
+        let mut remove = None;
+        for (i, snap) in win.snapshots.iter_mut().enumerate() {
+            ui.horizontal(|ui| {
+                ui.label(format!(
+                    "{}. {} ({} bytes)",
+                    i + 1,
+                    snap.label,
+                    snap.data.len()
+                ));
+                if win.criterion.uses_known() {
+                    ui.label("Known value");
+                    ui.add(egui::TextEdit::singleline(&mut snap.known).desired_width(80.0));
+                }
+                if ui.button("🗑").clicked() {
+                    remove = Some(i);
+                }
+            });
+        }
+        if let Some(i) = remove {
+            win.snapshots.remove(i);
+        }
+        ui.separator();
+        egui::ComboBox::new("snapshot_criterion", "Criterion")
+            .selected_text(win.criterion.name())
+            .show_ui(ui, |ui| {
+                for criterion in Criterion::ALL {
+                    ui.selectable_value(&mut win.criterion, criterion, criterion.name());
+                }
+            });
+        ui.horizontal(|ui| {
+            ui.label("Types");
+            for ty in WatchType::ALL {
+                let mut checked = win.types.contains(&ty);
+                if ui.checkbox(&mut checked, ty.name()).changed() {
+                    if checked {
+                        win.types.push(ty);
+                    } else {
+                        win.types.retain(|&t| t != ty);
+                    }
+                }
+            }
+        });
+        ui.horizontal(|ui| {
+            ui.checkbox(&mut win.little_endian, "Little endian");
+            ui.checkbox(&mut win.big_endian, "Big endian");
+            ui.label("Alignment");
+            ui.add(egui::DragValue::new(&mut win.align).clamp_range(1..=64));
+        });
+        if ui
+            .add_enabled(win.snapshots.len() >= 2, egui::Button::new("Analyze"))
+            .on_disabled_hover_text("Add at least 2 snapshots")
+            .clicked()
+        {
+            let result: anyhow::Result<()> = try {
+                let known = if win.criterion.uses_known() {
+                    win.snapshots
+                        .iter()
+                        .map(|snap| {
+                            snap.known
+                                .trim()
+                                .parse::<f64>()
+                                .map_err(|e| anyhow::anyhow!("Known value of {}: {e}", snap.label))
+                        })
+                        .collect::<anyhow::Result<Vec<_>>>()?
+                } else {
+                    Vec::new()
+                };
+                let mut interps = Vec::new();
+                for &ty in &win.types {
+                    if win.little_endian || ty.size() == 1 {
+                        interps.push(Interp {
+                            ty,
+                            big_endian: false,
+                        });
+                    }
+                    if win.big_endian && ty.size() > 1 {
+                        interps.push(Interp {
+                            ty,
+                            big_endian: true,
+                        });
+                    }
+                }
+                let snapshots: Vec<&[u8]> =
+                    win.snapshots.iter().map(|snap| &snap.data[..]).collect();
+                let report = snapshot_analysis::analyze(
+                    &snapshots,
+                    &known,
+                    &interps,
+                    win.criterion,
+                    win.align,
+                    MATCH_LIMIT,
+                );
+                win.matches = report.matches;
+                win.total = report.total;
+            };
+            msg_if_fail(result, "Analysis failed", &mut gui.msg_dialog);
+        }
+        if win.total > win.matches.len() {
+            ui.label(format!(
+                "{} matches, showing the first {}",
+                win.total,
+                win.matches.len()
+            ));
+        } else {
+            ui.label(format!("{} matches", win.total));
+        }
+        let mut action = Action::None;
+        TableBuilder::new(ui)
+            .column(Size::initial(100.0))
+            .column(Size::initial(60.0))
+            .column(Size::remainder())
+            .striped(true)
+            .header(20.0, |mut row| {
+                row.col(|ui| {
+                    ui.label("Offset");
+                });
+                row.col(|ui| {
+                    ui.label("Type");
+                });
+                row.col(|ui| {
+                    ui.label("Values");
+                });
+            })
+            .body(|body| {
+                body.rows(20.0, win.matches.len(), |idx, mut row| {
+                    let m = &win.matches[idx];
+                    row.col(|ui| {
+                        let re = ui.link(format!("{:X}", m.offset)).context_menu(|ui| {
+                            if ui.button("Add bookmark").clicked() {
+                                action = Action::Bookmark(idx);
+                                ui.close_menu();
+                            }
+                        });
+                        if re.clicked() {
+                            action = Action::Goto(m.offset);
+                        }
+                    });
+                    row.col(|ui| {
+                        ui.label(m.interp.label());
+                    });
+                    row.col(|ui| {
+                        let mut values = String::new();
+                        for (i, value) in m.values.iter().enumerate() {
+                            if i != 0 {
+                                values.push_str(" → ");
+                            }
+                            let _ = write!(values, "{value}");
+                        }
+                        ui.label(values);
+                    });
+                });
+            });
+        match action {
+            Action::None => {}
+            Action::Goto(offset) => {
+                if offset < app.data.len() {
+                    app.search_focus(offset);
+                }
+            }
+            Action::Bookmark(idx) => {
+                let m = &win.matches[idx];
+                let value_type = match (m.interp.ty, m.interp.big_endian) {
+                    (WatchType::U8, _) => ValueType::U8,
+                    (WatchType::U16, false) => ValueType::U16Le,
+                    _ => ValueType::None,
+                };
+                gui.bookmarks_window.selected = Some(app.meta_state.meta.bookmarks.len());
+                app.meta_state.meta.bookmarks.push(Bookmark {
+                    offset: m.offset,
+                    label: format!("{} ({})", win.criterion.name(), m.interp.label()),
+                    desc: String::new(),
+                    value_type,
+                    anchor: None,
+                });
+                gui.bookmarks_window.open.set(true);
+            }
+        }
+        gui.snapshot_analysis_window.open.post_ui();
+    }
+}
+
+enum Action {
+    None,
+    Goto(usize),
+    Bookmark(usize),
+}
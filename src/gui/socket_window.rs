@@ -0,0 +1,152 @@
+use {
+    super::{message_dialog::Icon, window_open::WindowOpen, Gui},
+    crate::{
+        app::App,
+        shell::msg_if_fail,
+        source::{Socket, SourceProvider},
+    },
+    egui_sfml::{egui, sfml::graphics::Font},
+    std::{
+        sync::{
+            atomic::{AtomicBool, Ordering},
+            mpsc::Receiver,
+            Arc,
+        },
+        time::Duration,
+    },
+};
+
+/// Give up connecting after this long
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(5);
+
+#[derive(Default)]
+pub struct SocketWindow {
+    pub open: WindowOpen,
+    kind: SocketKind,
+    /// `host:port` for TCP, socket file path for Unix sockets
+    addr: String,
+    writable: bool,
+    pending: Option<Pending>,
+}
+
+/// A connection being made on another thread
+struct Pending {
+    /// What's going on, like "Connecting to 127.0.0.1:9000"
+    desc: String,
+    recv: Receiver<std::io::Result<Socket>>,
+    /// Stops listening. Connecting gives up on its own after [`CONNECT_TIMEOUT`].
+    cancel: Arc<AtomicBool>,
+}
+
+impl Pending {
+    /// Run `f` on another thread
+    fn spawn(
+        desc: String,
+        f: impl FnOnce(&AtomicBool) -> std::io::Result<Socket> + Send + 'static,
+    ) -> Self {
+        let (tx, recv) = std::sync::mpsc::channel();
+        let cancel = Arc::new(AtomicBool::new(false));
+        let thread_cancel = Arc::clone(&cancel);
+        std::thread::spawn(move || {
+            // Nobody is waiting for the result anymore if it was cancelled
+            let _ = tx.send(f(&thread_cancel));
+        });
+        Self { desc, recv, cancel }
+    }
+}
+
+#[derive(Default, PartialEq, Eq, Clone, Copy)]
+enum SocketKind {
+    #[default]
+    Tcp,
+    #[cfg(unix)]
+    Unix,
+}
+
+impl SocketWindow {
+    pub fn ui(ui: &mut egui::Ui, gui: &mut Gui, app: &mut App, font: &Font) {
+        let win = &mut gui.socket_window;
+        ui.horizontal(|ui| {
+            ui.selectable_value(&mut win.kind, SocketKind::Tcp, "TCP");
+            #[cfg(unix)]
+            ui.selectable_value(&mut win.kind, SocketKind::Unix, "Unix");
+        });
+        let hint = match win.kind {
+            SocketKind::Tcp => "127.0.0.1:9000",
+            #[cfg(unix)]
+            SocketKind::Unix => "/tmp/test.sock",
+        };
+        ui.add(egui::TextEdit::singleline(&mut win.addr).hint_text(hint));
+        ui.checkbox(&mut win.writable, "Writable")
+            .on_hover_text("Saving sends the edited bytes back");
+        match &win.pending {
+            Some(pending) => {
+                ui.horizontal(|ui| {
+                    ui.spinner();
+                    ui.label(&pending.desc);
+                });
+                if ui.button("Cancel").clicked() {
+                    pending.cancel.store(true, Ordering::Relaxed);
+                    win.pending = None;
+                } else if let Ok(result) = pending.recv.try_recv() {
+                    win.pending = None;
+                    match result {
+                        Ok(socket) => app.load_socket_stream(socket, win.writable, font),
+                        Err(e) => {
+                            gui.msg_dialog
+                                .open(Icon::Error, "Failed to connect", e.to_string())
+                        }
+                    }
+                }
+            }
+            None => {
+                ui.horizontal(|ui| {
+                    let (kind, addr) = (win.kind, win.addr.clone());
+                    if ui.button("Connect").clicked() {
+                        win.pending = Some(Pending::spawn(
+                            format!("Connecting to {addr}"),
+                            move |_| match kind {
+                                SocketKind::Tcp => Socket::connect_tcp(&addr, CONNECT_TIMEOUT),
+                                #[cfg(unix)]
+                                SocketKind::Unix => Socket::connect_unix(&addr),
+                            },
+                        ));
+                    } else if ui
+                        .button("Listen")
+                        .on_hover_text("Accept a single connection")
+                        .clicked()
+                    {
+                        win.pending = Some(Pending::spawn(
+                            format!("Waiting for a connection on {addr}"),
+                            move |cancel| match kind {
+                                SocketKind::Tcp => Socket::accept_tcp(&addr, cancel),
+                                #[cfg(unix)]
+                                SocketKind::Unix => Socket::accept_unix(&addr, cancel),
+                            },
+                        ));
+                    }
+                });
+            }
+        }
+        if let Some(SourceProvider::Socket(stream)) = app.source.as_ref().map(|src| &src.provider) {
+            ui.separator();
+            ui.label(format!("Connected: {}", stream.socket.peer()));
+            let writable = app
+                .source
+                .as_ref()
+                .is_some_and(|src| src.attr.permissions.write);
+            if let Some(sel) = app.hex_ui.selection()
+                && ui
+                    .add_enabled(writable, egui::Button::new("Send selection"))
+                    .clicked()
+            {
+                msg_if_fail(
+                    app.send_to_socket(sel),
+                    "Failed to send selection",
+                    &mut gui.msg_dialog,
+                );
+            }
+        }
+        gui.socket_window.open.post_ui();
+    }
+}
@@ -0,0 +1,91 @@
+use {
+    super::Gui,
+    crate::app::App,
+    egui_sfml::egui::{Context, Ui, Window},
+};
+
+pub fn ui(ui: &mut Ui, gui: &mut Gui, app: &mut App) {
+    let mut switch = None;
+    let mut close = None;
+    let mut new = false;
+    ui.horizontal(|ui| {
+        for idx in 0..app.tabs.len() {
+            let dirty = app.tab_dirty(idx);
+            let mut label = app.tab_label(idx);
+            if dirty {
+                label.push('*');
+            }
+            let re = ui.selectable_label(idx == app.active_tab, label);
+            if re.clicked() {
+                switch = Some(idx);
+            }
+            if re.middle_clicked() || ui.small_button("🗙").on_hover_text("Close tab").clicked() {
+                close = Some(idx);
+            }
+            ui.separator();
+        }
+        new = ui.button("+").on_hover_text("New tab").clicked();
+    });
+    if let Some(idx) = close {
+        request_close(gui, app, idx);
+    } else if let Some(idx) = switch {
+        app.switch_tab(idx);
+        gui.clear_meta_refs();
+    } else if new {
+        app.new_tab();
+        gui.clear_meta_refs();
+    }
+}
+
+/// Close the tab at `idx`, asking first if it has unsaved changes
+pub fn request_close(gui: &mut Gui, app: &mut App, idx: usize) {
+    if app.tab_dirty(idx) {
+        gui.close_tab_prompt = Some(idx);
+    } else {
+        close(gui, app, idx);
+    }
+}
+
+fn close(gui: &mut Gui, app: &mut App, idx: usize) {
+    // Closing the active tab switches to another one
+    let was_active = idx == app.active_tab;
+    app.close_tab(idx);
+    if was_active {
+        gui.clear_meta_refs();
+    }
+}
+
+/// Confirmation for closing a tab with unsaved changes
+pub fn close_prompt_ui(ctx: &Context, gui: &mut Gui, app: &mut App) {
+    let Some(idx) = gui.close_tab_prompt else {
+        return;
+    };
+    if idx >= app.tabs.len() {
+        gui.close_tab_prompt = None;
+        return;
+    }
+    let mut choice = None;
+    Window::new("Unsaved changes")
+        .collapsible(false)
+        .resizable(false)
+        .show(ctx, |ui| {
+            ui.label(format!(
+                "{} has unsaved changes. Close it anyway?",
+                app.tab_label(idx)
+            ));
+            ui.horizontal(|ui| {
+                if ui.button("Close without saving").clicked() {
+                    choice = Some(true);
+                }
+                if ui.button("Cancel").clicked() {
+                    choice = Some(false);
+                }
+            });
+        });
+    if let Some(close_it) = choice {
+        gui.close_tab_prompt = None;
+        if close_it {
+            close(gui, app, idx);
+        }
+    }
+}
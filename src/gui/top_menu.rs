@@ -8,7 +8,7 @@ use {
         app::{col_change_impl_view_perspective, App},
         args::Args,
         damage_region::DamageRegion,
-        shell::msg_if_fail,
+        shell::{msg_fail, msg_if_fail},
         source::SourceProvider,
     },
     egui_sfml::{
@@ -26,6 +26,16 @@ pub fn top_menu(ui: &mut egui::Ui, gui: &mut crate::gui::Gui, app: &mut App, fon
                 crate::shell::open_file(app, font, &mut gui.msg_dialog);
                 ui.close_menu();
             }
+            if ui.button("Open in new tab...").on_hover_text("Open a file without closing the current one").clicked() {
+                app.new_tab();
+                gui.clear_meta_refs();
+                crate::shell::open_file(app, font, &mut gui.msg_dialog);
+                // Nothing was opened, go back to the previous tab
+                if app.data.is_empty() {
+                    app.close_tab(app.active_tab);
+                }
+                ui.close_menu();
+            }
             if ui.button("Advanced open...").clicked() {
                 gui.advanced_open_window.open.toggle();
                 ui.close_menu();
@@ -34,6 +44,48 @@ pub fn top_menu(ui: &mut egui::Ui, gui: &mut crate::gui::Gui, app: &mut App, fon
                 gui.open_process_window.open.toggle();
                 ui.close_menu();
             }
+            if ui.button("Open core dump...").on_hover_text("Open the memory of an ELF core dump, read-only").clicked() {
+                if let Some(path) = rfd::FileDialog::new().pick_file() {
+                    match app.load_elf_core(path, false, font) {
+                        Ok(core) => {
+                            gui.core_dump_window.threads = core.threads;
+                            gui.core_dump_window.open.set(true);
+                        }
+                        Err(e) => msg_fail(&e, "Failed to load core dump", &mut gui.msg_dialog),
+                    }
+                }
+                ui.close_menu();
+            }
+            if ui.button("Stream command output...").on_hover_text("Spawn a command and stream its standard output").clicked() {
+                gui.command_stream_window.open.toggle();
+                ui.close_menu();
+            }
+            if ui.button("Open socket...").on_hover_text("Stream the bytes received on a TCP or Unix socket").clicked() {
+                gui.socket_window.open.toggle();
+                ui.close_menu();
+            }
+            if ui.button("Compression...").on_hover_text("Decompress the opened file, and compress it again on save").clicked() {
+                gui.compression_window.open.toggle();
+                ui.close_menu();
+            }
+            if ui.button("Import HEX/SREC...").on_hover_text("Open an Intel HEX or Motorola S-record file").clicked() {
+                if let Some(path) = rfd::FileDialog::new().pick_file() {
+                    msg_if_fail(app.load_record_file(path, font), "Failed to import", &mut gui.msg_dialog);
+                }
+                ui.close_menu();
+            }
+            if ui.button("Export HEX/SREC...").on_hover_text("Write the data or the selection as Intel HEX or S-records").clicked() {
+                gui.record_export_window.open.toggle();
+                ui.close_menu();
+            }
+            if ui.button("Import text dump...").on_hover_text("Paste the output of xxd, hexdump -C or od").clicked() {
+                gui.dump_import_window.open.toggle();
+                ui.close_menu();
+            }
+            if ui.button("Export text dump...").on_hover_text("Write the selection or a region like xxd, hexdump -C or od").clicked() {
+                gui.dump_export_window.open.toggle();
+                ui.close_menu();
+            }
             let mut load = None;
             if button_with_shortcut(ui, "Open previous", "Ctrl+P").on_hover_text("Can be used to switch between 2 files quickly for comparison").clicked() {
                 crate::shell::open_previous(app, &mut load);
@@ -117,6 +169,10 @@ pub fn top_menu(ui: &mut egui::Ui, gui: &mut crate::gui::Gui, app: &mut App, fon
                 app.close_file();
                 ui.close_menu();
             }
+            if ui.add_enabled(app.tabs.len() > 1, egui::Button::new("Close tab")).clicked() {
+                super::tab_bar::request_close(gui, app, app.active_tab);
+                ui.close_menu();
+            }
         });
         ui.menu_button("Edit", |ui| {
             if button_with_shortcut(ui, "Find...", "Ctrl+F").clicked() {
@@ -173,6 +229,14 @@ pub fn top_menu(ui: &mut egui::Ui, gui: &mut crate::gui::Gui, app: &mut App, fon
                 }
                 ui.close_menu();
             }
+            if ui.button("Copy").on_hover_text("Copy the selection, to paste it in any tab").clicked() {
+                app.copy_selection();
+                ui.close_menu();
+            }
+            if ui.add_enabled(!app.clipboard.is_empty(), egui::Button::new("Paste")).on_hover_text("Overwrite the bytes at the cursor with the copied ones").clicked() {
+                app.paste_at_cursor();
+                ui.close_menu();
+            }
             if ui.button("Save selection to file").clicked() {
                 if let Some(file_path) = rfd::FileDialog::new().save_file() && let Some(sel) = app.hex_ui.selection() {
                     let result = std::fs::write(file_path, &app.data[sel.begin..=sel.end]);
@@ -191,6 +255,21 @@ pub fn top_menu(ui: &mut egui::Ui, gui: &mut crate::gui::Gui, app: &mut App, fon
             ui.checkbox(&mut app.preferences.sticky_edit, "Sticky edit")
                 .on_hover_text("Don't automatically move cursor after editing is finished");
         });
+        ui.menu_button("Patch", |ui| {
+            if ui.button("Apply patch...").on_hover_text("Apply an IPS, UPS, BPS or bsdiff patch to the data").clicked() {
+                ui.close_menu();
+                if let Some(path) = rfd::FileDialog::new().pick_file() {
+                    match std::fs::read(path).map_err(anyhow::Error::from).and_then(|patch| app.apply_patch(&patch)) {
+                        Ok(changed) => gui.msg_dialog.open(Icon::Info, "Patch applied", format!("{changed} bytes changed")),
+                        Err(e) => msg_fail(&e, "Failed to apply patch", &mut gui.msg_dialog),
+                    }
+                }
+            }
+            if ui.button("Create patch...").on_hover_text("Create a patch from the source file or backup to the current data").clicked() {
+                gui.create_patch_window.open.toggle();
+                ui.close_menu();
+            }
+        });
         ui.menu_button("Cursor", |ui| {
             let re = ui
                 .button("Reset")
@@ -241,6 +320,17 @@ pub fn top_menu(ui: &mut egui::Ui, gui: &mut crate::gui::Gui, app: &mut App, fon
             }
             ui.checkbox(&mut app.preferences.col_change_lock_col, "Lock col on col change");
             ui.checkbox(&mut app.preferences.col_change_lock_row, "Lock row on col change");
+            ui.separator();
+            let mut sector_grid = app.hex_ui.sector_grid.is_some();
+            if ui.checkbox(&mut sector_grid, "Sector grid").on_hover_text("Mark sector boundaries, and show offsets in sectors").changed() {
+                app.hex_ui.sector_grid = sector_grid.then(|| app.sector_size());
+            }
+            if let Some(size) = &mut app.hex_ui.sector_grid {
+                ui.horizontal(|ui| {
+                    ui.label("Sector size");
+                    ui.add(egui::DragValue::new(size).clamp_range(1..=65536));
+                });
+            }
         });
         ui.menu_button("Perspective", |ui| {
             if button_with_shortcut(ui, "Perspectives...", "F7").clicked() {
@@ -346,11 +436,54 @@ pub fn top_menu(ui: &mut egui::Ui, gui: &mut crate::gui::Gui, app: &mut App, fon
                 }
                 _ => { ui.add_enabled(false, egui::Button::new("Diff with backup")); }
             }
+            if app.tabs.len() > 1 {
+                ui.menu_button("Diff with tab", |ui| {
+                    let active = app.active_tab;
+                    for idx in (0..app.tabs.len()).filter(|&idx| idx != active) {
+                        if ui.button(app.tab_label(idx)).clicked() {
+                            ui.close_menu();
+                            let result = app.diff_with_tab(idx, gui);
+                            msg_if_fail(result, "Failed to diff", &mut gui.msg_dialog);
+                        }
+                    }
+                });
+            }
+            if ui.button("Compare with file...").on_hover_text("Show another file side by side with the data, highlighting the differences").clicked() {
+                ui.close_menu();
+                if let Some(path) = rfd::FileDialog::default().pick_file()
+                    && msg_if_fail(app.enter_compare(&path), "Failed to compare", &mut gui.msg_dialog).is_none()
+                {
+                    gui.compare_window.open.set(true);
+                }
+            }
+            if ui.button("Compare regions...").on_hover_text("Compare two regions or selections of the data byte by byte").clicked() {
+                gui.region_compare_window.open.toggle();
+                ui.close_menu();
+            }
+            if ui.button("Snapshot analysis...").on_hover_text("Find values that track known values, or that always or never change, across several snapshots").clicked() {
+                gui.snapshot_analysis_window.open.toggle();
+                ui.close_menu();
+            }
+            ui.separator();
+            if ui.button("Partition table to regions").on_hover_text("Add a region for every MBR/GPT partition at the start of the data").clicked() {
+                ui.close_menu();
+                if app.add_partition_regions() == 0 {
+                    gui.msg_dialog.open(Icon::Warn, "No partitions", "No MBR or GPT partition table found at the start of the data");
+                }
+            }
             ui.separator();
             if ui.add_enabled(gui.open_process_window.selected_pid.is_some(), egui::Button::new("Find memory pointers...")).clicked() {
                 gui.find_memory_pointers_window.open.toggle();
                 ui.close_menu()
             }
+            if ui.button("Watch list...").clicked() {
+                gui.watch_window.open.toggle();
+                ui.close_menu()
+            }
+            if ui.button("Change history...").on_hover_text("Record which bytes change on every reload").clicked() {
+                gui.change_history_window.open.toggle();
+                ui.close_menu()
+            }
         });
         ui.menu_button("Help", |ui| {
             if ui.button("Hexerator book").clicked() {
@@ -371,10 +504,46 @@ pub fn top_menu(ui: &mut egui::Ui, gui: &mut crate::gui::Gui, app: &mut App, fon
                                 Some(file) => ui.label(file.display().to_string()),
                                 None => ui.label("File path unknown"),
                             };
+                            if app.data.is_mapped() {
+                                ui.label("(memory mapped)").on_hover_text(
+                                    "Only the parts being viewed are read from disk",
+                                );
+                            }
                         }
                         SourceProvider::Stdin(_) => {
                             ui.label("Standard input");
                         }
+                        SourceProvider::Command(ref stream) => {
+                            ui.label(format!("Command: {}", stream.cmd_line));
+                        }
+                        SourceProvider::Socket(ref stream) => {
+                            ui.label(stream.socket.peer());
+                        }
+                        SourceProvider::BlockDevice { sector_size, .. } => {
+                            match &app.args.src.file {
+                                Some(file) => ui.label(format!("Block device: {} ({sector_size} byte sectors)", file.display())),
+                                None => ui.label("Block device"),
+                            };
+                        }
+                        #[cfg(target_os = "linux")]
+                        SourceProvider::ProcAddrSpace(_) => {
+                            match &app.args.src.file {
+                                Some(file) => ui.label(format!("Process address space: {}", file.display())),
+                                None => ui.label("Process address space"),
+                            };
+                        }
+                        SourceProvider::RecordFile { format, .. } => {
+                            match &app.args.src.file {
+                                Some(file) => ui.label(format!("{}: {}", format.name(), file.display())),
+                                None => ui.label(format.name()),
+                            };
+                        }
+                        SourceProvider::ElfCore { .. } => {
+                            match &app.args.src.file {
+                                Some(file) => ui.label(format!("Core dump: {}", file.display())),
+                                None => ui.label("Core dump"),
+                            };
+                        }
                         #[cfg(windows)]
                         SourceProvider::WinProc{handle, ..} => {
                             ui.label(format!("Windows process: {}", handle));
@@ -65,6 +65,18 @@ pub fn ui(ui: &mut Ui, gui: &mut Gui, app: &mut App, font: &Font) {
                             ColorMethod::Grayscale,
                             ColorMethod::Grayscale.name(),
                         );
+                        ui.selectable_value(
+                            &mut presentation.color_method,
+                            ColorMethod::RecentChanges,
+                            ColorMethod::RecentChanges.name(),
+                        )
+                        .on_hover_text("Bytes that changed on recent reloads or stream updates glow, then fade");
+                        ui.selectable_value(
+                            &mut presentation.color_method,
+                            ColorMethod::FrequentChanges,
+                            ColorMethod::FrequentChanges.name(),
+                        )
+                        .on_hover_text("Bytes that changed on the most reloads or stream updates glow");
                         if ui
                             .selectable_label(
                                 matches!(&presentation.color_method, ColorMethod::Custom(..)),
@@ -87,6 +99,10 @@ pub fn ui(ui: &mut Ui, gui: &mut Gui, app: &mut App, font: &Font) {
                     });
                 ui.color_edit_button_rgb(&mut app.preferences.bg_color);
                 ui.label("Bg color");
+                if presentation.color_method == ColorMethod::RecentChanges {
+                    ui.add(egui::DragValue::new(&mut app.heatmap.fade_ms).clamp_range(100..=600_000));
+                    ui.label("Fade (ms)");
+                }
                 if let ColorMethod::Custom(arr) = &mut presentation.color_method {
                     let Some(&byte) = app.data.get(app.edit_state.cursor) else { return };
                     let col = &mut arr.0[byte as usize];
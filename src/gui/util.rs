@@ -1,6 +1,10 @@
 //! Various egui utility functions
 
-use egui_sfml::egui::{pos2, text::LayoutJob, Response, TextStyle, Ui, Widget};
+use {
+    crate::{meta::ModuleAnchor, proc_mem},
+    egui_sfml::egui::{pos2, text::LayoutJob, Button, Response, TextStyle, Ui, Widget},
+    proc_maps::MapRange,
+};
 
 pub struct ButtonWithShortcut(pub &'static str, pub &'static str);
 
@@ -29,3 +33,31 @@ pub fn button_with_shortcut(ui: &mut Ui, label: &str, shortcut: &str) -> Respons
     );
     btn_re
 }
+
+/// Show the module anchor of an item, with a button to remove it,
+/// or to anchor the item at `addr` if it's not anchored yet.
+pub fn anchor_ui(ui: &mut Ui, anchor: &mut Option<ModuleAnchor>, addr: usize, ranges: &[MapRange]) {
+    ui.horizontal(|ui| {
+        if let Some(current) = anchor {
+            ui.label(format!(
+                "Anchor: {}+{:X}",
+                proc_mem::module_name(&current.module),
+                current.offset
+            ))
+            .on_hover_text(current.module.display().to_string());
+            if ui.button("✖").on_hover_text("Remove anchor").clicked() {
+                *anchor = None;
+            }
+        } else {
+            let new = proc_mem::anchor_for_addr(ranges, addr);
+            if ui
+                .add_enabled(new.is_some(), Button::new("Anchor to module"))
+                .on_hover_text("Keep this at the same place relative to its module across restarts")
+                .on_disabled_hover_text("Not inside a module of the selected process")
+                .clicked()
+            {
+                *anchor = new;
+            }
+        }
+    });
+}
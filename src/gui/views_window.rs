@@ -2,9 +2,13 @@ use {
     super::window_open::WindowOpen,
     crate::{
         app::App,
+        char_table::CharTable,
         meta::{NamedView, ViewKey},
+        num_conv::Radix,
         region_context_menu,
-        view::{HexData, TextData, TextKind, View, ViewKind},
+        shell::msg_fail,
+        view::{BinData, HexData, NumData, TextData, TextKind, View, ViewKind},
+        watch_list::WatchType,
     },
     egui_extras::{Size, TableBuilder},
     egui_sfml::{
@@ -27,12 +31,18 @@ impl ViewKind {
     const DEC_NAME: &str = "Decimal";
     const TEXT_NAME: &str = "Text";
     const BLOCK_NAME: &str = "Block";
+    const BIN_NAME: &str = "Binary";
+    const OCT_NAME: &str = "Octal";
+    const NUM_NAME: &str = "Number";
     fn name(&self) -> &'static str {
         match *self {
             ViewKind::Hex(_) => Self::HEX_NAME,
             ViewKind::Dec(_) => Self::DEC_NAME,
             ViewKind::Text(_) => Self::TEXT_NAME,
             ViewKind::Block => Self::BLOCK_NAME,
+            ViewKind::Bin(_) => Self::BIN_NAME,
+            ViewKind::Oct(_) => Self::OCT_NAME,
+            ViewKind::Num(_) => Self::NUM_NAME,
         }
     }
 }
@@ -195,6 +205,9 @@ impl ViewsWindow {
                 match &mut view.view.kind {
                     ViewKind::Hex(HexData { font_size, .. })
                     | ViewKind::Dec(HexData { font_size, .. })
+                    | ViewKind::Oct(HexData { font_size, .. })
+                    | ViewKind::Bin(BinData { font_size, .. })
+                    | ViewKind::Num(NumData { font_size, .. })
                     | ViewKind::Text(TextData { font_size, .. }) => {
                         ui.horizontal(|ui| {
                             ui.label("Font size");
@@ -208,33 +221,64 @@ impl ViewsWindow {
                                 adjust_block_size = true;
                             };
                         });
+                        if let ViewKind::Bin(bin) = &mut view.view.kind {
+                            ui.checkbox(&mut bin.lsb_first, "Least significant bit first");
+                        }
+                        if let ViewKind::Num(num) = &mut view.view.kind {
+                            let mut changed = false;
+                            egui::ComboBox::new(egui::Id::new("num_type_combo"), "Type")
+                                .selected_text(num.ty.name())
+                                .show_ui(ui, |ui| {
+                                    for ty in WatchType::ALL {
+                                        changed |= ui
+                                            .selectable_value(&mut num.ty, ty, ty.name())
+                                            .clicked();
+                                    }
+                                });
+                            egui::ComboBox::new(egui::Id::new("num_radix_combo"), "Radix")
+                                .selected_text(num.radix.name())
+                                .show_ui(ui, |ui| {
+                                    for radix in Radix::ALL {
+                                        changed |= ui
+                                            .selectable_value(&mut num.radix, radix, radix.name())
+                                            .clicked();
+                                    }
+                                });
+                            changed |= ui.checkbox(&mut num.big_endian, "Big endian").changed();
+                            if changed {
+                                view.view.adjust_state_to_kind();
+                            }
+                        }
                         if let ViewKind::Text(text) = &mut view.view.kind {
                             let mut changed = false;
                             egui::ComboBox::new(egui::Id::new("text_combo"), "Text kind")
                                 .selected_text(text.text_kind.name())
                                 .show_ui(ui, |ui| {
-                                    changed |= ui
-                                        .selectable_value(
-                                            &mut text.text_kind,
-                                            TextKind::Ascii,
-                                            TextKind::Ascii.name(),
-                                        )
-                                        .clicked();
-                                    changed |= ui
-                                        .selectable_value(
-                                            &mut text.text_kind,
-                                            TextKind::Utf16Le,
-                                            TextKind::Utf16Le.name(),
-                                        )
-                                        .clicked();
-                                    changed |= ui
-                                        .selectable_value(
-                                            &mut text.text_kind,
-                                            TextKind::Utf16Be,
-                                            TextKind::Utf16Be.name(),
-                                        )
-                                        .clicked();
+                                    for kind in TextKind::all() {
+                                        let name = kind.name();
+                                        changed |= ui
+                                            .selectable_value(&mut text.text_kind, kind, name)
+                                            .clicked();
+                                    }
                                 });
+                            if ui
+                                .button("Load table...")
+                                .on_hover_text("Use a custom character table (.tbl)")
+                                .clicked()
+                                && let Some(path) = rfd::FileDialog::new()
+                                    .add_filter("Character table", &["tbl"])
+                                    .pick_file()
+                            {
+                                match CharTable::load(&path) {
+                                    Ok(table) => {
+                                        text.text_kind = TextKind::Table(table);
+                                        changed = true;
+                                    }
+                                    Err(e) => {
+                                        msg_fail(&e, "Failed to load table", &mut gui.msg_dialog)
+                                    }
+                                }
+                            }
                             if changed {
                                 view.view.bytes_per_block = text.text_kind.bytes_needed();
                             }
@@ -301,6 +345,27 @@ fn view_combo(
                 *kind = ViewKind::Dec(HexData::default());
                 changed = true;
             }
+            if ui
+                .selectable_label(kind.name() == ViewKind::OCT_NAME, ViewKind::OCT_NAME)
+                .clicked()
+            {
+                *kind = ViewKind::Oct(HexData::default());
+                changed = true;
+            }
+            if ui
+                .selectable_label(kind.name() == ViewKind::BIN_NAME, ViewKind::BIN_NAME)
+                .clicked()
+            {
+                *kind = ViewKind::Bin(BinData::default());
+                changed = true;
+            }
+            if ui
+                .selectable_label(kind.name() == ViewKind::NUM_NAME, ViewKind::NUM_NAME)
+                .clicked()
+            {
+                *kind = ViewKind::Num(NumData::default());
+                changed = true;
+            }
             if ui
                 .selectable_label(kind.name() == ViewKind::TEXT_NAME, ViewKind::TEXT_NAME)
                 .clicked()
@@ -0,0 +1,190 @@
+use {
+    super::{message_dialog::Icon, window_open::WindowOpen, Gui},
+    crate::{app::App, shell::msg_if_fail, watch_list::WatchType},
+    egui_extras::{Size, TableBuilder},
+    egui_sfml::egui,
+};
+
+#[derive(Default)]
+pub struct WatchWindow {
+    pub open: WindowOpen,
+    /// Index of the entry whose value is being edited, and the edit buffer
+    edit: Option<(usize, String)>,
+}
+
+impl WatchWindow {
+    pub fn ui(ui: &mut egui::Ui, gui: &mut Gui, app: &mut App) {
+        let win = &mut gui.watch_window;
+        let list = &mut app.watch_list;
+        ui.horizontal(|ui| {
+            match list.pid() {
+                Some(pid) => ui.label(format!("Process: {pid}")),
+                None => ui.label("No process"),
+            };
+            ui.label("Refresh interval (ms)");
+            ui.add(egui::DragValue::new(&mut list.interval_ms).clamp_range(10..=10_000));
+        });
+        if let Some(err) = &list.last_error {
+            ui.label(egui::RichText::new(err).color(egui::Color32::RED));
+        }
+        if list.entries.is_empty() {
+            ui.label("Nothing is being watched. Add values from find results or bookmarks.");
+            win.open.post_ui();
+            return;
+        }
+        let mut action = Action::None;
+        TableBuilder::new(ui)
+            .column(Size::initial(140.))
+            .column(Size::initial(120.))
+            .column(Size::initial(60.))
+            .column(Size::remainder())
+            .column(Size::initial(80.))
+            .striped(true)
+            .header(20.0, |mut row| {
+                row.col(|ui| {
+                    ui.label("Label");
+                });
+                row.col(|ui| {
+                    ui.label("Address");
+                });
+                row.col(|ui| {
+                    ui.label("Type");
+                });
+                row.col(|ui| {
+                    ui.label("Value");
+                });
+                row.col(|ui| {
+                    ui.label("Action");
+                });
+            })
+            .body(|body| {
+                body.rows(20.0, list.entries.len(), |idx, mut row| {
+                    let entry = &mut list.entries[idx];
+                    row.col(|ui| {
+                        ui.text_edit_singleline(&mut entry.label);
+                    });
+                    row.col(|ui| {
+                        if ui
+                            .link(format!("{:X}", entry.addr))
+                            .on_hover_text("Jump to address")
+                            .clicked()
+                        {
+                            action = Action::Goto(entry.addr);
+                        }
+                    });
+                    row.col(|ui| {
+                        egui::ComboBox::from_id_source(("watch_type", idx))
+                            .selected_text(entry.ty.name())
+                            .show_ui(ui, |ui| {
+                                for ty in WatchType::ALL {
+                                    if ui.selectable_value(&mut entry.ty, ty, ty.name()).clicked() {
+                                        entry.value = None;
+                                        entry.frozen = None;
+                                    }
+                                }
+                            });
+                    });
+                    row.col(|ui| match &mut win.edit {
+                        Some((edit_idx, buf)) if *edit_idx == idx => {
+                            let re = ui.text_edit_singleline(buf);
+                            if re.lost_focus() {
+                                if ui.input().key_pressed(egui::Key::Enter) {
+                                    action = Action::Write(idx);
+                                } else {
+                                    win.edit = None;
+                                }
+                            } else {
+                                re.request_focus();
+                            }
+                        }
+                        _ => {
+                            let text = entry
+                                .value
+                                .as_ref()
+                                .and_then(|val| entry.ty.format(val))
+                                .unwrap_or_else(|| String::from("??"));
+                            if ui.link(&text).on_hover_text("Click to edit").clicked() {
+                                win.edit = Some((idx, text));
+                            }
+                        }
+                    });
+                    row.col(|ui| {
+                        let mut frozen = entry.frozen.is_some();
+                        if ui
+                            .checkbox(&mut frozen, "❄")
+                            .on_hover_text("Freeze value")
+                            .changed()
+                        {
+                            entry.frozen = if frozen { entry.value.clone() } else { None };
+                        }
+                        if ui.button("🗑").clicked() {
+                            action = Action::Remove(idx);
+                        }
+                    });
+                });
+            });
+        match action {
+            Action::Goto(addr) => match app.addr_to_offset(addr) {
+                Some(off) if off < app.data.len() => {
+                    app.center_view_on_offset(off);
+                    app.edit_state.set_cursor(off);
+                    app.hex_ui.flash_cursor();
+                }
+                _ => gui.msg_dialog.open(
+                    Icon::Error,
+                    "Address not loaded",
+                    format!("{addr:X} is not part of the loaded data"),
+                ),
+            },
+            Action::Write(idx) => {
+                if let Some((_, buf)) = win.edit.take() {
+                    let list = &mut app.watch_list;
+                    let result: anyhow::Result<()> = try {
+                        let bytes = list.entries[idx].ty.parse(&buf)?;
+                        list.write(idx, &bytes)?;
+                        let entry = &mut list.entries[idx];
+                        if entry.frozen.is_some() {
+                            entry.frozen = Some(bytes);
+                        }
+                    };
+                    msg_if_fail(result, "Failed to write value", &mut gui.msg_dialog);
+                }
+            }
+            Action::Remove(idx) => {
+                app.watch_list.entries.remove(idx);
+                win.edit = None;
+            }
+            Action::None => {}
+        }
+        gui.watch_window.open.post_ui();
+    }
+}
+
+/// Add the value at `offset` to the watch list of the process whose memory is loaded
+pub fn add_to_watch_list(
+    gui: &mut Gui,
+    app: &mut App,
+    offset: usize,
+    ty: WatchType,
+    label: String,
+) {
+    match app.source_pid() {
+        Some(pid) => {
+            app.watch_list
+                .add(pid, label, app.offset_to_addr(offset), ty);
+            gui.watch_window.open.set(true);
+        }
+        None => gui.msg_dialog.open(
+            Icon::Error,
+            "Not process memory",
+            "The watch list only works with process memory. Open the memory of a process first.",
+        ),
+    }
+}
+
+enum Action {
+    Goto(usize),
+    Write(usize),
+    Remove(usize),
+    None,
+}
@@ -0,0 +1,146 @@
+//! Per-byte record of how recently and how often the data changed, for coloring live sources
+
+use {
+    crate::color::{rgb, RgbColor},
+    std::{collections::HashMap, ops::Range, time::Instant},
+};
+
+/// Tracks changes of every byte across reloads and stream updates, while enabled
+///
+/// Only the bytes that changed are recorded, so tracking large address spaces
+/// costs memory proportional to the amount of changes, not the data size.
+pub struct Heatmap {
+    pub enabled: bool,
+    /// How long a change stays visible in the recent changes coloring
+    pub fade_ms: u32,
+    changes: HashMap<usize, Change>,
+    max_count: u16,
+    start: Instant,
+}
+
+#[derive(Default, Clone, Copy)]
+struct Change {
+    /// Milliseconds since the start of tracking
+    last: u32,
+    /// Number of times the byte changed
+    count: u16,
+}
+
+impl Default for Heatmap {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            fade_ms: 5000,
+            changes: HashMap::new(),
+            max_count: 0,
+            start: Instant::now(),
+        }
+    }
+}
+
+impl Heatmap {
+    pub fn clear(&mut self) {
+        self.changes = HashMap::new();
+        self.max_count = 0;
+        self.start = Instant::now();
+    }
+    /// Current time in milliseconds since the start of tracking.
+    ///
+    /// Get it once per frame and pass it to [`Self::recency`].
+    pub fn now_stamp(&self) -> u32 {
+        u32::try_from(self.start.elapsed().as_millis()).unwrap_or(u32::MAX)
+    }
+    fn mark(&mut self, idx: usize, stamp: u32) {
+        let change = self.changes.entry(idx).or_default();
+        change.last = stamp;
+        change.count = change.count.saturating_add(1);
+        self.max_count = self.max_count.max(change.count);
+    }
+    /// Mark the bytes that differ between the data before and after a reload
+    pub fn record(&mut self, old: &[u8], new: &[u8]) {
+        let stamp = self.now_stamp();
+        for (i, &byte) in new.iter().enumerate() {
+            if old.get(i) != Some(&byte) {
+                self.mark(i, stamp);
+            }
+        }
+    }
+    /// Mark a range of bytes as just changed, like data appended by a stream
+    pub fn mark_range(&mut self, range: Range<usize>) {
+        let stamp = self.now_stamp();
+        for i in range {
+            self.mark(i, stamp);
+        }
+    }
+    /// How recently the byte at `idx` changed as of `now` (see [`Self::now_stamp`]),
+    /// from 1.0 (just now) to 0.0 (faded out or never)
+    pub fn recency(&self, idx: usize, now: u32) -> f32 {
+        let Some(change) = self.changes.get(&idx) else {
+            return 0.0;
+        };
+        let age = now.saturating_sub(change.last);
+        if age >= self.fade_ms {
+            return 0.0;
+        }
+        #[expect(
+            clippy::cast_precision_loss,
+            reason = "Millisecond ages are small enough to not matter for coloring"
+        )]
+        let heat = 1.0 - age as f32 / self.fade_ms as f32;
+        heat
+    }
+    /// How often the byte at `idx` changed compared to the most changed byte, from 0.0 to 1.0
+    pub fn frequency(&self, idx: usize) -> f32 {
+        match self.changes.get(&idx) {
+            Some(change) => {
+                (f32::from(change.count) + 1.0).ln() / (f32::from(self.max_count) + 1.0).ln()
+            }
+            None => 0.0,
+        }
+    }
+}
+
+/// Color of a heat from 0.0 to 1.0, going from dim gray through red and yellow to white
+pub fn heat_color(heat: f32) -> RgbColor {
+    #[expect(
+        clippy::cast_possible_truncation,
+        clippy::cast_sign_loss,
+        reason = "The value is clamped to 0..=255"
+    )]
+    let ramp = |from: u8, to: u8, t: f32| {
+        (f32::from(from) + (f32::from(to) - f32::from(from)) * t.clamp(0.0, 1.0)) as u8
+    };
+    let heat = heat.clamp(0.0, 1.0);
+    if heat == 0.0 {
+        rgb(80, 80, 80)
+    } else if heat < 0.5 {
+        let t = heat * 2.0;
+        rgb(ramp(120, 255, t), ramp(40, 40, t), ramp(40, 0, t))
+    } else {
+        let t = (heat - 0.5) * 2.0;
+        rgb(255, ramp(40, 255, t), ramp(0, 200, t))
+    }
+}
+
+#[test]
+fn test_heatmap() {
+    let mut heatmap = Heatmap::default();
+    heatmap.record(b"abcd", b"aXcd");
+    heatmap.record(b"aXcd", b"aYcdef");
+    let count = |heatmap: &Heatmap, idx| heatmap.changes.get(&idx).map_or(0, |c| c.count);
+    assert_eq!(
+        (0..6).map(|i| count(&heatmap, i)).collect::<Vec<_>>(),
+        [0, 2, 0, 0, 1, 1]
+    );
+    assert_eq!(heatmap.changes.len(), 3);
+    let now = heatmap.now_stamp();
+    assert!(heatmap.recency(1, now) > 0.9);
+    assert!(heatmap.recency(0, now) == 0.0);
+    assert!(heatmap.recency(100, now) == 0.0);
+    assert!((heatmap.frequency(1) - 1.0).abs() < f32::EPSILON);
+    assert!(heatmap.frequency(4) > 0.0 && heatmap.frequency(4) < 1.0);
+    heatmap.mark_range(6..8);
+    assert_eq!(heatmap.changes.len(), 5);
+    heatmap.fade_ms = 0;
+    assert!(heatmap.recency(1, now) == 0.0);
+}
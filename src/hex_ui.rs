@@ -27,6 +27,8 @@ pub struct HexUi {
     pub scissor_views: bool,
     /// When alt is being held, it shows things like names of views as overlays
     pub show_alt_overlay: bool,
+    /// If set, sector boundaries of this size are marked in the views
+    pub sector_grid: Option<usize>,
 }
 
 impl HexUi {
@@ -78,6 +80,7 @@ impl Default for HexUi {
             flash_cursor_timer: Timer::default(),
             hex_iface_rect: ViewportRect::default(),
             show_alt_overlay: false,
+            sector_grid: None,
             current_layout: LayoutKey::null(),
         }
     }
@@ -0,0 +1,404 @@
+//! Intel HEX and Motorola S-record files
+
+use {
+    anyhow::{bail, Context},
+    std::fmt::Write,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RecordFormat {
+    IntelHex,
+    Srec,
+}
+
+impl RecordFormat {
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::IntelHex => "Intel HEX",
+            Self::Srec => "Motorola S-record",
+        }
+    }
+    /// Detect the format from the first record of `text`
+    pub fn detect(text: &str) -> Option<Self> {
+        match text.trim_start().as_bytes().first()? {
+            b':' => Some(Self::IntelHex),
+            b'S' => Some(Self::Srec),
+            _ => None,
+        }
+    }
+}
+
+/// Contiguous data at a load address
+#[derive(Debug, PartialEq, Eq)]
+pub struct Block {
+    pub addr: usize,
+    pub data: Vec<u8>,
+}
+
+/// Where execution starts, from a start address record
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StartAddr {
+    /// Intel HEX type 03 record, with CS in the upper and IP in the lower 16 bits
+    Segment(u32),
+    /// Intel HEX type 05 record, or the address of an S7, S8 or S9 record
+    Linear(u32),
+}
+
+/// The records besides the data ones, kept for writing the file back out the same way
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Extra {
+    /// Data of the S0 header record
+    pub header: Option<Vec<u8>>,
+    pub start: Option<StartAddr>,
+}
+
+/// The result of parsing a record file
+pub struct Parsed {
+    /// Blocks of contiguous records, sorted by address
+    pub blocks: Vec<Block>,
+    /// Largest amount of data in a single record, for writing the file back out the same way
+    pub record_size: usize,
+    pub extra: Extra,
+}
+
+/// Parse `text`, merging records that follow each other into blocks
+pub fn parse(text: &str, format: RecordFormat) -> anyhow::Result<Parsed> {
+    let mut records = Vec::new();
+    let mut extra = Extra::default();
+    let mut base = 0;
+    for (i, line) in text.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() {
+            continue;
+        }
+        let result = match format {
+            RecordFormat::IntelHex => parse_ihex_record(line, &mut base),
+            RecordFormat::Srec => parse_srec_record(line),
+        };
+        match result.with_context(|| format!("Line {}", i + 1))? {
+            Record::Data(addr, data) => records.push((addr, data)),
+            Record::Header(header) => extra.header = Some(header),
+            Record::Start(start) => extra.start = Some(start),
+            Record::Other => {}
+            Record::End(start) => {
+                extra.start = start.or(extra.start);
+                break;
+            }
+        }
+    }
+    let record_size = records
+        .iter()
+        .map(|(_, data)| data.len())
+        .max()
+        .unwrap_or(0);
+    records.sort_by_key(|(addr, _)| *addr);
+    let mut blocks: Vec<Block> = Vec::new();
+    for (addr, data) in records {
+        if let Some(last) = blocks.last_mut() {
+            let end = last.addr + last.data.len();
+            if addr < end {
+                bail!(
+                    "Record at {addr:X} overlaps with data at {:X}..{end:X}",
+                    last.addr
+                );
+            }
+            if addr == end {
+                last.data.extend_from_slice(&data);
+                continue;
+            }
+        }
+        blocks.push(Block { addr, data });
+    }
+    Ok(Parsed {
+        blocks,
+        record_size,
+        extra,
+    })
+}
+
+enum Record {
+    Data(usize, Vec<u8>),
+    Header(Vec<u8>),
+    Start(StartAddr),
+    Other,
+    /// S-record end records also hold the start address
+    End(Option<StartAddr>),
+}
+
+fn parse_hex_bytes(hex: &str) -> anyhow::Result<Vec<u8>> {
+    if hex.len() % 2 != 0 {
+        bail!("Odd number of hex digits");
+    }
+    (0..hex.len())
+        .step_by(2)
+        .map(|i| {
+            let digits = hex.get(i..i + 2).context("Invalid character")?;
+            u8::from_str_radix(digits, 16).with_context(|| format!("Invalid hex byte '{digits}'"))
+        })
+        .collect()
+}
+
+fn be_value(bytes: &[u8]) -> usize {
+    bytes.iter().fold(0, |acc, &b| (acc << 8) | usize::from(b))
+}
+
+/// Parse a `:LLAAAATT...CC` record. `base` is updated by extended address records.
+fn parse_ihex_record(line: &str, base: &mut usize) -> anyhow::Result<Record> {
+    let hex = line
+        .strip_prefix(':')
+        .context("Record doesn't start with ':'")?;
+    let bytes = parse_hex_bytes(hex)?;
+    if bytes.len() < 5 || bytes.len() != usize::from(bytes[0]) + 5 {
+        bail!("Invalid record length");
+    }
+    if bytes.iter().fold(0u8, |acc, &b| acc.wrapping_add(b)) != 0 {
+        bail!("Checksum mismatch");
+    }
+    let data = &bytes[4..bytes.len() - 1];
+    let record = match bytes[3] {
+        0x00 => Record::Data(*base + be_value(&bytes[1..3]), data.to_vec()),
+        0x01 => Record::End(None),
+        // Extended segment address
+        0x02 => {
+            *base = be_value(data) << 4;
+            Record::Other
+        }
+        // Extended linear address
+        0x04 => {
+            *base = be_value(data) << 16;
+            Record::Other
+        }
+        0x03 | 0x05 => {
+            if data.len() != 4 {
+                bail!("Invalid start address record");
+            }
+            #[expect(clippy::cast_possible_truncation, reason = "Read from 4 bytes")]
+            let value = be_value(data) as u32;
+            Record::Start(if bytes[3] == 0x03 {
+                StartAddr::Segment(value)
+            } else {
+                StartAddr::Linear(value)
+            })
+        }
+        ty => bail!("Unknown record type {ty:02X}"),
+    };
+    Ok(record)
+}
+
+/// Parse a `STLL<addr><data>CC` record
+fn parse_srec_record(line: &str) -> anyhow::Result<Record> {
+    let rest = line
+        .strip_prefix('S')
+        .context("Record doesn't start with 'S'")?;
+    let ty = rest.as_bytes().first().context("Missing record type")?;
+    let bytes = parse_hex_bytes(rest.get(1..).context("Invalid record type")?)?;
+    if bytes.len() < 2 || bytes.len() != usize::from(bytes[0]) + 1 {
+        bail!("Invalid record length");
+    }
+    if bytes.iter().fold(0u8, |acc, &b| acc.wrapping_add(b)) != 0xFF {
+        bail!("Checksum mismatch");
+    }
+    let addr_len = match ty {
+        b'0' | b'1' | b'9' => 2,
+        b'2' | b'8' => 3,
+        b'3' | b'7' => 4,
+        b'5' | b'6' => return Ok(Record::Other),
+        _ => bail!("Unknown record type S{}", char::from(*ty)),
+    };
+    let payload = &bytes[1..bytes.len() - 1];
+    if payload.len() < addr_len {
+        bail!("Record too short for its address");
+    }
+    let (addr, data) = (be_value(&payload[..addr_len]), payload[addr_len..].to_vec());
+    Ok(match ty {
+        b'0' => Record::Header(data),
+        #[expect(
+            clippy::cast_possible_truncation,
+            reason = "S-record addresses are at most 4 bytes"
+        )]
+        b'7' | b'8' | b'9' => Record::End(Some(StartAddr::Linear(addr as u32))),
+        _ => Record::Data(addr, data),
+    })
+}
+
+/// Write `blocks` as `(address, data)` pairs, with at most `record_size` data bytes per record.
+///
+/// The header and start address in `extra` are written too, if the format supports them.
+pub fn write(
+    format: RecordFormat,
+    blocks: &[(usize, &[u8])],
+    record_size: usize,
+    extra: &Extra,
+) -> anyhow::Result<String> {
+    let record_size = record_size.clamp(1, 250);
+    // S-records only have a linear start address
+    let start_addr = match extra.start {
+        Some(StartAddr::Segment(cs_ip)) => {
+            ((cs_ip >> 16) << 4) as usize + (cs_ip & 0xFFFF) as usize
+        }
+        Some(StartAddr::Linear(addr)) => addr as usize,
+        None => 0,
+    };
+    let max_addr = blocks
+        .iter()
+        .map(|(addr, data)| addr + data.len().saturating_sub(1))
+        .chain([start_addr])
+        .max()
+        .unwrap_or(0);
+    if max_addr > 0xFFFF_FFFF {
+        bail!("{} only supports 32 bit addresses", format.name());
+    }
+    let mut out = String::new();
+    match format {
+        RecordFormat::IntelHex => {
+            let mut upper = 0;
+            for &(addr, data) in blocks {
+                let mut pos = 0;
+                while pos < data.len() {
+                    let rec_addr = addr + pos;
+                    if rec_addr >> 16 != upper {
+                        upper = rec_addr >> 16;
+                        push_record_ihex(&mut out, 0, 0x04, &be_bytes(upper, 2));
+                    }
+                    // Records can't cross a 64 KiB boundary
+                    let to_boundary = 0x1_0000 - (rec_addr & 0xFFFF);
+                    let len = record_size.min(data.len() - pos).min(to_boundary);
+                    push_record_ihex(&mut out, rec_addr & 0xFFFF, 0x00, &data[pos..pos + len]);
+                    pos += len;
+                }
+            }
+            match extra.start {
+                Some(StartAddr::Segment(cs_ip)) => {
+                    push_record_ihex(&mut out, 0, 0x03, &be_bytes(cs_ip as usize, 4))
+                }
+                Some(StartAddr::Linear(addr)) => {
+                    push_record_ihex(&mut out, 0, 0x05, &be_bytes(addr as usize, 4))
+                }
+                None => {}
+            }
+            push_record_ihex(&mut out, 0, 0x01, &[]);
+        }
+        RecordFormat::Srec => {
+            let (data_ty, end_ty, addr_len) = match max_addr {
+                0..=0xFFFF => ('1', '9', 2),
+                0x1_0000..=0xFF_FFFF => ('2', '8', 3),
+                _ => ('3', '7', 4),
+            };
+            if let Some(header) = &extra.header {
+                push_record_srec(&mut out, '0', &[0, 0], header);
+            }
+            for &(addr, data) in blocks {
+                for (i, chunk) in data.chunks(record_size).enumerate() {
+                    let rec_addr = be_bytes(addr + i * record_size, addr_len);
+                    push_record_srec(&mut out, data_ty, &rec_addr, chunk);
+                }
+            }
+            push_record_srec(&mut out, end_ty, &be_bytes(start_addr, addr_len), &[]);
+        }
+    }
+    Ok(out)
+}
+
+#[expect(
+    clippy::cast_possible_truncation,
+    reason = "Truncating to the lowest byte is intended"
+)]
+fn be_bytes(value: usize, len: usize) -> Vec<u8> {
+    (0..len).rev().map(|i| (value >> (i * 8)) as u8).collect()
+}
+
+#[expect(
+    clippy::cast_possible_truncation,
+    reason = "Records are at most 250 bytes, and addresses are masked to 16 bits"
+)]
+fn push_record_ihex(out: &mut String, addr: usize, ty: u8, data: &[u8]) {
+    let mut bytes = vec![data.len() as u8, (addr >> 8) as u8, addr as u8, ty];
+    bytes.extend_from_slice(data);
+    let sum = bytes.iter().fold(0u8, |acc, &b| acc.wrapping_add(b));
+    bytes.push(sum.wrapping_neg());
+    out.push(':');
+    push_hex(out, &bytes);
+}
+
+#[expect(
+    clippy::cast_possible_truncation,
+    reason = "Records are at most 250 bytes plus a 4 byte address and checksum"
+)]
+fn push_record_srec(out: &mut String, ty: char, addr: &[u8], data: &[u8]) {
+    let mut bytes = vec![(addr.len() + data.len() + 1) as u8];
+    bytes.extend_from_slice(addr);
+    bytes.extend_from_slice(data);
+    let sum = bytes.iter().fold(0u8, |acc, &b| acc.wrapping_add(b));
+    bytes.push(!sum);
+    out.push('S');
+    out.push(ty);
+    push_hex(out, &bytes);
+}
+
+fn push_hex(out: &mut String, bytes: &[u8]) {
+    for b in bytes {
+        // Writing to a String can't fail
+        let _ = write!(out, "{b:02X}");
+    }
+    out.push('\n');
+}
+
+#[test]
+#[allow(clippy::unwrap_used)]
+fn test_parse_ihex() {
+    let text = ":0400100001020304E2\n\
+                :020014000506DF\n\
+                :020000040001F9\n\
+                :0100000007F8\n\
+                :00000001FF\n";
+    let parsed = parse(text, RecordFormat::IntelHex).unwrap();
+    assert_eq!(
+        parsed.blocks,
+        [
+            Block {
+                addr: 0x10,
+                data: vec![1, 2, 3, 4, 5, 6]
+            },
+            Block {
+                addr: 0x1_0000,
+                data: vec![7]
+            },
+        ]
+    );
+    assert_eq!(parsed.record_size, 4);
+    assert_eq!(parsed.extra, Extra::default());
+    assert!(parse(":0400100001020304E3", RecordFormat::IntelHex).is_err());
+}
+
+#[test]
+#[allow(clippy::unwrap_used)]
+fn test_roundtrip() {
+    let data: Vec<u8> = (0..=255).collect();
+    let blocks = [(0xFFF0, &data[..]), (0x2_0000, &data[..3])];
+    let extra = Extra {
+        header: Some(b"header".to_vec()),
+        start: Some(StartAddr::Linear(0x2_0001)),
+    };
+    for format in [RecordFormat::IntelHex, RecordFormat::Srec] {
+        let text = write(format, &blocks, 16, &extra).unwrap();
+        assert_eq!(RecordFormat::detect(&text), Some(format));
+        let parsed = parse(&text, format).unwrap();
+        let got: Vec<_> = parsed
+            .blocks
+            .iter()
+            .map(|block| (block.addr, &block.data[..]))
+            .collect();
+        assert_eq!(got, blocks);
+        assert_eq!(parsed.record_size, 16);
+        assert_eq!(parsed.extra.start, extra.start);
+        // Intel HEX has no header record
+        assert_eq!(parsed.extra.header.is_some(), format == RecordFormat::Srec);
+    }
+    // Segment start addresses survive a round trip through Intel HEX
+    let extra = Extra {
+        header: None,
+        start: Some(StartAddr::Segment(0x1234_5678)),
+    };
+    let text = write(RecordFormat::IntelHex, &blocks, 16, &extra).unwrap();
+    assert_eq!(parse(&text, RecordFormat::IntelHex).unwrap().extra, extra);
+}
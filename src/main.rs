@@ -24,29 +24,48 @@ use {
     rfd::MessageLevel,
 };
 
+mod addr_map;
 mod app;
 mod args;
 mod backend;
+mod bin_conv;
+mod binary_diff;
+mod block_dev;
+mod change_history;
+mod char_table;
 mod color;
+mod compression;
 mod config;
 mod damage_region;
+mod data;
 mod dec_conv;
+mod elf_core;
 pub mod edit_buffer;
 mod gui;
+mod heatmap;
 mod hex_conv;
 mod hex_ui;
+mod ihex_srec;
 mod input;
 mod layout;
 mod meta;
 mod meta_state;
+mod num_conv;
+mod oct_conv;
 mod parse_radix;
+mod patch;
 mod preferences;
+mod proc_mem;
 mod shell;
 mod slice_ext;
+mod snapshot_analysis;
 mod source;
+mod text_conv;
+mod text_dump;
 mod timer;
 mod value_color;
 mod view;
+mod watch_list;
 #[cfg(windows)]
 mod windows;
 
@@ -223,9 +242,17 @@ fn update(app: &mut App, egui_wants_kb: bool) {
         let (src_row, src_col) = (src.scroll_offset.row(), src.scroll_offset.col());
         let (src_yoff, src_xoff) = (src.scroll_offset.pix_yoff(), src.scroll_offset.pix_xoff());
         let (src_row_h, src_col_w) = (src.row_h, src.col_w);
+        // Compare views have their own perspectives, which follow the ones they were cloned from
+        let paired_perspective = app.compare.as_ref().and_then(|cmp| cmp.paired_perspective(src_perspective));
+        if let Some(paired) = paired_perspective {
+            let perspectives = &mut app.meta_state.meta.low.perspectives;
+            let (cols, flip_row_order) = (perspectives[src_perspective].cols, perspectives[src_perspective].flip_row_order);
+            perspectives[paired].cols = cols;
+            perspectives[paired].flip_row_order = flip_row_order;
+        }
         for NamedView { view, name: _ } in app.meta_state.meta.views.values_mut() {
             // Only sync views that have the same perspective
-            if view.perspective != src_perspective {
+            if view.perspective != src_perspective && Some(view.perspective) != paired_perspective {
                 continue;
             }
             view.sync_to(src_row, src_yoff, src_col, src_xoff, src_row_h, src_col_w);
@@ -386,12 +413,17 @@ fn handle_text_entered(app: &mut App, unicode: char, msg: &mut MessageDialog) {
             let Some(focused) = app.hex_ui.focused_view else {
                 return
             };
+            // The compared file is read-only
+            if app.compare.as_ref().is_some_and(|cmp| cmp.is_compare_view(focused)) {
+                return;
+            }
             let view = &mut app.meta_state.meta.views[focused].view;
             view.handle_text_entered(
                 unicode,
                 &mut app.edit_state,
                 &app.preferences,
                 &mut app.data,
+                &app.meta_state.meta.low,
                 msg,
             );
             keep_cursor_in_view(
@@ -426,6 +458,12 @@ fn handle_key_pressed(
         return;
     }
     match code {
+        Key::Up if key_mod.alt && gui.file_diff_result_window.has_diffs() => {
+            gui.file_diff_result_window.goto_diff(app, false);
+        }
+        Key::Down if key_mod.alt && gui.file_diff_result_window.has_diffs() => {
+            gui.file_diff_result_window.goto_diff(app, true);
+        }
         Key::Up => match app.hex_ui.interact_mode {
             InteractMode::View => {
                 if key_mod.ctrl && let Some(view_key) = app.hex_ui.focused_view {
@@ -473,16 +511,21 @@ fn handle_key_pressed(
                     || (!app.preferences.move_edit_cursor && key_mod.ctrl);
                     if let Some(view_key) = app.hex_ui.focused_view {
                         let view = &mut app.meta_state.meta.views[view_key];
+                        // Step over whole blocks, like multi-byte values in Num views
+                        let low = &app.meta_state.meta.low;
+                        let step = usize::from(view.view.bytes_per_block);
+                        let block_start = view.view.block_start(app.edit_state.cursor, &low.perspectives, &low.regions);
+                        app.edit_state.set_cursor_no_history(block_start);
                 if move_edit {
                         if let Some(edit_buf) = view.view.edit_buffer_mut() {
                             if !edit_buf.move_cursor_back() {
                                 edit_buf.move_cursor_end();
                                 edit_buf.dirty = false;
-                                app.edit_state.step_cursor_back();
+                                app.edit_state.step_cursor_back(step);
                             }
                         }
                 } else {
-                    app.edit_state.step_cursor_back();
+                    app.edit_state.step_cursor_back(step);
                     keep_cursor_in_view(&mut view.view, &app.meta_state.meta.low.perspectives, &app.meta_state.meta.low.regions, app.edit_state.cursor);
                 }
             }
@@ -499,22 +542,28 @@ fn handle_key_pressed(
                 app.cursor_history_forward();
                 break 'block;
             }
-            if app.hex_ui.interact_mode == InteractMode::Edit && app.edit_state.cursor + 1 < app.data.len()
-            {
+            if app.hex_ui.interact_mode == InteractMode::Edit {
                 let move_edit = (app.preferences.move_edit_cursor && !key_mod.ctrl)
                     || (!app.preferences.move_edit_cursor && key_mod.ctrl);
                     if let Some(view_key) = app.hex_ui.focused_view {
                         let view = &mut app.meta_state.meta.views[view_key];
+                        let low = &app.meta_state.meta.low;
+                        let step = usize::from(view.view.bytes_per_block);
+                        let block_start = view.view.block_start(app.edit_state.cursor, &low.perspectives, &low.regions);
+                        if block_start + step >= app.data.len() {
+                            break 'block;
+                        }
+                        app.edit_state.set_cursor_no_history(block_start);
                 if move_edit {
                         if let Some(edit_buf) = &mut view.view.edit_buffer_mut() {
                             if !edit_buf.move_cursor_forward() {
                                 edit_buf.move_cursor_begin();
                                 edit_buf.dirty = false;
-                                app.edit_state.step_cursor_forward();
+                                app.edit_state.step_cursor_forward(step);
                             }
                         }
                 } else {
-                    app.edit_state.step_cursor_forward();
+                    app.edit_state.step_cursor_forward(step);
                     keep_cursor_in_view(&mut view.view, &app.meta_state.meta.low.perspectives, &app.meta_state.meta.low.regions, app.edit_state.cursor);
                 }
             }
@@ -601,7 +650,7 @@ fn handle_key_pressed(
         }
         Key::Enter => {
             if let Some(view_key) = app.hex_ui.focused_view {
-                app.meta_state.meta.views[view_key].view.finish_editing(&mut app.edit_state, &mut app.data, &app.preferences, &mut gui.msg_dialog);
+                app.meta_state.meta.views[view_key].view.finish_editing(&mut app.edit_state, &mut app.data, &app.preferences, &app.meta_state.meta.low, &mut gui.msg_dialog);
             }
         }
         Key::A if key_mod.ctrl => {
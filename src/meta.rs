@@ -7,6 +7,7 @@ use {
     egui_sfml::egui::epaint::ahash::HashMap,
     serde::{Deserialize, Serialize},
     slotmap::{new_key_type, SlotMap},
+    std::path::PathBuf,
 };
 
 new_key_type! {
@@ -34,6 +35,9 @@ pub struct Bookmark {
     /// A bookmark can optionally have a type, which can be used to display its value, etc.
     #[serde(default)]
     pub value_type: ValueType,
+    /// If set, the offset is re-resolved from this when a process is opened
+    #[serde(default)]
+    pub anchor: Option<ModuleAnchor>,
 }
 
 #[derive(Serialize, Deserialize, Clone, PartialEq, Eq, Default)]
@@ -72,6 +76,51 @@ pub struct Meta {
     pub layouts: LayoutMap,
     pub bookmarks: Bookmarks,
     pub misc: Misc,
+    #[serde(default)]
+    pub pointer_paths: Vec<PointerPath>,
+    #[serde(default)]
+    pub auto_attach: Option<AutoAttach>,
+}
+
+/// A location relative to the base address of a module (executable or shared library)
+///
+/// Stays valid when the process is restarted and the module gets mapped at a different address.
+#[derive(Serialize, Deserialize, Clone, PartialEq, Eq, Debug)]
+pub struct ModuleAnchor {
+    /// Path of the module
+    pub module: PathBuf,
+    /// Offset from the base address of the module
+    pub offset: usize,
+}
+
+/// Attach to a process by executable name whenever it (re)starts
+#[derive(Serialize, Deserialize, Clone, PartialEq, Eq, Debug)]
+pub struct AutoAttach {
+    pub exe_name: String,
+    /// What to load after attaching
+    pub target: AttachTarget,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, Eq, Debug)]
+pub enum AttachTarget {
+    /// Every readable mapping of the process
+    AddrSpace,
+    /// The mapping containing the anchored address
+    Mapping(ModuleAnchor),
+}
+
+/// A chain of pointers starting from a static address inside a module
+///
+/// Can be used to find the same value again after the process was restarted.
+#[derive(Serialize, Deserialize, Clone, PartialEq, Eq, Debug)]
+pub struct PointerPath {
+    pub label: String,
+    /// Path of the module (executable or shared library) the chain starts in
+    pub module: PathBuf,
+    /// Offset of the first pointer from the base of the module
+    pub module_offset: usize,
+    /// Offsets to add after each dereference
+    pub offsets: Vec<usize>,
 }
 
 pub(crate) fn find_most_specific_region_for_offset(
@@ -156,6 +205,9 @@ pub struct NamedRegion {
     pub region: Region,
     #[serde(default)]
     pub desc: String,
+    /// If set, the region is moved to this anchor when a process is opened
+    #[serde(default)]
+    pub anchor: Option<ModuleAnchor>,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
@@ -169,6 +221,7 @@ impl NamedRegion {
             name: format!("New ({}..={})", sel.begin, sel.end),
             region: sel,
             desc: String::new(),
+            anchor: None,
         }
     }
 }
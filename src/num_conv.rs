@@ -0,0 +1,248 @@
+//! Formatting and parsing of typed numeric values for the numeric view kind
+
+use {
+    crate::watch_list::WatchType,
+    anyhow::{bail, Context},
+    serde::{Deserialize, Serialize},
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Radix {
+    Dec,
+    Hex,
+    Oct,
+    Bin,
+}
+
+impl Radix {
+    pub const ALL: [Self; 4] = [Self::Dec, Self::Hex, Self::Oct, Self::Bin];
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Dec => "decimal",
+            Self::Hex => "hex",
+            Self::Oct => "octal",
+            Self::Bin => "binary",
+        }
+    }
+    /// Bits that a digit holds, for the power of two radixes
+    fn digit_bits(self) -> u32 {
+        match self {
+            Self::Hex => 4,
+            Self::Oct => 3,
+            Self::Dec | Self::Bin => 1,
+        }
+    }
+    fn base(self) -> u32 {
+        match self {
+            Self::Dec => 10,
+            Self::Hex => 16,
+            Self::Oct => 8,
+            Self::Bin => 2,
+        }
+    }
+}
+
+fn is_signed(ty: WatchType) -> bool {
+    matches!(
+        ty,
+        WatchType::I8 | WatchType::I16 | WatchType::I32 | WatchType::I64
+    )
+}
+
+fn is_float(ty: WatchType) -> bool {
+    matches!(ty, WatchType::F32 | WatchType::F64)
+}
+
+fn bits(ty: WatchType) -> u32 {
+    #[expect(clippy::cast_possible_truncation, reason = "Sizes are at most 8")]
+    let bits = ty.size() as u32 * 8;
+    bits
+}
+
+/// Number of glyphs needed to show any value of `ty` in `radix`.
+///
+/// Floats are always decimal.
+pub fn width(ty: WatchType, radix: Radix) -> usize {
+    match ty {
+        WatchType::F32 => 14,
+        WatchType::F64 => 24,
+        _ => match radix {
+            // The minimum has the most digits, plus the sign
+            Radix::Dec if is_signed(ty) => (1u64 << (bits(ty) - 1)).to_string().len() + 1,
+            Radix::Dec => (u64::MAX >> (64 - bits(ty))).to_string().len(),
+            // Show the bits of signed values as they are, like unsigned ones
+            _ => {
+                let (bits, digit_bits) = (bits(ty), radix.digit_bits());
+                (bits / digit_bits + u32::from(bits % digit_bits != 0)) as usize
+            }
+        },
+    }
+}
+
+/// Read `bytes` as an unsigned integer
+fn read_bits(bytes: &[u8], big_endian: bool) -> u64 {
+    let fold = |acc: u64, &b: &u8| (acc << 8) | u64::from(b);
+    if big_endian {
+        bytes.iter().fold(0, fold)
+    } else {
+        bytes.iter().rev().fold(0, fold)
+    }
+}
+
+/// The lowest `size` bytes of `value`
+fn write_bits(value: u64, size: usize, big_endian: bool) -> Vec<u8> {
+    let mut bytes = value.to_le_bytes()[..size].to_vec();
+    if big_endian {
+        bytes.reverse();
+    }
+    bytes
+}
+
+/// Shortest representation of `value` that fits in `width`, if possible
+fn fit_float(value: impl std::fmt::Display + std::fmt::LowerExp + Copy, width: usize) -> String {
+    let s = value.to_string();
+    if s.len() <= width {
+        return s;
+    }
+    let s = format!("{value:e}");
+    if s.len() <= width {
+        return s;
+    }
+    for precision in (0..width).rev() {
+        let s = format!("{value:.precision$e}");
+        if s.len() <= width {
+            return s;
+        }
+    }
+    s
+}
+
+/// Format `bytes` as a value of `ty`, right aligned to [`width`].
+///
+/// Returns `None` if `bytes` is not the size of `ty`.
+pub fn format(ty: WatchType, bytes: &[u8], big_endian: bool, radix: Radix) -> Option<String> {
+    if bytes.len() != ty.size() {
+        return None;
+    }
+    let width = width(ty, radix);
+    let raw = read_bits(bytes, big_endian);
+    let s = match ty {
+        WatchType::F32 => {
+            #[expect(clippy::cast_possible_truncation, reason = "Read from 4 bytes")]
+            let value = f32::from_bits(raw as u32);
+            fit_float(value, width)
+        }
+        WatchType::F64 => fit_float(f64::from_bits(raw), width),
+        _ => match radix {
+            Radix::Dec if is_signed(ty) => {
+                let shift = 64 - bits(ty);
+                #[expect(clippy::cast_possible_wrap, reason = "Reinterpreting the sign bit")]
+                let value = (raw << shift) as i64 >> shift;
+                value.to_string()
+            }
+            Radix::Dec => raw.to_string(),
+            Radix::Hex => format!("{raw:0width$X}"),
+            Radix::Oct => format!("{raw:0width$o}"),
+            Radix::Bin => format!("{raw:0width$b}"),
+        },
+    };
+    Some(format!("{s:>width$}"))
+}
+
+/// Parse `s` as a value of `ty`, and return its bytes
+pub fn parse(ty: WatchType, s: &str, big_endian: bool, radix: Radix) -> anyhow::Result<Vec<u8>> {
+    let s = s.trim();
+    let size = ty.size();
+    let raw = match ty {
+        WatchType::F32 => u64::from(s.parse::<f32>()?.to_bits()),
+        WatchType::F64 => s.parse::<f64>()?.to_bits(),
+        _ if radix == Radix::Dec && is_signed(ty) => {
+            let value: i64 = s.parse()?;
+            if bits(ty) < 64 {
+                let half = 1i64 << (bits(ty) - 1);
+                if !(-half..half).contains(&value) {
+                    bail!("Value out of range for {}", ty.name());
+                }
+            }
+            #[expect(clippy::cast_sign_loss, reason = "Keeping the two's complement bits")]
+            let raw = value as u64;
+            raw
+        }
+        _ => {
+            let value = u64::from_str_radix(s, radix.base())
+                .with_context(|| format!("Invalid {} number", radix.name()))?;
+            if value.checked_shr(bits(ty)).unwrap_or(0) != 0 {
+                bail!("Value out of range for {}", ty.name());
+            }
+            value
+        }
+    };
+    Ok(write_bits(raw, size, big_endian))
+}
+
+/// Whether `c` can be typed as part of a value of `ty` in `radix`
+pub fn char_valid(ty: WatchType, radix: Radix, c: char) -> bool {
+    if is_float(ty) {
+        return c.is_ascii_digit() || matches!(c, '-' | '+' | '.' | 'e' | 'E');
+    }
+    match radix {
+        Radix::Dec => c.is_ascii_digit() || (c == '-' && is_signed(ty)),
+        _ => c.is_digit(radix.base()),
+    }
+}
+
+#[test]
+fn test_format() {
+    let fmt = |ty, bytes: &[u8], be, radix| format(ty, bytes, be, radix).unwrap_or_default();
+    assert_eq!(fmt(WatchType::U16, &[1, 2], false, Radix::Dec), "  513");
+    assert_eq!(fmt(WatchType::U16, &[1, 2], true, Radix::Dec), "  258");
+    assert_eq!(fmt(WatchType::I8, &[0xFF], false, Radix::Dec), "  -1");
+    assert_eq!(fmt(WatchType::I16, &[0, 0x80], false, Radix::Dec), "-32768");
+    assert_eq!(
+        fmt(WatchType::I32, &[0xFE; 4], false, Radix::Hex),
+        "FEFEFEFE"
+    );
+    assert_eq!(fmt(WatchType::U8, &[8], false, Radix::Oct), "010");
+    assert_eq!(fmt(WatchType::U8, &[5], false, Radix::Bin), "00000101");
+    assert_eq!(
+        fmt(WatchType::U64, &[0xFF; 8], false, Radix::Dec),
+        u64::MAX.to_string()
+    );
+    assert_eq!(
+        fmt(WatchType::F32, &1.5f32.to_le_bytes(), false, Radix::Dec),
+        "           1.5"
+    );
+    assert_eq!(
+        fmt(WatchType::F32, &f32::MIN.to_be_bytes(), true, Radix::Hex).trim(),
+        "-3.4028235e38"
+    );
+    assert_eq!(format(WatchType::U32, &[1, 2], false, Radix::Dec), None);
+}
+
+#[test]
+#[allow(clippy::unwrap_used)]
+fn test_parse() {
+    assert_eq!(
+        parse(WatchType::I16, "-2", false, Radix::Dec).unwrap(),
+        [0xFE, 0xFF]
+    );
+    assert_eq!(
+        parse(WatchType::U32, "258", true, Radix::Dec).unwrap(),
+        [0, 0, 1, 2]
+    );
+    assert_eq!(
+        parse(WatchType::I8, "ff", false, Radix::Hex).unwrap(),
+        [0xFF]
+    );
+    assert!(parse(WatchType::I8, "128", false, Radix::Dec).is_err());
+    assert!(parse(WatchType::U8, "400", false, Radix::Oct).is_err());
+    assert!(parse(WatchType::I64, &i64::MIN.to_string(), false, Radix::Dec).is_ok());
+    assert_eq!(
+        parse(WatchType::F64, "0.5", false, Radix::Dec).unwrap(),
+        0.5f64.to_le_bytes()
+    );
+    assert!(char_valid(WatchType::I32, Radix::Dec, '-'));
+    assert!(!char_valid(WatchType::U32, Radix::Dec, '-'));
+    assert!(char_valid(WatchType::U32, Radix::Hex, 'b'));
+    assert!(!char_valid(WatchType::U32, Radix::Oct, '8'));
+}
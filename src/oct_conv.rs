@@ -0,0 +1,18 @@
+pub fn byte_to_oct_digits(byte: u8) -> [u8; 3] {
+    [b'0' + byte / 64, b'0' + (byte / 8) % 8, b'0' + byte % 8]
+}
+
+#[test]
+fn test_byte_to_oct_digits() {
+    let pairs = [
+        (255, b"377"),
+        (0, b"000"),
+        (7, b"007"),
+        (8, b"010"),
+        (64, b"100"),
+        (100, b"144"),
+    ];
+    for (byte, oct) in pairs {
+        assert_eq!(byte_to_oct_digits(byte), *oct);
+    }
+}
@@ -0,0 +1,568 @@
+//! Binary patch formats: IPS, UPS, BPS and bsdiff
+
+use {
+    anyhow::{ensure, Context},
+    std::io::{Read, Write},
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PatchFormat {
+    Ips,
+    Ups,
+    Bps,
+    Bsdiff,
+}
+
+impl PatchFormat {
+    pub const ALL: [Self; 4] = [Self::Ips, Self::Ups, Self::Bps, Self::Bsdiff];
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Ips => "IPS",
+            Self::Ups => "UPS",
+            Self::Bps => "BPS",
+            Self::Bsdiff => "bsdiff",
+        }
+    }
+    pub fn extension(self) -> &'static str {
+        match self {
+            Self::Ips => "ips",
+            Self::Ups => "ups",
+            Self::Bps => "bps",
+            Self::Bsdiff => "bsdiff",
+        }
+    }
+    /// Detect the format from the magic bytes at the start of `patch`
+    pub fn detect(patch: &[u8]) -> Option<Self> {
+        if patch.starts_with(b"PATCH") {
+            Some(Self::Ips)
+        } else if patch.starts_with(b"UPS1") {
+            Some(Self::Ups)
+        } else if patch.starts_with(b"BPS1") {
+            Some(Self::Bps)
+        } else if patch.starts_with(b"BSDIFF40") {
+            Some(Self::Bsdiff)
+        } else {
+            None
+        }
+    }
+}
+
+/// Apply `patch` to `source`, detecting its format
+pub fn apply(patch: &[u8], source: &[u8]) -> anyhow::Result<Vec<u8>> {
+    match PatchFormat::detect(patch).context("Unknown patch format")? {
+        PatchFormat::Ips => apply_ips(patch, source),
+        PatchFormat::Ups => apply_ups(patch, source),
+        PatchFormat::Bps => apply_bps(patch, source),
+        PatchFormat::Bsdiff => apply_bsdiff(patch, source),
+    }
+}
+
+/// Create a patch that turns `source` into `target`
+pub fn create(format: PatchFormat, source: &[u8], target: &[u8]) -> anyhow::Result<Vec<u8>> {
+    match format {
+        PatchFormat::Ips => create_ips(source, target),
+        PatchFormat::Ups => Ok(create_ups(source, target)),
+        PatchFormat::Bps => Ok(create_bps(source, target)),
+        PatchFormat::Bsdiff => create_bsdiff(source, target),
+    }
+}
+
+struct Reader<'a> {
+    data: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn new(data: &'a [u8]) -> Self {
+        Self { data, pos: 0 }
+    }
+    fn at_end(&self) -> bool {
+        self.pos >= self.data.len()
+    }
+    fn bytes(&mut self, n: usize) -> anyhow::Result<&'a [u8]> {
+        let bytes = self
+            .pos
+            .checked_add(n)
+            .and_then(|end| self.data.get(self.pos..end))
+            .context("Unexpected end of patch")?;
+        self.pos += n;
+        Ok(bytes)
+    }
+    fn u8(&mut self) -> anyhow::Result<u8> {
+        Ok(self.bytes(1)?[0])
+    }
+    fn be(&mut self, n: usize) -> anyhow::Result<usize> {
+        Ok(self
+            .bytes(n)?
+            .iter()
+            .fold(0, |acc, &b| (acc << 8) | usize::from(b)))
+    }
+    /// The variable length integers of UPS and BPS
+    fn varint(&mut self) -> anyhow::Result<usize> {
+        let mut value: u64 = 0;
+        let mut shift: u64 = 1;
+        loop {
+            let x = self.u8()?;
+            value = u64::from(x & 0x7F)
+                .checked_mul(shift)
+                .and_then(|add| value.checked_add(add))
+                .context("Number overflow")?;
+            if x & 0x80 != 0 {
+                break;
+            }
+            shift = shift.checked_mul(0x80).context("Number overflow")?;
+            value = value.checked_add(shift).context("Number overflow")?;
+        }
+        Ok(usize::try_from(value)?)
+    }
+}
+
+#[expect(
+    clippy::cast_possible_truncation,
+    reason = "Only the lowest 7 bits are taken"
+)]
+fn push_varint(out: &mut Vec<u8>, mut value: usize) {
+    loop {
+        let x = (value & 0x7F) as u8;
+        value >>= 7;
+        if value == 0 {
+            out.push(0x80 | x);
+            break;
+        }
+        out.push(x);
+        value -= 1;
+    }
+}
+
+#[expect(
+    clippy::cast_possible_truncation,
+    reason = "Truncating to the lowest byte is intended"
+)]
+fn push_be(out: &mut Vec<u8>, value: usize, len: usize) {
+    out.extend((0..len).rev().map(|i| (value >> (i * 8)) as u8));
+}
+
+/// The furthest an IPS record can reach, with the largest 3 byte offset and 2 byte length
+const IPS_MAX_END: usize = 0xFF_FFFF + 0xFFFF;
+
+fn apply_ips(patch: &[u8], source: &[u8]) -> anyhow::Result<Vec<u8>> {
+    let mut r = Reader::new(patch);
+    r.bytes(5)?;
+    let mut out = source.to_vec();
+    loop {
+        if r.data.get(r.pos..r.pos + 3) == Some(b"EOF") {
+            r.pos += 3;
+            break;
+        }
+        let offset = r.be(3)?;
+        let size = r.be(2)?;
+        // Size 0 means a run of the same byte
+        let (len, run) = if size == 0 {
+            (r.be(2)?, Some(r.u8()?))
+        } else {
+            (size, None)
+        };
+        let end = offset
+            .checked_add(len)
+            .filter(|&end| end <= IPS_MAX_END)
+            .context("Record out of bounds")?;
+        if out.len() < end {
+            out.resize(end, 0);
+        }
+        match run {
+            Some(byte) => out[offset..end].fill(byte),
+            None => out[offset..end].copy_from_slice(r.bytes(len)?),
+        }
+    }
+    // Optional truncation extension
+    if let Ok(len) = r.be(3) {
+        out.truncate(len);
+    }
+    Ok(out)
+}
+
+fn create_ips(source: &[u8], target: &[u8]) -> anyhow::Result<Vec<u8>> {
+    ensure!(
+        target.len() < 0x100_0000,
+        "IPS patches can't address more than 16 MiB"
+    );
+    let mut out = b"PATCH".to_vec();
+    let mut i = 0;
+    while i < target.len() {
+        if source.get(i) == Some(&target[i]) {
+            i += 1;
+            continue;
+        }
+        let mut start = i;
+        // A record at this offset would be read as the end of the patch
+        if start == 0x45_4F46 {
+            start -= 1;
+        }
+        let mut end = i;
+        while end < target.len() && end - start < 0xFFFF && source.get(end) != Some(&target[end]) {
+            end += 1;
+        }
+        push_be(&mut out, start, 3);
+        push_be(&mut out, end - start, 2);
+        out.extend_from_slice(&target[start..end]);
+        i = end;
+    }
+    out.extend_from_slice(b"EOF");
+    if target.len() < source.len() {
+        push_be(&mut out, target.len(), 3);
+    }
+    Ok(out)
+}
+
+fn read_crc(bytes: &[u8]) -> u32 {
+    bytes
+        .iter()
+        .rev()
+        .fold(0, |acc, &b| (acc << 8) | u32::from(b))
+}
+
+/// Split the 12 byte checksum footer of UPS and BPS patches, after verifying the patch itself.
+///
+/// Returns the body and the source and target checksums.
+fn split_footer(patch: &[u8]) -> anyhow::Result<(&[u8], u32, u32)> {
+    ensure!(patch.len() >= 16, "Patch is too short");
+    let (body, footer) = patch.split_at(patch.len() - 12);
+    ensure!(
+        crc32fast::hash(&patch[..patch.len() - 4]) == read_crc(&footer[8..]),
+        "Patch checksum mismatch, the patch is damaged"
+    );
+    Ok((body, read_crc(&footer[..4]), read_crc(&footer[4..8])))
+}
+
+fn check_source(source: &[u8], size: usize, crc: u32) -> anyhow::Result<()> {
+    ensure!(
+        source.len() == size && crc32fast::hash(source) == crc,
+        "The patch is for different data"
+    );
+    Ok(())
+}
+
+fn push_footer(out: &mut Vec<u8>, source: &[u8], target: &[u8]) {
+    out.extend_from_slice(&crc32fast::hash(source).to_le_bytes());
+    out.extend_from_slice(&crc32fast::hash(target).to_le_bytes());
+    let patch_crc = crc32fast::hash(out);
+    out.extend_from_slice(&patch_crc.to_le_bytes());
+}
+
+fn apply_ups(patch: &[u8], source: &[u8]) -> anyhow::Result<Vec<u8>> {
+    let (body, source_crc, target_crc) = split_footer(patch)?;
+    let mut r = Reader::new(body);
+    r.bytes(4)?;
+    let source_size = r.varint()?;
+    let target_size = r.varint()?;
+    check_source(source, source_size, source_crc)?;
+    let mut out = source.to_vec();
+    out.resize(target_size, 0);
+    let mut pos: usize = 0;
+    while !r.at_end() {
+        pos = pos.checked_add(r.varint()?).context("Offset overflow")?;
+        // XOR with the source until a zero byte
+        loop {
+            let x = r.u8()?;
+            if x == 0 {
+                pos += 1;
+                break;
+            }
+            if let Some(byte) = out.get_mut(pos) {
+                *byte ^= x;
+            }
+            pos += 1;
+        }
+    }
+    ensure!(
+        crc32fast::hash(&out) == target_crc,
+        "Result checksum mismatch"
+    );
+    Ok(out)
+}
+
+fn create_ups(source: &[u8], target: &[u8]) -> Vec<u8> {
+    let mut out = b"UPS1".to_vec();
+    push_varint(&mut out, source.len());
+    push_varint(&mut out, target.len());
+    let source_at = |i| source.get(i).copied().unwrap_or(0);
+    let mut pos = 0;
+    let mut i = 0;
+    while i < target.len() {
+        if source_at(i) == target[i] {
+            i += 1;
+            continue;
+        }
+        push_varint(&mut out, i - pos);
+        while i < target.len() && source_at(i) != target[i] {
+            out.push(source_at(i) ^ target[i]);
+            i += 1;
+        }
+        out.push(0);
+        i += 1;
+        pos = i;
+    }
+    push_footer(&mut out, source, target);
+    out
+}
+
+fn apply_bps(patch: &[u8], source: &[u8]) -> anyhow::Result<Vec<u8>> {
+    let (body, source_crc, target_crc) = split_footer(patch)?;
+    let mut r = Reader::new(body);
+    r.bytes(4)?;
+    let source_size = r.varint()?;
+    let target_size = r.varint()?;
+    let metadata_size = r.varint()?;
+    r.bytes(metadata_size)?;
+    check_source(source, source_size, source_crc)?;
+    // The target size comes from the patch, so it's not trusted for preallocating
+    let mut out = Vec::new();
+    let mut source_rel: usize = 0;
+    let mut target_rel: usize = 0;
+    let out_of_bounds = "Copy out of bounds";
+    while !r.at_end() {
+        let action = r.varint()?;
+        let len = (action >> 2) + 1;
+        ensure!(
+            out.len().checked_add(len).is_some_and(|n| n <= target_size),
+            "Patch writes past the target size"
+        );
+        match action & 3 {
+            // Source read
+            0 => {
+                let start = out.len();
+                let bytes = start
+                    .checked_add(len)
+                    .and_then(|end| source.get(start..end))
+                    .context(out_of_bounds)?;
+                out.extend_from_slice(bytes);
+            }
+            // Target read
+            1 => out.extend_from_slice(r.bytes(len)?),
+            // Source and target copy, from a relative offset
+            cmd => {
+                let delta = r.varint()?;
+                let rel = if cmd == 2 {
+                    &mut source_rel
+                } else {
+                    &mut target_rel
+                };
+                *rel = if delta & 1 != 0 {
+                    rel.checked_sub(delta >> 1)
+                } else {
+                    rel.checked_add(delta >> 1)
+                }
+                .context(out_of_bounds)?;
+                if cmd == 2 {
+                    let start = *rel;
+                    let bytes = start
+                        .checked_add(len)
+                        .and_then(|end| source.get(start..end))
+                        .context(out_of_bounds)?;
+                    out.extend_from_slice(bytes);
+                    *rel += len;
+                } else {
+                    // The copied range can overlap with what is being written
+                    for _ in 0..len {
+                        let byte = *out.get(*rel).context(out_of_bounds)?;
+                        out.push(byte);
+                        *rel += 1;
+                    }
+                }
+            }
+        }
+    }
+    ensure!(
+        out.len() == target_size,
+        "Patch ended before the target size"
+    );
+    ensure!(
+        crc32fast::hash(&out) == target_crc,
+        "Result checksum mismatch"
+    );
+    Ok(out)
+}
+
+/// Create a BPS patch that only reads from the source at the same offset, or has the new bytes
+fn create_bps(source: &[u8], target: &[u8]) -> Vec<u8> {
+    let mut out = b"BPS1".to_vec();
+    push_varint(&mut out, source.len());
+    push_varint(&mut out, target.len());
+    // No metadata
+    push_varint(&mut out, 0);
+    let same = |i| source.get(i) == target.get(i);
+    let mut i = 0;
+    while i < target.len() {
+        let run_same = same(i);
+        let mut end = i;
+        while end < target.len() && same(end) == run_same {
+            end += 1;
+        }
+        let cmd = usize::from(!run_same);
+        push_varint(&mut out, ((end - i - 1) << 2) | cmd);
+        if !run_same {
+            out.extend_from_slice(&target[i..end]);
+        }
+        i = end;
+    }
+    push_footer(&mut out, source, target);
+    out
+}
+
+/// The sign and magnitude integers of bsdiff
+fn read_offt(bytes: &[u8]) -> anyhow::Result<i64> {
+    let raw = u64::from_le_bytes(bytes.try_into()?);
+    let magnitude = i64::try_from(raw & !(1 << 63))?;
+    Ok(if raw & (1 << 63) != 0 {
+        -magnitude
+    } else {
+        magnitude
+    })
+}
+
+fn bunzip(data: &[u8]) -> anyhow::Result<Vec<u8>> {
+    let mut out = Vec::new();
+    bzip2::read::BzDecoder::new(data).read_to_end(&mut out)?;
+    Ok(out)
+}
+
+fn bzip(data: &[u8]) -> std::io::Result<Vec<u8>> {
+    let mut enc = bzip2::write::BzEncoder::new(Vec::new(), bzip2::Compression::best());
+    enc.write_all(data)?;
+    enc.finish()
+}
+
+fn apply_bsdiff(patch: &[u8], source: &[u8]) -> anyhow::Result<Vec<u8>> {
+    ensure!(patch.len() >= 32, "Patch is too short");
+    let ctrl_len = usize::try_from(read_offt(&patch[8..16])?)?;
+    let diff_len = usize::try_from(read_offt(&patch[16..24])?)?;
+    let target_size = usize::try_from(read_offt(&patch[24..32])?)?;
+    let mut r = Reader::new(&patch[32..]);
+    let ctrl = bunzip(r.bytes(ctrl_len)?).context("Control block")?;
+    let diff = bunzip(r.bytes(diff_len)?).context("Diff block")?;
+    let extra = bunzip(&patch[32 + r.pos..]).context("Extra block")?;
+    let (mut diff, mut extra) = (Reader::new(&diff), Reader::new(&extra));
+    let mut out = Vec::with_capacity(target_size);
+    let mut old_pos: i64 = 0;
+    let mut ctrl = ctrl.chunks_exact(24);
+    while out.len() < target_size {
+        let triple = ctrl.next().context("Control block ended early")?;
+        let add_len = usize::try_from(read_offt(&triple[..8])?)?;
+        let copy_len = usize::try_from(read_offt(&triple[8..16])?)?;
+        let seek = read_offt(&triple[16..])?;
+        ensure!(
+            out.len() + add_len <= target_size,
+            "Patch writes past the target size"
+        );
+        // Add the diff bytes to the old bytes, which are 0 when out of bounds
+        for &byte in diff.bytes(add_len)? {
+            let old = usize::try_from(old_pos)
+                .ok()
+                .and_then(|pos| source.get(pos))
+                .copied()
+                .unwrap_or(0);
+            out.push(old.wrapping_add(byte));
+            old_pos += 1;
+        }
+        ensure!(
+            out.len() + copy_len <= target_size,
+            "Patch writes past the target size"
+        );
+        out.extend_from_slice(extra.bytes(copy_len)?);
+        old_pos = old_pos.checked_add(seek).context("Seek overflow")?;
+    }
+    Ok(out)
+}
+
+/// Create a bsdiff patch without searching for moved data.
+///
+/// The common length is a single diff block, which is mostly zeroes and compresses well
+/// for in-place modifications. The rest of the target is the extra block.
+fn create_bsdiff(source: &[u8], target: &[u8]) -> anyhow::Result<Vec<u8>> {
+    let common = source.len().min(target.len());
+    let offt =
+        |value: usize| -> anyhow::Result<[u8; 8]> { Ok(i64::try_from(value)?.to_le_bytes()) };
+    let mut ctrl = Vec::new();
+    ctrl.extend_from_slice(&offt(common)?);
+    ctrl.extend_from_slice(&offt(target.len() - common)?);
+    ctrl.extend_from_slice(&offt(0)?);
+    let diff: Vec<u8> = target[..common]
+        .iter()
+        .zip(source)
+        .map(|(new, old)| new.wrapping_sub(*old))
+        .collect();
+    let ctrl = bzip(&ctrl)?;
+    let diff = bzip(&diff)?;
+    let extra = bzip(&target[common..])?;
+    let mut out = b"BSDIFF40".to_vec();
+    out.extend_from_slice(&offt(ctrl.len())?);
+    out.extend_from_slice(&offt(diff.len())?);
+    out.extend_from_slice(&offt(target.len())?);
+    out.extend_from_slice(&ctrl);
+    out.extend_from_slice(&diff);
+    out.extend_from_slice(&extra);
+    Ok(out)
+}
+
+#[cfg(test)]
+fn test_pairs() -> Vec<(Vec<u8>, Vec<u8>)> {
+    let source: Vec<u8> = (0..=255).cycle().take(5000).collect();
+    let mut edited = source.clone();
+    edited[10] = 0xAA;
+    edited[100..200].fill(0);
+    let mut grown = edited.clone();
+    grown.extend_from_slice(b"appended");
+    let shrunk = edited[..3000].to_vec();
+    vec![
+        (source.clone(), edited),
+        (source.clone(), grown),
+        (source.clone(), shrunk),
+        (source, Vec::new()),
+    ]
+}
+
+#[test]
+#[allow(clippy::unwrap_used)]
+fn test_roundtrip() {
+    for format in PatchFormat::ALL {
+        for (source, target) in test_pairs() {
+            let patch = create(format, &source, &target).unwrap();
+            assert_eq!(PatchFormat::detect(&patch), Some(format));
+            assert_eq!(apply(&patch, &source).unwrap(), target, "{}", format.name());
+        }
+    }
+}
+
+#[test]
+#[allow(clippy::unwrap_used)]
+fn test_apply_ips() {
+    // A normal record, a run of 4 bytes, then truncation to 6 bytes
+    let patch = b"PATCH\x00\x00\x01\x00\x02AB\x00\x00\x04\x00\x00\x00\x04ZEOF\x00\x00\x06";
+    assert_eq!(apply(patch, b"01234567").unwrap(), b"0AB3ZZ");
+}
+
+#[test]
+#[allow(clippy::unwrap_used)]
+fn test_checksums() {
+    let (source, target) = test_pairs().swap_remove(0);
+    for format in [PatchFormat::Ups, PatchFormat::Bps] {
+        let mut patch = create(format, &source, &target).unwrap();
+        assert!(apply(&patch, &target).is_err());
+        patch[6] ^= 1;
+        assert!(apply(&patch, &source).is_err());
+    }
+}
+
+#[test]
+fn test_bps_bounds() {
+    // A target read of one byte, then a target copy far past the target size
+    let mut patch = b"BPS1".to_vec();
+    for value in [0, 1, 0, 1] {
+        push_varint(&mut patch, value);
+    }
+    patch.push(b'A');
+    push_varint(&mut patch, (usize::MAX >> 2) << 2 | 3);
+    push_varint(&mut patch, 0);
+    push_footer(&mut patch, b"", b"A");
+    assert!(apply(&patch, b"").is_err());
+}
@@ -0,0 +1,378 @@
+//! Reading and writing memory of other processes, and finding pointer paths in it
+
+use {
+    crate::meta::{ModuleAnchor, PointerPath},
+    anyhow::Context,
+    proc_maps::MapRange,
+    std::{
+        collections::HashSet,
+        path::Path,
+        sync::atomic::{AtomicBool, AtomicUsize, Ordering},
+    },
+};
+
+const PTR_SIZE: usize = (usize::BITS / 8) as usize;
+
+/// A handle that can be used to access the memory of a running process.
+///
+/// Opened for writing if possible, falling back to read-only access.
+pub struct ProcMem {
+    #[cfg(target_os = "linux")]
+    file: std::fs::File,
+    #[cfg(windows)]
+    handle: windows_sys::Win32::Foundation::HANDLE,
+}
+
+impl ProcMem {
+    #[cfg(target_os = "linux")]
+    pub fn open(pid: sysinfo::Pid) -> anyhow::Result<Self> {
+        let path = Path::new("/proc/").join(pid.to_string()).join("mem");
+        let file = std::fs::OpenOptions::new()
+            .read(true)
+            .write(true)
+            .open(&path)
+            .or_else(|_| std::fs::File::open(&path))
+            .context("Failed to open process memory")?;
+        Ok(Self { file })
+    }
+    #[cfg(windows)]
+    pub fn open(pid: sysinfo::Pid) -> anyhow::Result<Self> {
+        use {sysinfo::PidExt, windows_sys::Win32::System::Threading::*};
+        let read_access = PROCESS_VM_READ | PROCESS_QUERY_INFORMATION;
+        let mut handle = unsafe {
+            OpenProcess(
+                read_access | PROCESS_VM_WRITE | PROCESS_VM_OPERATION,
+                0,
+                pid.as_u32(),
+            )
+        };
+        if handle == 0 {
+            handle = unsafe { OpenProcess(read_access, 0, pid.as_u32()) };
+        }
+        if handle == 0 {
+            anyhow::bail!("Failed to open process.");
+        }
+        Ok(Self { handle })
+    }
+    /// Fill `buf` with memory starting at address `addr`
+    #[cfg(target_os = "linux")]
+    pub fn read_at(&self, addr: usize, buf: &mut [u8]) -> anyhow::Result<()> {
+        use std::os::unix::fs::FileExt;
+        self.file.read_exact_at(buf, addr as u64)?;
+        Ok(())
+    }
+    /// Fill `buf` with memory starting at address `addr`
+    #[cfg(windows)]
+    pub fn read_at(&self, addr: usize, buf: &mut [u8]) -> anyhow::Result<()> {
+        let mut n_read: usize = 0;
+        let ok = unsafe {
+            windows_sys::Win32::System::Diagnostics::Debug::ReadProcessMemory(
+                self.handle,
+                addr as _,
+                buf.as_mut_ptr() as *mut std::ffi::c_void,
+                buf.len(),
+                &mut n_read,
+            )
+        };
+        if ok == 0 || n_read != buf.len() {
+            anyhow::bail!("Failed to read process memory at {addr:X}");
+        }
+        Ok(())
+    }
+    /// Write `buf` to memory starting at address `addr`
+    #[cfg(target_os = "linux")]
+    pub fn write_at(&self, addr: usize, buf: &[u8]) -> anyhow::Result<()> {
+        use std::os::unix::fs::FileExt;
+        self.file.write_all_at(buf, addr as u64)?;
+        Ok(())
+    }
+    /// Write `buf` to memory starting at address `addr`
+    #[cfg(windows)]
+    pub fn write_at(&self, addr: usize, buf: &[u8]) -> anyhow::Result<()> {
+        let mut n_write: usize = 0;
+        let ok = unsafe {
+            windows_sys::Win32::System::Diagnostics::Debug::WriteProcessMemory(
+                self.handle,
+                addr as _,
+                buf.as_ptr() as *const std::ffi::c_void,
+                buf.len(),
+                &mut n_write,
+            )
+        };
+        if ok == 0 || n_write != buf.len() {
+            anyhow::bail!("Failed to write process memory at {addr:X}");
+        }
+        Ok(())
+    }
+    pub fn read_usize(&self, addr: usize) -> anyhow::Result<usize> {
+        let mut buf = [0; PTR_SIZE];
+        self.read_at(addr, &mut buf)?;
+        Ok(usize::from_le_bytes(buf))
+    }
+}
+
+#[cfg(windows)]
+impl Drop for ProcMem {
+    fn drop(&mut self) {
+        unsafe {
+            windows_sys::Win32::Foundation::CloseHandle(self.handle);
+        }
+    }
+}
+
+/// Parameters for a pointer path scan
+pub struct PointerScanParams {
+    /// The address the pointer paths should lead to
+    pub target: usize,
+    /// Maximum number of dereferences in a path
+    pub max_depth: u8,
+    /// Maximum offset added to a pointer value at each level
+    pub max_offset: usize,
+    /// Stop scanning after this many paths were found
+    pub max_results: usize,
+}
+
+/// Progress of a pointer path scan running on another thread, which can also cancel it
+#[derive(Default)]
+pub struct ScanProgress {
+    /// Steps done so far. Every memory range read and every level explored is a step.
+    pub done: AtomicUsize,
+    pub total: AtomicUsize,
+    pub cancel: AtomicBool,
+}
+
+impl ScanProgress {
+    /// How much of the scan is done, from 0 to 1
+    pub fn fraction(&self) -> f32 {
+        let total = self.total.load(Ordering::Relaxed);
+        if total == 0 {
+            return 0.0;
+        }
+        #[expect(
+            clippy::cast_precision_loss,
+            reason = "Step counts are small, and this is only for a progress bar"
+        )]
+        let fraction = self.done.load(Ordering::Relaxed) as f32 / total as f32;
+        fraction
+    }
+    fn step(&self) {
+        self.done.fetch_add(1, Ordering::Relaxed);
+    }
+    fn cancelled(&self) -> bool {
+        self.cancel.load(Ordering::Relaxed)
+    }
+}
+
+/// Maximum number of addresses to explore per level. Keeps deep scans from exploding.
+const MAX_FRONTIER: usize = 100_000;
+
+/// A mapping that belongs to a module (executable or shared library)
+struct ModuleRange<'a> {
+    start: usize,
+    end: usize,
+    path: &'a Path,
+}
+
+/// Returns the path of the module a map range belongs to, if any.
+///
+/// Anonymous mappings directly following a module mapping (like `.bss`) are
+/// considered part of that module.
+fn module_of(ranges: &[MapRange], idx: usize) -> Option<&Path> {
+    let range = &ranges[idx];
+    match range.filename() {
+        Some(path) => (!path.to_string_lossy().starts_with('[')).then_some(path),
+        None => {
+            let prev_idx = idx.checked_sub(1)?;
+            let prev = &ranges[prev_idx];
+            if prev.start() + prev.size() == range.start() {
+                module_of(ranges, prev_idx)
+            } else {
+                None
+            }
+        }
+    }
+}
+
+/// Base address of a module, which is the lowest address it's mapped at
+pub fn module_base(ranges: &[MapRange], module: &Path) -> Option<usize> {
+    ranges
+        .iter()
+        .filter(|range| range.filename() == Some(module))
+        .map(|range| range.start())
+        .min()
+}
+
+/// Express `addr` relative to the base of the module it belongs to, if any
+pub fn anchor_for_addr(ranges: &[MapRange], addr: usize) -> Option<ModuleAnchor> {
+    let module = module_of(ranges, range_idx_for_addr(ranges, addr)?)?;
+    let base = module_base(ranges, module)?;
+    Some(ModuleAnchor {
+        module: module.to_owned(),
+        offset: addr - base,
+    })
+}
+
+/// The address an anchor points to, if its module is loaded
+pub fn resolve_anchor(ranges: &[MapRange], anchor: &ModuleAnchor) -> Option<usize> {
+    module_base(ranges, &anchor.module).map(|base| base + anchor.offset)
+}
+
+/// Find pointer paths that lead from a static module address to `params.target`.
+///
+/// Every address is only explored once, so paths sharing a common tail are reported
+/// through the first one found.
+///
+/// If cancelled through `progress`, returns the paths found until then.
+pub fn scan_pointer_paths(
+    mem: &ProcMem,
+    ranges: &[MapRange],
+    params: &PointerScanParams,
+    progress: &ScanProgress,
+) -> Vec<PointerPath> {
+    let mut readable: Vec<(usize, usize)> = ranges
+        .iter()
+        .filter(|range| range.is_read())
+        .map(|range| (range.start(), range.start() + range.size()))
+        .collect();
+    readable.sort_unstable();
+    let points_to_readable = |ptr: usize| {
+        let idx = readable.partition_point(|&(start, _)| start <= ptr);
+        idx.checked_sub(1)
+            .map_or(false, |idx| ptr < readable[idx].1)
+    };
+    // (pointer value, address of pointer)
+    let mut pointers: Vec<(usize, usize)> = Vec::new();
+    let mut buf = Vec::new();
+    let writable = || {
+        ranges
+            .iter()
+            .filter(|range| range.is_read() && range.is_write())
+    };
+    progress.total.store(
+        writable().count() + usize::from(params.max_depth) + 1,
+        Ordering::Relaxed,
+    );
+    for range in writable() {
+        if progress.cancelled() {
+            return Vec::new();
+        }
+        progress.step();
+        buf.resize(range.size(), 0);
+        if mem.read_at(range.start(), &mut buf).is_err() {
+            continue;
+        }
+        for (i, chunk) in buf.array_chunks::<PTR_SIZE>().enumerate() {
+            let ptr = usize::from_le_bytes(*chunk);
+            if points_to_readable(ptr) {
+                pointers.push((ptr, range.start() + i * PTR_SIZE));
+            }
+        }
+    }
+    pointers.sort_unstable();
+    let mut modules: Vec<ModuleRange> = (0..ranges.len())
+        .filter_map(|idx| {
+            module_of(ranges, idx).map(|path| ModuleRange {
+                start: ranges[idx].start(),
+                end: ranges[idx].start() + ranges[idx].size(),
+                path,
+            })
+        })
+        .collect();
+    modules.sort_unstable_by_key(|module| module.start);
+    let module_at = |addr: usize| {
+        let idx = modules.partition_point(|module| module.start <= addr);
+        let module = &modules[idx.checked_sub(1)?];
+        (addr < module.end).then_some(module.path)
+    };
+    let mut results = Vec::new();
+    let mut visited = HashSet::new();
+    // (address, offsets leading from the value at this address to the target)
+    let mut frontier: Vec<(usize, Vec<usize>)> = vec![(params.target, Vec::new())];
+    for depth in 0..=params.max_depth {
+        progress.step();
+        let mut next = Vec::new();
+        for (addr, offsets) in frontier {
+            if progress.cancelled() {
+                return results;
+            }
+            if !visited.insert(addr) {
+                continue;
+            }
+            if let Some(module) = module_at(addr) {
+                let Some(base) = module_base(ranges, module) else {
+                    continue;
+                };
+                results.push(PointerPath {
+                    label: String::new(),
+                    module: module.to_owned(),
+                    module_offset: addr - base,
+                    offsets,
+                });
+                if results.len() >= params.max_results {
+                    return results;
+                }
+                continue;
+            }
+            if depth == params.max_depth {
+                continue;
+            }
+            let lowest = addr.saturating_sub(params.max_offset);
+            let first = pointers.partition_point(|&(val, _)| val < lowest);
+            for &(val, ptr_addr) in pointers[first..]
+                .iter()
+                .take_while(|&&(val, _)| val <= addr)
+            {
+                let mut new_offsets = Vec::with_capacity(offsets.len() + 1);
+                new_offsets.push(addr - val);
+                new_offsets.extend_from_slice(&offsets);
+                next.push((ptr_addr, new_offsets));
+            }
+        }
+        next.truncate(MAX_FRONTIER);
+        frontier = next;
+    }
+    results
+}
+
+/// Follow a pointer path in a running process, and return the address it leads to
+pub fn resolve_pointer_path(
+    mem: &ProcMem,
+    ranges: &[MapRange],
+    path: &PointerPath,
+) -> anyhow::Result<usize> {
+    let base = module_base(ranges, &path.module)
+        .with_context(|| format!("Module {} is not loaded", path.module.display()))?;
+    let mut addr = base + path.module_offset;
+    for off in &path.offsets {
+        addr = mem.read_usize(addr)?.wrapping_add(*off);
+    }
+    Ok(addr)
+}
+
+/// Short name of a module, for display purposes
+pub fn module_name(module: &Path) -> String {
+    module.file_name().map_or_else(
+        || module.display().to_string(),
+        |name| name.to_string_lossy().into_owned(),
+    )
+}
+
+/// Label for a map range, like `/usr/lib/libc.so.6 r-x`
+pub fn map_range_label(range: &MapRange) -> String {
+    format!(
+        "{} {}{}{}",
+        range
+            .filename()
+            .map_or_else(|| String::from("[anon]"), |p| p.display().to_string()),
+        if range.is_read() { 'r' } else { '-' },
+        if range.is_write() { 'w' } else { '-' },
+        if range.is_exec() { 'x' } else { '-' },
+    )
+}
+
+/// Find the index of the readable map range containing `addr`
+pub fn range_idx_for_addr(ranges: &[MapRange], addr: usize) -> Option<usize> {
+    ranges.iter().position(|range| {
+        range.is_read() && range.start() <= addr && addr < range.start() + range.size()
+    })
+}
@@ -0,0 +1,186 @@
+//! Find the offsets whose values behave in a certain way across several snapshots of the same
+//! data, like save files taken while a known in-game value changed
+
+use crate::watch_list::WatchType;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Criterion {
+    /// The value equals the known value of every snapshot
+    EqualsKnown,
+    /// The value goes up, down or stays the same whenever the known value does
+    FollowsKnown,
+    /// The value is different from the previous snapshot in every snapshot
+    ChangedEvery,
+    /// The value is the same in all snapshots
+    ChangedNever,
+}
+
+impl Criterion {
+    pub const ALL: [Self; 4] = [
+        Self::EqualsKnown,
+        Self::FollowsKnown,
+        Self::ChangedEvery,
+        Self::ChangedNever,
+    ];
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::EqualsKnown => "Equals known values",
+            Self::FollowsKnown => "Moves with known values",
+            Self::ChangedEvery => "Changed in every snapshot",
+            Self::ChangedNever => "Never changed",
+        }
+    }
+    pub fn uses_known(self) -> bool {
+        matches!(self, Self::EqualsKnown | Self::FollowsKnown)
+    }
+}
+
+/// A typed interpretation of the bytes at an offset
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Interp {
+    pub ty: WatchType,
+    pub big_endian: bool,
+}
+
+impl Interp {
+    pub fn label(self) -> String {
+        if self.ty.size() == 1 {
+            self.ty.name().to_string()
+        } else if self.big_endian {
+            format!("{} be", self.ty.name())
+        } else {
+            format!("{} le", self.ty.name())
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Match {
+    pub offset: usize,
+    pub interp: Interp,
+    /// The value in each snapshot
+    pub values: Vec<f64>,
+}
+
+/// The matches that were found, capped at a limit
+pub struct Report {
+    pub matches: Vec<Match>,
+    /// Number of matches, including the ones over the limit
+    pub total: usize,
+}
+
+fn approx_eq(a: f64, b: f64) -> bool {
+    (a - b).abs() <= 1e-6 * a.abs().max(b.abs()).max(1.0)
+}
+
+fn direction(from: f64, to: f64) -> std::cmp::Ordering {
+    if approx_eq(from, to) {
+        std::cmp::Ordering::Equal
+    } else {
+        from.total_cmp(&to)
+    }
+}
+
+fn matches(criterion: Criterion, values: &[f64], known: &[f64]) -> bool {
+    if values.iter().any(|v| !v.is_finite()) {
+        return false;
+    }
+    let pairs = || values.windows(2).map(|w| (w[0], w[1]));
+    match criterion {
+        Criterion::EqualsKnown => values.iter().zip(known).all(|(&v, &k)| approx_eq(v, k)),
+        Criterion::FollowsKnown => pairs()
+            .zip(known.windows(2))
+            .all(|((a, b), k)| direction(a, b) == direction(k[0], k[1])),
+        Criterion::ChangedEvery => pairs().all(|(a, b)| !approx_eq(a, b)),
+        Criterion::ChangedNever => pairs().all(|(a, b)| approx_eq(a, b)),
+    }
+}
+
+/// Check every `align`ed offset of the snapshots under every interpretation.
+///
+/// `known` has the known value of each snapshot, and is ignored by criteria that don't use it.
+/// Only the length that all snapshots have in common is examined.
+pub fn analyze(
+    snapshots: &[&[u8]],
+    known: &[f64],
+    interps: &[Interp],
+    criterion: Criterion,
+    align: usize,
+    limit: usize,
+) -> Report {
+    let mut report = Report {
+        matches: Vec::new(),
+        total: 0,
+    };
+    if snapshots.len() < 2 || (criterion.uses_known() && known.len() != snapshots.len()) {
+        return report;
+    }
+    let len = snapshots.iter().map(|snap| snap.len()).min().unwrap_or(0);
+    let mut values = Vec::with_capacity(snapshots.len());
+    for offset in (0..len).step_by(align.max(1)) {
+        for &interp in interps {
+            let size = interp.ty.size();
+            if offset + size > len {
+                continue;
+            }
+            values.clear();
+            values.extend(snapshots.iter().filter_map(|snap| {
+                interp
+                    .ty
+                    .to_f64(&snap[offset..offset + size], interp.big_endian)
+            }));
+            if matches(criterion, &values, known) {
+                report.total += 1;
+                if report.matches.len() < limit {
+                    report.matches.push(Match {
+                        offset,
+                        interp,
+                        values: values.clone(),
+                    });
+                }
+            }
+        }
+    }
+    report
+}
+
+#[test]
+fn test_analyze() {
+    // Gold is a little endian u16 at offset 4, a counter that always changes is at offset 0
+    let snaps: [&[u8]; 3] = [
+        &[1, 0, 0, 0, 100, 0, 7, 7],
+        &[2, 0, 0, 0, 150, 0, 7, 7],
+        &[3, 0, 0, 0, 90, 0, 7, 7],
+    ];
+    let u16le = Interp {
+        ty: WatchType::U16,
+        big_endian: false,
+    };
+    let u8 = Interp {
+        ty: WatchType::U8,
+        big_endian: false,
+    };
+    let found = |criterion, interps: &[Interp]| -> Vec<(usize, String)> {
+        analyze(&snaps, &[100., 150., 90.], interps, criterion, 1, 100)
+            .matches
+            .into_iter()
+            .map(|m| (m.offset, m.interp.label()))
+            .collect()
+    };
+    assert_eq!(
+        found(Criterion::EqualsKnown, &[u16le]),
+        [(4, "u16 le".to_string())]
+    );
+    // Offset 0 goes up, up while the known value goes up, down
+    assert_eq!(
+        found(Criterion::FollowsKnown, &[u8]),
+        [(4, "u8".to_string())]
+    );
+    assert_eq!(
+        found(Criterion::ChangedEvery, &[u8]),
+        [(0, "u8".to_string()), (4, "u8".to_string())]
+    );
+    assert_eq!(found(Criterion::ChangedNever, &[u8]).len(), 6);
+    let report = analyze(&snaps, &[], &[u8], Criterion::ChangedNever, 2, 1);
+    assert_eq!((report.matches.len(), report.total), (1, 2));
+}
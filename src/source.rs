@@ -1,15 +1,61 @@
-use std::{
-    fs::File,
-    io::{Read, Stdin},
+use {
+    crate::{
+        compression::Compression,
+        ihex_srec::{Extra, RecordFormat},
+    },
+    anyhow::{bail, Context},
+    std::{
+        fs::File,
+        io::{ErrorKind, Read, Stdin, Write},
+        net::{Shutdown, TcpListener, TcpStream, ToSocketAddrs},
+        process::{Child, ChildStdout, Command, ExitStatus, Stdio},
+        sync::{
+            atomic::{AtomicBool, Ordering},
+            Arc, Mutex,
+        },
+        time::Duration,
+    },
 };
 
+/// How often a listening socket checks whether it was cancelled
+const ACCEPT_POLL_INTERVAL: Duration = Duration::from_millis(50);
+
 #[derive(Debug)]
 pub enum SourceProvider {
     File(File),
     Stdin(Stdin),
+    /// Standard output of a spawned command
+    Command(CommandStream),
+    /// A connected TCP or Unix socket
+    Socket(SocketStream),
+    /// An Intel HEX or S-record file, with its blocks laid out one after another
+    RecordFile {
+        file: File,
+        format: RecordFormat,
+        /// Data bytes per record when writing it back out
+        record_size: usize,
+        /// Header and start address to write back out
+        extra: Extra,
+    },
+    /// A disk, partition or other block device, which is read and written in whole sectors
+    BlockDevice {
+        file: File,
+        sector_size: usize,
+    },
+    /// `/proc/<pid>/mem` of a process, with all of its mappings loaded
+    #[cfg(target_os = "linux")]
+    ProcAddrSpace(File),
+    /// An ELF core dump, with its `PT_LOAD` segments laid out one after another
+    ElfCore {
+        file: File,
+        /// Offset in the core file of each segment of the address map
+        file_offsets: Vec<u64>,
+    },
     #[cfg(windows)]
     WinProc {
         handle: windows_sys::Win32::Foundation::HANDLE,
+        /// The process the handle was opened for
+        pid: sysinfo::Pid,
         start: usize,
         size: usize,
     },
@@ -35,6 +81,9 @@ pub struct SourceAttributes {
 pub struct SourceState {
     /// Whether streaming has finished
     pub stream_end: bool,
+    /// If set, the data is the decompressed contents of the source,
+    /// and it gets compressed again when saving
+    pub compression: Option<Compression>,
 }
 
 #[derive(Debug)]
@@ -52,13 +101,38 @@ impl Clone for SourceProvider {
         match self {
             Self::File(file) => Self::File(file.try_clone().unwrap()),
             Self::Stdin(_) => Self::Stdin(std::io::stdin()),
+            Self::Command(stream) => Self::Command(stream.clone()),
+            Self::Socket(stream) => Self::Socket(stream.try_clone().unwrap()),
+            Self::BlockDevice { file, sector_size } => Self::BlockDevice {
+                file: file.try_clone().unwrap(),
+                sector_size: *sector_size,
+            },
+            #[cfg(target_os = "linux")]
+            Self::ProcAddrSpace(file) => Self::ProcAddrSpace(file.try_clone().unwrap()),
+            Self::RecordFile {
+                file,
+                format,
+                record_size,
+                extra,
+            } => Self::RecordFile {
+                file: file.try_clone().unwrap(),
+                format: *format,
+                record_size: *record_size,
+                extra: extra.clone(),
+            },
+            Self::ElfCore { file, file_offsets } => Self::ElfCore {
+                file: file.try_clone().unwrap(),
+                file_offsets: file_offsets.clone(),
+            },
             #[cfg(windows)]
             Self::WinProc {
                 handle,
+                pid,
                 start,
                 size,
             } => Self::WinProc {
                 handle: *handle,
+                pid: *pid,
                 start: *start,
                 size: *size,
             },
@@ -71,6 +145,12 @@ impl Read for SourceProvider {
         match self {
             SourceProvider::File(f) => f.read(buf),
             SourceProvider::Stdin(stdin) => stdin.read(buf),
+            SourceProvider::Command(stream) => stream.read(buf),
+            SourceProvider::Socket(stream) => stream.socket.read(buf),
+            SourceProvider::BlockDevice { file, .. } => file.read(buf),
+            #[cfg(target_os = "linux")]
+            SourceProvider::ProcAddrSpace(_) => Ok(0),
+            SourceProvider::ElfCore { .. } | SourceProvider::RecordFile { .. } => Ok(0),
             #[cfg(windows)]
             SourceProvider::WinProc { .. } => {
                 gamedebug_core::per_msg!("Todo: Read unimplemented");
@@ -79,3 +159,305 @@ impl Read for SourceProvider {
         }
     }
 }
+
+/// A spawned command whose standard output is streamed in.
+///
+/// The command is killed when the original (non-cloned) handle is dropped.
+#[derive(Debug)]
+pub struct CommandStream {
+    /// The command line that was spawned
+    pub cmd_line: String,
+    child: Arc<Mutex<Child>>,
+    stdout: Arc<Mutex<ChildStdout>>,
+    /// Everything the command wrote to its standard error so far
+    pub stderr: Arc<Mutex<String>>,
+    /// Whether this is the original handle, rather than a clone for the reader thread
+    owner: bool,
+}
+
+impl CommandStream {
+    /// Spawn `cmd_line`, which is split into the program and its arguments
+    /// with [`split_command_line`]
+    pub fn spawn(cmd_line: &str) -> anyhow::Result<Self> {
+        let tokens = split_command_line(cmd_line)?;
+        let (cmd, args) = tokens.split_first().context("Missing command")?;
+        let mut child = Command::new(cmd)
+            .args(args)
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()?;
+        let stdout = child.stdout.take().context("Failed to capture stdout")?;
+        let stderr = Arc::new(Mutex::new(String::new()));
+        if let Some(mut pipe) = child.stderr.take() {
+            let stderr = Arc::clone(&stderr);
+            std::thread::spawn(move || {
+                let mut buf = [0; 1024];
+                while let Ok(amount) = pipe.read(&mut buf)
+                    && amount != 0
+                {
+                    let Ok(mut stderr) = stderr.lock() else {
+                        return;
+                    };
+                    stderr.push_str(&String::from_utf8_lossy(&buf[..amount]));
+                }
+            });
+        }
+        Ok(Self {
+            cmd_line: cmd_line.to_owned(),
+            child: Arc::new(Mutex::new(child)),
+            stdout: Arc::new(Mutex::new(stdout)),
+            stderr,
+            owner: true,
+        })
+    }
+    pub fn pid(&self) -> Option<u32> {
+        self.child.lock().ok().map(|child| child.id())
+    }
+    /// The exit status, if the command has exited
+    pub fn try_status(&self) -> Option<ExitStatus> {
+        self.child.lock().ok()?.try_wait().ok()?
+    }
+    pub fn kill(&self) -> anyhow::Result<()> {
+        self.child
+            .lock()
+            .map_err(|_| anyhow::anyhow!("Child process lock poisoned"))?
+            .kill()?;
+        Ok(())
+    }
+    fn read(&self, buf: &mut [u8]) -> std::io::Result<usize> {
+        match self.stdout.lock() {
+            Ok(mut stdout) => stdout.read(buf),
+            Err(_) => Ok(0),
+        }
+    }
+}
+
+impl Clone for CommandStream {
+    fn clone(&self) -> Self {
+        Self {
+            cmd_line: self.cmd_line.clone(),
+            child: Arc::clone(&self.child),
+            stdout: Arc::clone(&self.stdout),
+            stderr: Arc::clone(&self.stderr),
+            owner: false,
+        }
+    }
+}
+
+impl Drop for CommandStream {
+    fn drop(&mut self) {
+        if self.owner
+            && let Ok(mut child) = self.child.lock()
+        {
+            // It might have exited already, nothing to do about errors
+            let _ = child.kill();
+            let _ = child.wait();
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum Socket {
+    Tcp(TcpStream),
+    #[cfg(unix)]
+    Unix(std::os::unix::net::UnixStream),
+}
+
+impl Socket {
+    /// Connect to `addr`, giving up on each address it resolves to after `timeout`
+    pub fn connect_tcp(addr: &str, timeout: Duration) -> std::io::Result<Self> {
+        let mut last_err = None;
+        for addr in addr.to_socket_addrs()? {
+            match TcpStream::connect_timeout(&addr, timeout) {
+                Ok(stream) => return Ok(Self::Tcp(stream)),
+                Err(e) => last_err = Some(e),
+            }
+        }
+        Err(last_err.unwrap_or_else(|| {
+            std::io::Error::new(ErrorKind::InvalidInput, "The address didn't resolve")
+        }))
+    }
+    /// Listen on `addr`, and accept a single connection.
+    ///
+    /// Blocks until someone connects, or until `cancel` is set.
+    pub fn accept_tcp(addr: &str, cancel: &AtomicBool) -> std::io::Result<Self> {
+        let listener = TcpListener::bind(addr)?;
+        listener.set_nonblocking(true)?;
+        let stream = poll_accept(|| listener.accept().map(|(stream, _)| stream), cancel)?;
+        stream.set_nonblocking(false)?;
+        Ok(Self::Tcp(stream))
+    }
+    #[cfg(unix)]
+    pub fn connect_unix(path: &str) -> std::io::Result<Self> {
+        std::os::unix::net::UnixStream::connect(path).map(Self::Unix)
+    }
+    /// Listen on the socket file at `path`, and accept a single connection.
+    ///
+    /// Blocks until someone connects, or until `cancel` is set.
+    #[cfg(unix)]
+    pub fn accept_unix(path: &str, cancel: &AtomicBool) -> std::io::Result<Self> {
+        let listener = std::os::unix::net::UnixListener::bind(path)?;
+        let result: std::io::Result<_> = try {
+            listener.set_nonblocking(true)?;
+            let stream = poll_accept(|| listener.accept().map(|(stream, _)| stream), cancel)?;
+            stream.set_nonblocking(false)?;
+            stream
+        };
+        // Nobody else can connect anyway, don't leave the socket file lying around
+        let _ = std::fs::remove_file(path);
+        Ok(Self::Unix(result?))
+    }
+    /// Description of the other end of the connection
+    pub fn peer(&self) -> String {
+        match self {
+            Self::Tcp(stream) => match stream.peer_addr() {
+                Ok(addr) => format!("TCP {addr}"),
+                Err(_) => String::from("TCP"),
+            },
+            #[cfg(unix)]
+            Self::Unix(stream) => match stream
+                .peer_addr()
+                .ok()
+                .and_then(|addr| addr.as_pathname().map(|path| path.display().to_string()))
+            {
+                Some(path) => format!("Unix {path}"),
+                None => String::from("Unix socket"),
+            },
+        }
+    }
+    fn try_clone(&self) -> std::io::Result<Self> {
+        match self {
+            Self::Tcp(stream) => stream.try_clone().map(Self::Tcp),
+            #[cfg(unix)]
+            Self::Unix(stream) => stream.try_clone().map(Self::Unix),
+        }
+    }
+    fn shutdown(&self) -> std::io::Result<()> {
+        match self {
+            Self::Tcp(stream) => stream.shutdown(Shutdown::Both),
+            #[cfg(unix)]
+            Self::Unix(stream) => stream.shutdown(Shutdown::Both),
+        }
+    }
+}
+
+/// Call the non-blocking `accept` until it returns a connection, or until `cancel` is set
+fn poll_accept<T>(
+    mut accept: impl FnMut() -> std::io::Result<T>,
+    cancel: &AtomicBool,
+) -> std::io::Result<T> {
+    loop {
+        match accept() {
+            Err(e) if e.kind() == ErrorKind::WouldBlock => {
+                if cancel.load(Ordering::Relaxed) {
+                    return Err(std::io::Error::new(ErrorKind::Interrupted, "Cancelled"));
+                }
+                std::thread::sleep(ACCEPT_POLL_INTERVAL);
+            }
+            result => return result,
+        }
+    }
+}
+
+impl Read for Socket {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        match self {
+            Self::Tcp(stream) => stream.read(buf),
+            #[cfg(unix)]
+            Self::Unix(stream) => stream.read(buf),
+        }
+    }
+}
+
+impl Write for Socket {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        match self {
+            Self::Tcp(stream) => stream.write(buf),
+            #[cfg(unix)]
+            Self::Unix(stream) => stream.write(buf),
+        }
+    }
+    fn flush(&mut self) -> std::io::Result<()> {
+        match self {
+            Self::Tcp(stream) => stream.flush(),
+            #[cfg(unix)]
+            Self::Unix(stream) => stream.flush(),
+        }
+    }
+}
+
+/// A socket whose received bytes are streamed in.
+///
+/// Like with [`CommandStream`], the connection is shut down when the original
+/// (non-cloned) handle is dropped.
+#[derive(Debug)]
+pub struct SocketStream {
+    pub socket: Socket,
+    owner: bool,
+}
+
+impl SocketStream {
+    pub fn new(socket: Socket) -> Self {
+        Self {
+            socket,
+            owner: true,
+        }
+    }
+    fn try_clone(&self) -> std::io::Result<Self> {
+        Ok(Self {
+            socket: self.socket.try_clone()?,
+            owner: false,
+        })
+    }
+}
+
+impl Drop for SocketStream {
+    fn drop(&mut self) {
+        if self.owner {
+            // The other end might have closed it already
+            let _ = self.socket.shutdown();
+        }
+    }
+}
+
+/// Split a command line at whitespace, except inside double or single quotes.
+///
+/// There are no escapes, so Windows paths can be written as they are.
+fn split_command_line(cmd_line: &str) -> anyhow::Result<Vec<String>> {
+    let mut tokens = Vec::new();
+    let mut token = None::<String>;
+    let mut quote = None;
+    for c in cmd_line.chars() {
+        match quote {
+            Some(q) if c == q => quote = None,
+            Some(_) => token.get_or_insert_with(String::new).push(c),
+            None if c == '"' || c == '\'' => {
+                quote = Some(c);
+                // Empty quotes are still an argument
+                token.get_or_insert_with(String::new);
+            }
+            None if c.is_whitespace() => tokens.extend(token.take()),
+            None => token.get_or_insert_with(String::new).push(c),
+        }
+    }
+    if let Some(q) = quote {
+        bail!("Missing closing {q}");
+    }
+    tokens.extend(token);
+    Ok(tokens)
+}
+
+#[test]
+#[allow(clippy::unwrap_used)]
+fn test_split_command_line() {
+    assert_eq!(
+        split_command_line(r#"sh -c "cat /dev/mem | head"  ''"#).unwrap(),
+        ["sh", "-c", "cat /dev/mem | head", ""]
+    );
+    assert_eq!(
+        split_command_line(r#""C:\Program Files\x.exe" --out='a b'"#).unwrap(),
+        [r"C:\Program Files\x.exe", "--out=a b"]
+    );
+    assert!(split_command_line("echo \"oops").is_err());
+}
@@ -0,0 +1,308 @@
+//! Decoding and encoding of the text kinds that Text views and text search use
+
+use {
+    crate::view::TextKind,
+    encoding_rs::{DecoderResult, Encoding},
+};
+
+/// What a Text view shows for a byte (or block) of data
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Decoded {
+    /// A character that starts here
+    Char(char),
+    /// Part of a multi-byte character that started at an earlier byte
+    Continuation,
+    /// Not valid in the encoding
+    Invalid,
+}
+
+/// The ISO-8859 parts that can be chosen. Part 12 was never published.
+pub const ISO_8859_PARTS: [u8; 15] = [1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 13, 14, 15, 16];
+
+/// How far back to look for the start of a run of Shift-JIS lead bytes
+const SHIFT_JIS_LOOKBACK: usize = 64;
+
+/// Decode the text at `idx` of `data`.
+///
+/// Multi-byte characters are decoded at their first byte, and the rest of their bytes
+/// are [`Decoded::Continuation`].
+pub fn decode_at(kind: &TextKind, data: &[u8], idx: usize) -> Decoded {
+    let Some(&byte) = data.get(idx) else {
+        return Decoded::Invalid;
+    };
+    let decoded = match kind {
+        TextKind::Ascii => Some(char::from(byte)),
+        TextKind::Utf16Le => return decode_utf16_at(data, idx, u16::from_le_bytes),
+        TextKind::Utf16Be => return decode_utf16_at(data, idx, u16::from_be_bytes),
+        TextKind::Utf8 => return decode_utf8_at(data, idx),
+        TextKind::ShiftJis => return decode_shift_jis_at(data, idx),
+        TextKind::Table(table) => return table.decode_at(data, idx),
+        TextKind::Cp437 => Some(match byte {
+            0x00..=0x7F => char::from(byte),
+            _ => CP437_HIGH[usize::from(byte - 0x80)],
+        }),
+        TextKind::Windows1252 => decode_with(encoding_rs::WINDOWS_1252, &[byte]),
+        TextKind::Iso8859(part) => match byte {
+            // The lower part is the same for all parts: ASCII and the C1 control codes
+            0x00..=0x9F => Some(char::from(byte)),
+            _ => iso_8859_upper(*part).and_then(|enc| decode_with(enc, &[byte])),
+        },
+        TextKind::Ebcdic => Some(EBCDIC_037[usize::from(byte)]),
+    };
+    decoded.map_or(Decoded::Invalid, Decoded::Char)
+}
+
+/// Encode `s` as `kind`, or return `None` if it has characters that `kind` can't encode
+pub fn encode(kind: &TextKind, s: &str) -> Option<Vec<u8>> {
+    match kind {
+        TextKind::Ascii => s.is_ascii().then(|| s.as_bytes().to_vec()),
+        TextKind::Utf8 => Some(s.as_bytes().to_vec()),
+        TextKind::Utf16Le => Some(s.encode_utf16().flat_map(u16::to_le_bytes).collect()),
+        TextKind::Utf16Be => Some(s.encode_utf16().flat_map(u16::to_be_bytes).collect()),
+        TextKind::ShiftJis => {
+            let (bytes, _, unmappable) = encoding_rs::SHIFT_JIS.encode(s);
+            (!unmappable).then(|| bytes.into_owned())
+        }
+        TextKind::Table(table) => table.encode(s),
+        // Single byte encodings, where a search of all the bytes is cheap enough
+        TextKind::Cp437 | TextKind::Windows1252 | TextKind::Iso8859(_) | TextKind::Ebcdic => s
+            .chars()
+            .map(|c| (0..=255).find(|&b| decode_at(kind, &[b], 0) == Decoded::Char(c)))
+            .collect(),
+    }
+}
+
+/// Decode a complete sequence of `bytes` that should make up exactly one character
+fn decode_with(encoding: &'static Encoding, bytes: &[u8]) -> Option<char> {
+    let mut decoder = encoding.new_decoder_without_bom_handling();
+    let mut buf = [0; 8];
+    let (result, read, written) = decoder.decode_to_utf8_without_replacement(bytes, &mut buf, true);
+    if !matches!(result, DecoderResult::InputEmpty) || read != bytes.len() {
+        return None;
+    }
+    let mut chars = std::str::from_utf8(&buf[..written]).ok()?.chars();
+    match (chars.next(), chars.next()) {
+        (Some(c), None) => Some(c),
+        _ => None,
+    }
+}
+
+/// An encoding that has the same upper part (0xA0..=0xFF) as an ISO-8859 part.
+///
+/// Parts 1, 9 and 11 aren't in `encoding_rs`, but their Windows supersets only differ below 0xA0.
+fn iso_8859_upper(part: u8) -> Option<&'static Encoding> {
+    use encoding_rs::*;
+    Some(match part {
+        1 => WINDOWS_1252,
+        2 => ISO_8859_2,
+        3 => ISO_8859_3,
+        4 => ISO_8859_4,
+        5 => ISO_8859_5,
+        6 => ISO_8859_6,
+        7 => ISO_8859_7,
+        8 => ISO_8859_8,
+        9 => WINDOWS_1254,
+        10 => ISO_8859_10,
+        11 => WINDOWS_874,
+        13 => ISO_8859_13,
+        14 => ISO_8859_14,
+        15 => ISO_8859_15,
+        16 => ISO_8859_16,
+        _ => return None,
+    })
+}
+
+fn decode_utf16_at(data: &[u8], idx: usize, from_bytes: fn([u8; 2]) -> u16) -> Decoded {
+    let unit_at = |i: usize| data.get(i..i + 2).map(|b| from_bytes([b[0], b[1]]));
+    let Some(unit) = unit_at(idx) else {
+        return Decoded::Invalid;
+    };
+    match unit {
+        0xD800..=0xDBFF => match unit_at(idx + 2) {
+            Some(low) => match char::decode_utf16([unit, low]).next() {
+                Some(Ok(c)) => Decoded::Char(c),
+                _ => Decoded::Invalid,
+            },
+            None => Decoded::Invalid,
+        },
+        0xDC00..=0xDFFF => match idx.checked_sub(2).and_then(unit_at) {
+            Some(0xD800..=0xDBFF) => Decoded::Continuation,
+            _ => Decoded::Invalid,
+        },
+        _ => char::from_u32(u32::from(unit)).map_or(Decoded::Invalid, Decoded::Char),
+    }
+}
+
+/// The character of a valid UTF-8 sequence starting at `idx`, and the length of the sequence
+fn utf8_char_at(data: &[u8], idx: usize) -> Option<(char, usize)> {
+    let len = match *data.get(idx)? {
+        0x00..=0x7F => 1,
+        0xC2..=0xDF => 2,
+        0xE0..=0xEF => 3,
+        0xF0..=0xF4 => 4,
+        _ => return None,
+    };
+    let c = std::str::from_utf8(data.get(idx..idx + len)?)
+        .ok()?
+        .chars()
+        .next()?;
+    Some((c, len))
+}
+
+fn decode_utf8_at(data: &[u8], idx: usize) -> Decoded {
+    if let Some((c, _)) = utf8_char_at(data, idx) {
+        return Decoded::Char(c);
+    }
+    // Look for a sequence that started at most 3 bytes earlier, and covers this byte
+    for start in (idx.saturating_sub(3)..idx).rev() {
+        if let Some((_, len)) = utf8_char_at(data, start) {
+            return if start + len > idx {
+                Decoded::Continuation
+            } else {
+                Decoded::Invalid
+            };
+        }
+    }
+    Decoded::Invalid
+}
+
+fn is_shift_jis_lead(byte: u8) -> bool {
+    matches!(byte, 0x81..=0x9F | 0xE0..=0xFC)
+}
+
+fn is_shift_jis_trail(byte: u8) -> bool {
+    matches!(byte, 0x40..=0x7E | 0x80..=0xFC)
+}
+
+fn decode_shift_jis_at(data: &[u8], idx: usize) -> Decoded {
+    let byte = data[idx];
+    // Lead bytes can also be trail bytes, so whether this is the second byte of a pair
+    // depends on how many lead bytes are right before it
+    let leads_before = data[..idx]
+        .iter()
+        .rev()
+        .take(SHIFT_JIS_LOOKBACK)
+        .take_while(|&&b| is_shift_jis_lead(b))
+        .count();
+    if leads_before % 2 == 1
+        && is_shift_jis_trail(byte)
+        && decode_with(encoding_rs::SHIFT_JIS, &data[idx - 1..=idx]).is_some()
+    {
+        return Decoded::Continuation;
+    }
+    let decoded = if is_shift_jis_lead(byte) {
+        data.get(idx..idx + 2)
+            .and_then(|pair| decode_with(encoding_rs::SHIFT_JIS, pair))
+    } else {
+        decode_with(encoding_rs::SHIFT_JIS, &[byte])
+    };
+    decoded.map_or(Decoded::Invalid, Decoded::Char)
+}
+
+/// The upper half of code page 437. The lower half is ASCII.
+const CP437_HIGH: [char; 128] = [
+    'Ç', 'ü', 'é', 'â', 'ä', 'à', 'å', 'ç', 'ê', 'ë', 'è', 'ï', 'î', 'ì', 'Ä', 'Å', 'É', 'æ', 'Æ',
+    'ô', 'ö', 'ò', 'û', 'ù', 'ÿ', 'Ö', 'Ü', '¢', '£', '¥', '₧', 'ƒ', 'á', 'í', 'ó', 'ú', 'ñ', 'Ñ',
+    'ª', 'º', '¿', '⌐', '¬', '½', '¼', '¡', '«', '»', '░', '▒', '▓', '│', '┤', '╡', '╢', '╖', '╕',
+    '╣', '║', '╗', '╝', '╜', '╛', '┐', '└', '┴', '┬', '├', '─', '┼', '╞', '╟', '╚', '╔', '╩', '╦',
+    '╠', '═', '╬', '╧', '╨', '╤', '╥', '╙', '╘', '╒', '╓', '╫', '╪', '┘', '┌', '█', '▄', '▌', '▐',
+    '▀', 'α', 'ß', 'Γ', 'π', 'Σ', 'σ', 'µ', 'τ', 'Φ', 'Θ', 'Ω', 'δ', '∞', 'φ', 'ε', '∩', '≡', '±',
+    '≥', '≤', '⌠', '⌡', '÷', '≈', '°', '∙', '·', '√', 'ⁿ', '²', '■', '\u{a0}',
+];
+
+/// EBCDIC code page 037, the US/Canada variant
+const EBCDIC_037: [char; 256] = [
+    '\u{0}', '\u{1}', '\u{2}', '\u{3}', '\u{9c}', '\u{9}', '\u{86}', '\u{7f}', '\u{97}', '\u{8d}',
+    '\u{8e}', '\u{b}', '\u{c}', '\u{d}', '\u{e}', '\u{f}', '\u{10}', '\u{11}', '\u{12}', '\u{13}',
+    '\u{9d}', '\u{85}', '\u{8}', '\u{87}', '\u{18}', '\u{19}', '\u{92}', '\u{8f}', '\u{1c}',
+    '\u{1d}', '\u{1e}', '\u{1f}', '\u{80}', '\u{81}', '\u{82}', '\u{83}', '\u{84}', '\u{a}',
+    '\u{17}', '\u{1b}', '\u{88}', '\u{89}', '\u{8a}', '\u{8b}', '\u{8c}', '\u{5}', '\u{6}',
+    '\u{7}', '\u{90}', '\u{91}', '\u{16}', '\u{93}', '\u{94}', '\u{95}', '\u{96}', '\u{4}',
+    '\u{98}', '\u{99}', '\u{9a}', '\u{9b}', '\u{14}', '\u{15}', '\u{9e}', '\u{1a}', ' ', '\u{a0}',
+    'â', 'ä', 'à', 'á', 'ã', 'å', 'ç', 'ñ', '¢', '.', '<', '(', '+', '|', '&', 'é', 'ê', 'ë', 'è',
+    'í', 'î', 'ï', 'ì', 'ß', '!', '$', '*', ')', ';', '¬', '-', '/', 'Â', 'Ä', 'À', 'Á', 'Ã', 'Å',
+    'Ç', 'Ñ', '¦', ',', '%', '_', '>', '?', 'ø', 'É', 'Ê', 'Ë', 'È', 'Í', 'Î', 'Ï', 'Ì', '`', ':',
+    '#', '@', '\'', '=', '"', 'Ø', 'a', 'b', 'c', 'd', 'e', 'f', 'g', 'h', 'i', '«', '»', 'ð', 'ý',
+    'þ', '±', '°', 'j', 'k', 'l', 'm', 'n', 'o', 'p', 'q', 'r', 'ª', 'º', 'æ', '¸', 'Æ', '¤', 'µ',
+    '~', 's', 't', 'u', 'v', 'w', 'x', 'y', 'z', '¡', '¿', 'Ð', 'Ý', 'Þ', '®', '^', '£', '¥', '·',
+    '©', '§', '¶', '¼', '½', '¾', '[', ']', '¯', '¨', '´', '×', '{', 'A', 'B', 'C', 'D', 'E', 'F',
+    'G', 'H', 'I', '\u{ad}', 'ô', 'ö', 'ò', 'ó', 'õ', '}', 'J', 'K', 'L', 'M', 'N', 'O', 'P', 'Q',
+    'R', '¹', 'û', 'ü', 'ù', 'ú', 'ÿ', '\\', '÷', 'S', 'T', 'U', 'V', 'W', 'X', 'Y', 'Z', '²', 'Ô',
+    'Ö', 'Ò', 'Ó', 'Õ', '0', '1', '2', '3', '4', '5', '6', '7', '8', '9', '³', 'Û', 'Ü', 'Ù', 'Ú',
+    '\u{9f}',
+];
+
+#[test]
+fn test_decode() {
+    use Decoded::{Char, Continuation, Invalid};
+    let decode_all = |kind, data: &[u8]| {
+        (0..data.len())
+            .map(|i| decode_at(&kind, data, i))
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(
+        decode_all(TextKind::Utf8, "aé€".as_bytes()),
+        [
+            Char('a'),
+            Char('é'),
+            Continuation,
+            Char('€'),
+            Continuation,
+            Continuation
+        ]
+    );
+    assert_eq!(
+        decode_all(TextKind::Utf8, b"\xC3a\x80"),
+        [Invalid, Char('a'), Invalid]
+    );
+    // 0x83 is both a lead and a trail byte
+    assert_eq!(
+        decode_all(TextKind::ShiftJis, b"\x83\x83\x83\x41a\xB1"),
+        [
+            Char('ャ'),
+            Continuation,
+            Char('ア'),
+            Continuation,
+            Char('a'),
+            Char('ｱ')
+        ]
+    );
+    assert_eq!(decode_at(&TextKind::Cp437, &[0xB0], 0), Char('░'));
+    assert_eq!(decode_at(&TextKind::Ebcdic, &[0xC1], 0), Char('A'));
+    assert_eq!(decode_at(&TextKind::Windows1252, &[0x80], 0), Char('€'));
+    assert_eq!(decode_at(&TextKind::Iso8859(1), &[0x80], 0), Char('\u{80}'));
+    assert_eq!(decode_at(&TextKind::Iso8859(5), &[0xB0], 0), Char('А'));
+    assert_eq!(decode_at(&TextKind::Iso8859(9), &[0xF0], 0), Char('ğ'));
+    // Surrogate pair
+    let pair = [0x3D, 0xD8, 0x00, 0xDE];
+    assert_eq!(decode_at(&TextKind::Utf16Le, &pair, 0), Char('😀'));
+    assert_eq!(decode_at(&TextKind::Utf16Le, &pair, 2), Continuation);
+    assert_eq!(decode_at(&TextKind::Utf16Le, &pair[2..], 0), Invalid);
+}
+
+#[test]
+fn test_encode() {
+    assert_eq!(encode(&TextKind::Ascii, "é"), None);
+    assert_eq!(
+        encode(&TextKind::Utf16Be, "a").as_deref(),
+        Some(&[0, b'a'][..])
+    );
+    assert_eq!(
+        encode(&TextKind::ShiftJis, "アa").as_deref(),
+        Some(&b"\x83\x41a"[..])
+    );
+    assert_eq!(
+        encode(&TextKind::Ebcdic, "A1").as_deref(),
+        Some(&[0xC1, 0xF1][..])
+    );
+    assert_eq!(encode(&TextKind::Cp437, "░").as_deref(), Some(&[0xB0][..]));
+    assert_eq!(
+        encode(&TextKind::Iso8859(5), "А").as_deref(),
+        Some(&[0xB0][..])
+    );
+    assert_eq!(
+        encode(&TextKind::Windows1252, "€").as_deref(),
+        Some(&[0x80][..])
+    );
+    assert_eq!(encode(&TextKind::Cp437, "€"), None);
+}
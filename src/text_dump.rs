@@ -0,0 +1,248 @@
+//! Text hex dumps, like the ones produced by `xxd`, `hexdump -C` and `od`
+
+use {
+    anyhow::{bail, Context},
+    std::fmt::Write,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DumpFormat {
+    /// `xxd`
+    Xxd,
+    /// `hexdump -C`
+    HexdumpC,
+    /// `od -An -tx1`
+    Od,
+    /// Hex digits only, whitespace is ignored
+    Plain,
+}
+
+const ROW_LEN: usize = 16;
+
+impl DumpFormat {
+    pub const ALL: [Self; 4] = [Self::Xxd, Self::HexdumpC, Self::Od, Self::Plain];
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Xxd => "xxd",
+            Self::HexdumpC => "hexdump -C",
+            Self::Od => "od -An -tx1",
+            Self::Plain => "Plain hex",
+        }
+    }
+    /// Detect the format from the first dump line of `text`.
+    ///
+    /// `od -An` output is parsed the same way as plain hex, so it's never returned.
+    pub fn detect(text: &str) -> Self {
+        let Some(line) = dump_lines(text).next() else {
+            return Self::Plain;
+        };
+        let offset_len = line
+            .find(|c: char| !c.is_ascii_hexdigit())
+            .unwrap_or(line.len());
+        let rest = &line[offset_len..];
+        if offset_len == 0 {
+            Self::Plain
+        } else if rest.starts_with("  ") {
+            Self::HexdumpC
+        } else if rest.starts_with(':') {
+            Self::Xxd
+        } else {
+            Self::Plain
+        }
+    }
+}
+
+/// Non-empty lines, without annotation comments
+fn dump_lines(text: &str) -> impl Iterator<Item = &str> {
+    text.lines()
+        .map(str::trim_end)
+        .filter(|line| !line.trim().is_empty() && !line.trim_start().starts_with('#'))
+}
+
+/// Reconstruct the dumped bytes from `text`, detecting its format.
+///
+/// Offsets of `xxd` and `hexdump -C` dumps are honored, relative to the first line.
+/// Gaps are filled with zeroes, except after a `*` line, which repeats the previous row.
+pub fn parse(text: &str) -> anyhow::Result<Vec<u8>> {
+    let format = DumpFormat::detect(text);
+    let mut out = Vec::new();
+    let mut first_offset = None;
+    let mut last_row: Vec<u8> = Vec::new();
+    let mut repeat = false;
+    for (i, line) in dump_lines(text).enumerate() {
+        let result: anyhow::Result<()> = try {
+            let (offset, hex) = match format {
+                DumpFormat::Xxd => {
+                    let (offset, rest) = line.split_once(':').context("Missing offset")?;
+                    // The hex columns end at the double space before the text column
+                    let hex = rest.trim_start().split("  ").next().unwrap_or("");
+                    (Some(offset), hex)
+                }
+                DumpFormat::HexdumpC => {
+                    if line.trim() == "*" {
+                        repeat = true;
+                        continue;
+                    }
+                    let (offset, rest) = line.split_once(' ').unwrap_or((line, ""));
+                    (Some(offset), rest.split('|').next().unwrap_or(""))
+                }
+                DumpFormat::Od | DumpFormat::Plain => {
+                    if line.trim() == "*" {
+                        bail!("Repeated lines can't be restored without offsets");
+                    }
+                    (None, line)
+                }
+            };
+            let bytes = parse_hex(hex)?;
+            if let Some(offset) = offset {
+                let offset = usize::from_str_radix(offset.trim(), 16).context("Invalid offset")?;
+                let pos = offset
+                    .checked_sub(*first_offset.get_or_insert(offset))
+                    .context("Offset is before the first line")?;
+                if pos < out.len() {
+                    bail!("Offset {offset:x} overlaps with the previous line");
+                }
+                while out.len() < pos {
+                    if repeat && !last_row.is_empty() {
+                        let n = last_row.len().min(pos - out.len());
+                        out.extend_from_slice(&last_row[..n]);
+                    } else {
+                        out.push(0);
+                    }
+                }
+                repeat = false;
+            }
+            out.extend_from_slice(&bytes);
+            if !bytes.is_empty() {
+                last_row = bytes;
+            }
+        };
+        result.with_context(|| format!("Line {}", i + 1))?;
+    }
+    Ok(out)
+}
+
+fn parse_hex(text: &str) -> anyhow::Result<Vec<u8>> {
+    let digits: Vec<u8> = text.bytes().filter(|b| !b.is_ascii_whitespace()).collect();
+    if digits.len() % 2 != 0 {
+        bail!("Odd number of hex digits");
+    }
+    digits
+        .chunks(2)
+        .map(|pair| {
+            let pair = std::str::from_utf8(pair).context("Invalid hex digit")?;
+            u8::from_str_radix(pair, 16).with_context(|| format!("Invalid hex byte '{pair}'"))
+        })
+        .collect()
+}
+
+/// Dump `data` as text. `base` is the address of the first byte.
+///
+/// `annotations` are `(address, name)` pairs, written as `#` comment lines before the row
+/// containing the address. Those lines are skipped by [`parse`].
+pub fn write(
+    format: DumpFormat,
+    data: &[u8],
+    base: usize,
+    annotations: &[(usize, String)],
+) -> String {
+    let mut annotations: Vec<_> = annotations.iter().collect();
+    annotations.sort_by_key(|(addr, _)| *addr);
+    let mut annotations = annotations.into_iter().peekable();
+    let mut out = String::new();
+    // Writing to a String can't fail, so the results are ignored
+    for (i, row) in data.chunks(ROW_LEN).enumerate() {
+        let addr = base + i * ROW_LEN;
+        while let Some((ann_addr, name)) = annotations.next_if(|(a, _)| *a < addr + row.len()) {
+            let _ = writeln!(out, "# {ann_addr:08x}: {name}");
+        }
+        match format {
+            DumpFormat::Xxd => {
+                let mut hex = String::new();
+                for (j, b) in row.iter().enumerate() {
+                    if j != 0 && j % 2 == 0 {
+                        hex.push(' ');
+                    }
+                    let _ = write!(hex, "{b:02x}");
+                }
+                let _ = writeln!(out, "{addr:08x}: {hex:<39}  {}", ascii(row));
+            }
+            DumpFormat::HexdumpC => {
+                let mut hex = String::new();
+                for (j, b) in row.iter().enumerate() {
+                    if j == 8 {
+                        hex.push(' ');
+                    }
+                    let _ = write!(hex, "{b:02x} ");
+                }
+                let _ = writeln!(out, "{addr:08x}  {hex:<49} |{}|", ascii(row));
+            }
+            DumpFormat::Od => {
+                for b in row {
+                    let _ = write!(out, " {b:02x}");
+                }
+                out.push('\n');
+            }
+            DumpFormat::Plain => {
+                for b in row {
+                    let _ = write!(out, "{b:02x}");
+                }
+                out.push('\n');
+            }
+        }
+    }
+    if format == DumpFormat::HexdumpC {
+        let _ = writeln!(out, "{:08x}", base + data.len());
+    }
+    out
+}
+
+fn ascii(row: &[u8]) -> String {
+    row.iter()
+        .map(|&b| {
+            if b == b' ' || b.is_ascii_graphic() {
+                char::from(b)
+            } else {
+                '.'
+            }
+        })
+        .collect()
+}
+
+#[test]
+#[allow(clippy::unwrap_used)]
+fn test_parse() {
+    let xxd = "00000000: 4865 6c6c 6f2c 2077 6f72 6c64 210a       Hello, world!.\n";
+    let hexdump = "00000000  48 65 6c 6c 6f 2c 20 77  6f 72 6c 64 21 0a        |Hello, world!.|\n\
+                   0000000e\n";
+    let od = " 48 65 6c 6c 6f 2c 20 77 6f 72 6c 64 21 0a\n";
+    let plain = "48656c6c6f2c20776f726c64\n210a";
+    for text in [xxd, hexdump, od, plain] {
+        assert_eq!(parse(text).unwrap(), b"Hello, world!\n");
+    }
+    assert_eq!(DumpFormat::detect(xxd), DumpFormat::Xxd);
+    assert_eq!(DumpFormat::detect(hexdump), DumpFormat::HexdumpC);
+    assert_eq!(DumpFormat::detect(od), DumpFormat::Plain);
+    // Squeezed repeated lines
+    let squeezed =
+        "00000000  00 00 00 00 00 00 00 00  00 00 00 00 00 00 00 00  |................|\n\
+                    *\n\
+                    00000030  01                                                |.|\n\
+                    00000031\n";
+    let mut expected = vec![0; 0x30];
+    expected.push(1);
+    assert_eq!(parse(squeezed).unwrap(), expected);
+    assert!(parse("4865 6").is_err());
+}
+
+#[test]
+#[allow(clippy::unwrap_used)]
+fn test_roundtrip() {
+    let data: Vec<u8> = (0..=255).cycle().take(1000).collect();
+    let annotations = [(0x1010, "Header".to_string()), (0x1100, "Body".to_string())];
+    for format in DumpFormat::ALL {
+        let text = write(format, &data, 0x1000, &annotations);
+        assert!(text.contains("# 00001010: Header\n"));
+        assert_eq!(parse(&text).unwrap(), data, "{}", format.name());
+    }
+}
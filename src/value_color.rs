@@ -1,5 +1,8 @@
 use {
-    crate::color::{rgb, RgbColor},
+    crate::{
+        color::{rgb, RgbColor},
+        heatmap::{heat_color, Heatmap},
+    },
     serde::{Deserialize, Serialize},
     serde_big_array::BigArray,
     std::path::Path,
@@ -13,6 +16,10 @@ pub enum ColorMethod {
     Vga13h,
     Grayscale,
     Custom(Box<Palette>),
+    /// Bytes that changed recently on reloads or stream updates are hot
+    RecentChanges,
+    /// Bytes that changed often on reloads or stream updates are hot
+    FrequentChanges,
 }
 
 #[derive(Debug, Serialize, Deserialize, PartialEq, Eq, Clone)]
@@ -48,6 +55,8 @@ impl ColorMethod {
                 let [r, g, b] = pal.0[byte as usize];
                 rgb(r, g, b)
             }
+            // Without a position, there is no change information to color by
+            ColorMethod::RecentChanges | ColorMethod::FrequentChanges => heat_color(0.0),
         };
         if invert {
             color.invert()
@@ -56,6 +65,39 @@ impl ColorMethod {
         }
     }
 
+    /// Color of `byte` at offset `idx`, which the change heatmap methods need.
+    ///
+    /// `now` is the [`Heatmap::now_stamp`] of the current frame.
+    #[must_use]
+    pub fn offset_color(
+        &self,
+        byte: u8,
+        idx: usize,
+        heatmap: &Heatmap,
+        now: u32,
+        invert: bool,
+    ) -> RgbColor {
+        let heat = match self {
+            ColorMethod::RecentChanges => heatmap.recency(idx, now),
+            ColorMethod::FrequentChanges => heatmap.frequency(idx),
+            _ => return self.byte_color(byte, invert),
+        };
+        let color = heat_color(heat);
+        if invert {
+            color.invert()
+        } else {
+            color
+        }
+    }
+
+    /// Whether this method colors by the change heatmap
+    pub fn is_heatmap(&self) -> bool {
+        matches!(
+            self,
+            ColorMethod::RecentChanges | ColorMethod::FrequentChanges
+        )
+    }
+
     pub(crate) fn name(&self) -> &str {
         match self {
             ColorMethod::Mono => "monochrome (white)",
@@ -64,6 +106,8 @@ impl ColorMethod {
             ColorMethod::Vga13h => "VGA 13h",
             ColorMethod::Grayscale => "grayscale",
             ColorMethod::Custom(_) => "custom",
+            ColorMethod::RecentChanges => "recent changes",
+            ColorMethod::FrequentChanges => "frequent changes",
         }
     }
 }
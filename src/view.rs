@@ -1,17 +1,23 @@
 use {
     crate::{
         app::{edit_state::EditState, presentation::Presentation},
+        bin_conv::{bin_digits_to_byte, byte_to_bin_digits},
+        char_table::CharTable,
         damage_region::DamageRegion,
         edit_buffer::EditBuffer,
         gui::message_dialog::{Icon, MessageDialog},
         hex_conv::merge_hex_halves,
-        meta::{region::Region, PerspectiveKey, PerspectiveMap, RegionMap},
+        meta::{region::Region, MetaLow, PerspectiveKey, PerspectiveMap, RegionMap},
+        num_conv::{self, Radix},
         preferences::Preferences,
+        text_conv,
+        watch_list::WatchType,
     },
     egui_sfml::sfml::graphics::Font,
     gamedebug_core::per_msg,
     serde::{Deserialize, Serialize},
     slotmap::Key,
+    std::borrow::Cow,
 };
 
 mod draw;
@@ -295,9 +301,11 @@ impl View {
     pub fn adjust_block_size(&mut self) {
         (self.col_w, self.row_h) = match &self.kind {
             ViewKind::Hex(hex) => (hex.font_size * 2 - 2, hex.font_size),
-            ViewKind::Dec(dec) => (dec.font_size * 3 - 6, dec.font_size),
+            ViewKind::Dec(dec) | ViewKind::Oct(dec) => (dec.font_size * 3 - 6, dec.font_size),
             ViewKind::Text(data) => (data.font_size, data.line_spacing.max(1)),
             ViewKind::Block => (self.col_w, self.row_h),
+            ViewKind::Bin(bin) => (bin.font_size * 8 - 26, bin.font_size),
+            ViewKind::Num(num) => (num.width() * (num.font_size - 4) + 6, num.font_size),
         }
     }
     /// Adjust state after kind was changed
@@ -307,17 +315,25 @@ impl View {
         match &mut self.kind {
             ViewKind::Hex(HexData { edit_buf, .. })
             | ViewKind::Dec(HexData { edit_buf, .. })
+            | ViewKind::Oct(HexData { edit_buf, .. })
+            | ViewKind::Bin(BinData { edit_buf, .. })
+            | ViewKind::Num(NumData { edit_buf, .. })
             | ViewKind::Text(TextData { edit_buf, .. }) => edit_buf.resize(glyph_count),
             _ => {}
         }
+        if let ViewKind::Num(num) = &self.kind {
+            self.bytes_per_block = num.bytes_per_block();
+        }
     }
     /// The number of glyphs per block this view has
     fn glyph_count(&self) -> u16 {
         match self.kind {
             ViewKind::Hex(_) => 2,
-            ViewKind::Dec(_) => 3,
+            ViewKind::Dec(_) | ViewKind::Oct(_) => 3,
             ViewKind::Text { .. } => 1,
             ViewKind::Block => 1,
+            ViewKind::Bin(_) => 8,
+            ViewKind::Num(ref num) => num.width(),
         }
     }
     pub fn handle_text_entered(
@@ -326,6 +342,7 @@ impl View {
         edit_state: &mut EditState,
         preferences: &Preferences,
         data: &mut [u8],
+        meta_low: &MetaLow,
         msg: &mut MessageDialog,
     ) {
         if self.char_valid(unicode) {
@@ -338,7 +355,7 @@ impl View {
                     if hex.edit_buf.enter_byte(unicode.to_ascii_uppercase() as u8)
                         || preferences.quick_edit
                     {
-                        self.finish_editing(edit_state, data, preferences, msg);
+                        self.finish_editing(edit_state, data, preferences, meta_low, msg);
                     }
                 }
                 ViewKind::Dec(dec) => {
@@ -349,16 +366,75 @@ impl View {
                     if dec.edit_buf.enter_byte(unicode.to_ascii_uppercase() as u8)
                         || preferences.quick_edit
                     {
-                        self.finish_editing(edit_state, data, preferences, msg);
+                        self.finish_editing(edit_state, data, preferences, meta_low, msg);
                     }
                 }
+                // Characters can be multiple bytes, so they are written straight to the data
+                // instead of going through the one glyph edit buffer
                 ViewKind::Text(text) => {
-                    if text.edit_buf.enter_byte(unicode as u8) || preferences.quick_edit {
-                        self.finish_editing(edit_state, data, preferences, msg);
+                    match text_conv::encode(&text.text_kind, unicode.encode_utf8(&mut [0; 4])) {
+                        Some(bytes) => {
+                            let cursor = edit_state.cursor;
+                            let end = cursor + bytes.len();
+                            match data.get_mut(cursor..end) {
+                                Some(dst) => {
+                                    dst.copy_from_slice(&bytes);
+                                    edit_state.widen_dirty_region(DamageRegion::Range(cursor..end));
+                                    if end < data.len() && !preferences.sticky_edit {
+                                        edit_state.set_cursor_no_history(end);
+                                    }
+                                }
+                                None => msg.open(
+                                    Icon::Error,
+                                    "Invalid value",
+                                    "The character doesn't fit before the end of the data",
+                                ),
+                            }
+                        }
+                        None => msg.open(
+                            Icon::Error,
+                            "Invalid character",
+                            format!("'{unicode}' can't be encoded as {}", text.text_kind.name()),
+                        ),
                     }
                 }
                 // Block doesn't do any text input
                 ViewKind::Block => {}
+                ViewKind::Oct(oct) => {
+                    if !oct.edit_buf.dirty {
+                        let s = format!("{:03o}", data[edit_state.cursor]);
+                        oct.edit_buf.update_from_string(&s);
+                    }
+                    if oct.edit_buf.enter_byte(unicode as u8) || preferences.quick_edit {
+                        self.finish_editing(edit_state, data, preferences, meta_low, msg);
+                    }
+                }
+                ViewKind::Bin(bin) => {
+                    if !bin.edit_buf.dirty {
+                        let digits = byte_to_bin_digits(data[edit_state.cursor], bin.lsb_first);
+                        bin.edit_buf.buf.copy_from_slice(&digits);
+                    }
+                    // Space toggles the bit under the cursor
+                    let digit = match unicode {
+                        ' ' if bin.edit_buf.buf[usize::from(bin.edit_buf.cursor)] == b'1' => b'0',
+                        ' ' => b'1',
+                        _ => unicode as u8,
+                    };
+                    if bin.edit_buf.enter_byte(digit) || preferences.quick_edit {
+                        self.finish_editing(edit_state, data, preferences, meta_low, msg);
+                    }
+                }
+                ViewKind::Num(num) => {
+                    // Typing replaces the whole value
+                    if !num.edit_buf.dirty {
+                        num.edit_buf.buf.fill(b' ');
+                        num.edit_buf.cursor = 0;
+                    }
+                    // Values can be shorter than the buffer, so quick edit doesn't apply
+                    if num.edit_buf.enter_byte(unicode as u8) {
+                        self.finish_editing(edit_state, data, preferences, meta_low, msg);
+                    }
+                }
             }
         }
     }
@@ -384,8 +460,16 @@ impl View {
         match self.kind {
             ViewKind::Hex(_) => matches!(unicode, '0'..='9' | 'a'..='f'),
             ViewKind::Dec(_) => matches!(unicode, '0'..='9'),
-            ViewKind::Text { .. } => unicode.is_ascii(),
+            ViewKind::Text(ref text) => match text.text_kind {
+                TextKind::Ascii => unicode.is_ascii(),
+                // Tables rarely have entries for control codes like the ones of Enter or Backspace
+                TextKind::Table(_) => !unicode.is_control(),
+                _ => unicode.is_ascii() || !unicode.is_control(),
+            },
             ViewKind::Block => false,
+            ViewKind::Oct(_) => matches!(unicode, '0'..='7'),
+            ViewKind::Bin(_) => matches!(unicode, '0' | '1' | ' '),
+            ViewKind::Num(ref num) => num_conv::char_valid(num.ty, num.radix, unicode),
         }
     }
 
@@ -394,8 +478,12 @@ impl View {
         edit_state: &mut EditState,
         data: &mut [u8],
         preferences: &Preferences,
+        meta_low: &MetaLow,
         msg: &mut MessageDialog,
     ) {
+        // Multi-byte values are written over the whole block the cursor is in
+        let block_start =
+            self.block_start(edit_state.cursor, &meta_low.perspectives, &meta_low.regions);
         match &mut self.kind {
             ViewKind::Hex(hex) => {
                 match merge_hex_halves(hex.edit_buf.buf[0], hex.edit_buf.buf[1]) {
@@ -415,18 +503,80 @@ impl View {
                     Err(e) => msg.open(Icon::Error, "Invalid value", e.to_string()),
                 }
             }
-            ViewKind::Text(text) => {
-                data[edit_state.cursor] = text.edit_buf.buf[0];
+            // Typed characters are written by `handle_text_entered`
+            ViewKind::Text(_) => {}
+            ViewKind::Block => {}
+            ViewKind::Oct(oct) => {
+                let s =
+                    std::str::from_utf8(&oct.edit_buf.buf).expect("Invalid utf-8 in edit buffer");
+                match u8::from_str_radix(s, 8) {
+                    Ok(num) => {
+                        data[edit_state.cursor] = num;
+                        edit_state.widen_dirty_region(DamageRegion::Single(edit_state.cursor));
+                    }
+                    Err(e) => msg.open(Icon::Error, "Invalid value", e.to_string()),
+                }
+            }
+            ViewKind::Bin(bin) => {
+                match bin_digits_to_byte(&bin.edit_buf.buf, bin.lsb_first) {
+                    Some(byte) => data[edit_state.cursor] = byte,
+                    None => per_msg!("finish_editing: Failed to parse bits"),
+                }
                 edit_state.widen_dirty_region(DamageRegion::Single(edit_state.cursor));
             }
-            ViewKind::Block => {}
+            // Enter without typing anything leaves the value alone
+            ViewKind::Num(num) if num.edit_buf.dirty => {
+                let s =
+                    std::str::from_utf8(&num.edit_buf.buf).expect("Invalid utf-8 in edit buffer");
+                let cursor = block_start;
+                match num_conv::parse(num.ty, s, num.big_endian, num.radix) {
+                    Ok(bytes) => match data.get_mut(cursor..cursor + bytes.len()) {
+                        Some(dst) => {
+                            dst.copy_from_slice(&bytes);
+                            edit_state.widen_dirty_region(DamageRegion::Range(
+                                cursor..cursor + bytes.len(),
+                            ));
+                        }
+                        None => msg.open(
+                            Icon::Error,
+                            "Invalid value",
+                            "The value doesn't fit before the end of the data",
+                        ),
+                    },
+                    Err(e) => msg.open(Icon::Error, "Invalid value", e.to_string()),
+                }
+            }
+            ViewKind::Num(_) => {}
         }
-        if edit_state.cursor + 1 < data.len() && !preferences.sticky_edit {
-            edit_state.step_cursor_forward()
+        // Typed values span their whole block
+        let (from, step) = match &self.kind {
+            ViewKind::Num(num) => (block_start, num.ty.size()),
+            _ => (edit_state.cursor, 1),
+        };
+        if from + step < data.len() && !preferences.sticky_edit {
+            edit_state.set_cursor_no_history(from + step);
         }
         self.reset_edit_buf();
     }
 
+    /// The offset of the block `offset` is in. Blocks are counted from the start of the region.
+    pub fn block_start(
+        &self,
+        offset: usize,
+        perspectives: &PerspectiveMap,
+        regions: &RegionMap,
+    ) -> usize {
+        let size = usize::from(self.bytes_per_block);
+        if size <= 1 || self.perspective.is_null() {
+            return offset;
+        }
+        let begin = regions[perspectives[self.perspective].region].region.begin;
+        if offset < begin {
+            return offset;
+        }
+        begin + (offset - begin) / size * size
+    }
+
     pub fn cancel_editing(&mut self) {
         self.reset_edit_buf();
     }
@@ -445,8 +595,12 @@ impl View {
 
     pub(crate) fn edit_buffer_mut(&mut self) -> Option<&mut EditBuffer> {
         match &mut self.kind {
-            ViewKind::Hex(data) | ViewKind::Dec(data) => Some(&mut data.edit_buf),
+            ViewKind::Hex(data) | ViewKind::Dec(data) | ViewKind::Oct(data) => {
+                Some(&mut data.edit_buf)
+            }
             ViewKind::Text(data) => Some(&mut data.edit_buf),
+            ViewKind::Bin(data) => Some(&mut data.edit_buf),
+            ViewKind::Num(data) => Some(&mut data.edit_buf),
             ViewKind::Block => None,
         }
     }
@@ -626,6 +780,9 @@ pub enum ViewKind {
     Dec(HexData),
     Text(TextData),
     Block,
+    Bin(BinData),
+    Oct(HexData),
+    Num(NumData),
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -672,6 +829,86 @@ impl Default for HexData {
     }
 }
 
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct BinData {
+    #[serde(skip)]
+    pub edit_buf: EditBuffer,
+    pub font_size: u16,
+    /// Show the least significant bit first, instead of the most significant
+    pub lsb_first: bool,
+}
+
+impl PartialEq for BinData {
+    fn eq(&self, other: &Self) -> bool {
+        self.font_size == other.font_size && self.lsb_first == other.lsb_first
+    }
+}
+
+impl Eq for BinData {}
+
+impl Default for BinData {
+    fn default() -> Self {
+        Self {
+            edit_buf: Default::default(),
+            font_size: 14,
+            lsb_first: false,
+        }
+    }
+}
+
+/// A typed numeric value for every block
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct NumData {
+    #[serde(skip)]
+    pub edit_buf: EditBuffer,
+    pub font_size: u16,
+    pub ty: WatchType,
+    pub big_endian: bool,
+    /// Radix of integers. Floats are always decimal.
+    pub radix: Radix,
+}
+
+impl PartialEq for NumData {
+    fn eq(&self, other: &Self) -> bool {
+        self.font_size == other.font_size
+            && self.ty == other.ty
+            && self.big_endian == other.big_endian
+            && self.radix == other.radix
+    }
+}
+
+impl Eq for NumData {}
+
+impl Default for NumData {
+    fn default() -> Self {
+        Self {
+            edit_buf: Default::default(),
+            font_size: 14,
+            ty: WatchType::U32,
+            big_endian: false,
+            radix: Radix::Dec,
+        }
+    }
+}
+
+impl NumData {
+    /// Number of glyphs a value takes
+    #[expect(
+        clippy::cast_possible_truncation,
+        reason = "Values are at most 64 binary digits"
+    )]
+    pub fn width(&self) -> u16 {
+        num_conv::width(self.ty, self.radix) as u16
+    }
+    #[expect(
+        clippy::cast_possible_truncation,
+        reason = "Values are at most 8 bytes"
+    )]
+    pub fn bytes_per_block(&self) -> u8 {
+        self.ty.size() as u8
+    }
+}
+
 impl TextData {
     #[expect(
         clippy::cast_possible_truncation,
@@ -688,27 +925,63 @@ impl TextData {
     }
 }
 
-#[derive(Debug, PartialEq, Eq, Serialize, Deserialize, Clone)]
+#[derive(Debug, PartialEq, Eq, Serialize, Deserialize, Clone, Default)]
 pub enum TextKind {
+    #[default]
     Ascii,
     Utf16Le,
     Utf16Be,
+    Utf8,
+    /// The original IBM PC code page
+    Cp437,
+    Windows1252,
+    /// ISO-8859 with the part number, like 1 for Latin-1
+    Iso8859(u8),
+    /// EBCDIC code page 037
+    Ebcdic,
+    /// Only offered for searching, not in [`TextKind::all`], because the font has no kana
+    /// or kanji glyphs to show it in text views
+    ShiftJis,
+    /// A custom table loaded from a `.tbl` file
+    Table(CharTable),
 }
 
 impl TextKind {
-    pub fn name(&self) -> &'static str {
+    pub fn name(&self) -> Cow<'static, str> {
         match self {
-            TextKind::Ascii => "ascii",
-            TextKind::Utf16Le => "utf-16 le",
-            TextKind::Utf16Be => "utf-16 be",
+            TextKind::Ascii => "ascii".into(),
+            TextKind::Utf16Le => "utf-16 le".into(),
+            TextKind::Utf16Be => "utf-16 be".into(),
+            TextKind::Utf8 => "utf-8".into(),
+            TextKind::Cp437 => "cp437".into(),
+            TextKind::Windows1252 => "windows-1252".into(),
+            TextKind::Iso8859(part) => format!("iso-8859-{part}").into(),
+            TextKind::Ebcdic => "ebcdic (cp037)".into(),
+            TextKind::ShiftJis => "shift-jis".into(),
+            TextKind::Table(table) => format!("table ({})", table.name).into(),
         }
     }
 
+    /// All the built-in text kinds. Tables are loaded from files instead.
+    pub fn all() -> impl Iterator<Item = TextKind> {
+        [
+            TextKind::Ascii,
+            TextKind::Utf8,
+            TextKind::Utf16Le,
+            TextKind::Utf16Be,
+            TextKind::Cp437,
+            TextKind::Windows1252,
+        ]
+        .into_iter()
+        .chain(text_conv::ISO_8859_PARTS.map(TextKind::Iso8859))
+        .chain([TextKind::Ebcdic])
+    }
+
     pub(crate) fn bytes_needed(&self) -> u8 {
         match self {
-            TextKind::Ascii => 1,
-            TextKind::Utf16Le => 2,
-            TextKind::Utf16Be => 2,
+            TextKind::Utf16Le | TextKind::Utf16Be => 2,
+            // Multi-byte characters of the other kinds span several blocks
+            _ => 1,
         }
     }
 }
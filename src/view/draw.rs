@@ -2,12 +2,17 @@ use {
     super::View,
     crate::{
         app::{presentation::Presentation, App},
+        bin_conv,
         color::RgbColor,
         dec_conv,
+        edit_buffer::EditBuffer,
         gui::Gui,
+        heatmap::Heatmap,
         hex_conv,
         meta::{region::Region, PerspectiveMap, RegionMap, ViewKey},
-        view::ViewKind,
+        num_conv, oct_conv,
+        text_conv::{self, Decoded},
+        view::{TextKind, ViewKind},
     },
     egui_sfml::sfml::{
         graphics::{
@@ -21,11 +26,22 @@ use {
     slotmap::Key,
 };
 
+/// Background of bytes that differ from the other file in compare mode, or between the
+/// compared regions
+const COMPARE_DIFF_COLOR: Color = Color::rgb(110, 30, 30);
+
+#[expect(
+    clippy::too_many_arguments,
+    reason = "The parts of the app state are passed separately to allow borrowing them in drawfn"
+)]
 pub fn draw_view(
     view: &View,
     app_perspectives: &PerspectiveMap,
     app_regions: &RegionMap,
     app_data: &[u8],
+    sector_grid: Option<usize>,
+    hard_seek: usize,
+    heatmap: &Heatmap,
     vertex_buffer: &mut Vec<Vertex>,
     mut drawfn: impl FnMut(&mut Vec<Vertex>, f32, f32, &[u8], usize, RgbColor),
 ) {
@@ -35,6 +51,7 @@ pub fn draw_view(
     }
     let perspective = &app_perspectives[view.perspective];
     let region = &app_regions[perspective.region].region;
+    let heat_now = heatmap.now_stamp();
     let mut idx = region.begin;
     let start_row: usize = view.scroll_offset.row;
     idx += start_row * (perspective.cols * usize::from(view.bytes_per_block));
@@ -76,10 +93,13 @@ pub fn draw_view(
             }
             match app_data.get(idx..idx + view.bytes_per_block as usize) {
                 Some(data) => {
-                    let c = view
-                        .presentation
-                        .color_method
-                        .byte_color(data[0], view.presentation.invert_color);
+                    let c = view.presentation.color_method.offset_color(
+                        data[0],
+                        idx,
+                        heatmap,
+                        heat_now,
+                        view.presentation.invert_color,
+                    );
                     #[expect(
                         clippy::cast_precision_loss,
                         reason = "At this point, the viewport coordinates should be small enough to fit in viewport"
@@ -92,6 +112,21 @@ pub fn draw_view(
                         idx,
                         c,
                     );
+                    if let Some(sector) = sector_grid {
+                        #[expect(
+                            clippy::cast_precision_loss,
+                            reason = "At this point, the viewport coordinates should be small enough to fit in viewport"
+                        )]
+                        draw_sector_edges(
+                            vertex_buffer,
+                            (viewport_x as f32, viewport_y as f32),
+                            view,
+                            idx + hard_seek,
+                            col == 0,
+                            perspective.cols * usize::from(view.bytes_per_block),
+                            sector,
+                        );
+                    }
                     /*if gamedebug_core::enabled() {
                         #[expect(
                             clippy::cast_precision_loss,
@@ -119,6 +154,28 @@ pub fn draw_view(
     }
 }
 
+/// Mark the edges of the cell at file position `pos` that lie on a sector boundary
+fn draw_sector_edges(
+    vertex_buffer: &mut Vec<Vertex>,
+    (x, y): (f32, f32),
+    view: &View,
+    pos: usize,
+    first_col: bool,
+    row_bytes: usize,
+    sector: usize,
+) {
+    let color = Color::rgb(150, 150, 190);
+    let sector = sector.max(1);
+    // The byte above is in a different sector
+    if pos >= row_bytes && pos / sector != (pos - row_bytes) / sector {
+        draw_rect(vertex_buffer, x, y, view.col_w.into(), 1.0, color);
+    }
+    // A sector starts in the middle of the row
+    if pos % sector == 0 && !first_col {
+        draw_rect(vertex_buffer, x, y, 1.0, view.row_h.into(), color);
+    }
+}
+
 fn draw_text_cursor(
     x: f32,
     y: f32,
@@ -316,13 +373,87 @@ impl View {
         let this = &app.meta_state.meta.views[key];
         match &this.view.kind {
             ViewKind::Hex(hex) => {
+                draw_digits_view(
+                    key,
+                    app,
+                    gui,
+                    vertex_buffer,
+                    font,
+                    hex.font_size,
+                    &hex.edit_buf,
+                    |data| hex_conv::byte_to_hex_digits(data[0]),
+                );
+                rs.set_texture(Some(font.texture(hex.font_size.into())));
+            }
+            ViewKind::Dec(dec) => {
+                draw_digits_view(
+                    key,
+                    app,
+                    gui,
+                    vertex_buffer,
+                    font,
+                    dec.font_size,
+                    &dec.edit_buf,
+                    |data| dec_conv::byte_to_dec_digits(data[0]),
+                );
+                rs.set_texture(Some(font.texture(dec.font_size.into())));
+            }
+            ViewKind::Oct(oct) => {
+                draw_digits_view(
+                    key,
+                    app,
+                    gui,
+                    vertex_buffer,
+                    font,
+                    oct.font_size,
+                    &oct.edit_buf,
+                    |data| oct_conv::byte_to_oct_digits(data[0]),
+                );
+                rs.set_texture(Some(font.texture(oct.font_size.into())));
+            }
+            ViewKind::Bin(bin) => {
+                draw_digits_view(
+                    key,
+                    app,
+                    gui,
+                    vertex_buffer,
+                    font,
+                    bin.font_size,
+                    &bin.edit_buf,
+                    |data| bin_conv::byte_to_bin_digits(data[0], bin.lsb_first),
+                );
+                rs.set_texture(Some(font.texture(bin.font_size.into())));
+            }
+            ViewKind::Num(num) => {
+                draw_digits_view(
+                    key,
+                    app,
+                    gui,
+                    vertex_buffer,
+                    font,
+                    num.font_size,
+                    &num.edit_buf,
+                    |data| {
+                        num_conv::format(num.ty, data, num.big_endian, num.radix)
+                            .unwrap_or_default()
+                            .into_bytes()
+                    },
+                );
+                rs.set_texture(Some(font.texture(num.font_size.into())));
+            }
+            ViewKind::Text(text) => {
+                // Multi-byte characters need the bytes around the block to decode
+                let view_data = app.view_data(key);
                 draw_view(
                     &this.view,
                     &app.meta_state.meta.low.perspectives,
                     &app.meta_state.meta.low.regions,
-                    &app.data,
+                    view_data,
+                    app.hex_ui.sector_grid,
+                    app.args.src.hard_seek.unwrap_or(0),
+                    &app.heatmap,
                     vertex_buffer,
-                    |vertex_buffer, x, y, data, idx, c| {
+                    |vertex_buffer, x, y, _data, idx, c| {
                         if selected_or_find_result_contains(app.hex_ui.selection(), idx, gui) {
                             draw_rect(
                                 vertex_buffer,
@@ -332,140 +463,52 @@ impl View {
                                 f32::from(this.view.row_h),
                                 this.view.presentation.sel_color.into(),
                             )
-                        }
-                        let mut gx = x;
-                        for (i, mut d) in hex_conv::byte_to_hex_digits(data[0])
-                            .into_iter()
-                            .enumerate()
+                        } else if app.compare_differs(idx)
+                            || gui.region_compare_window.differs(&app.data, idx)
                         {
-                            if idx == app.edit_state.cursor && hex.edit_buf.dirty {
-                                d = hex.edit_buf.buf[i];
-                            }
-                            draw_glyph(
-                                font,
-                                hex.font_size.into(),
+                            draw_rect(
                                 vertex_buffer,
-                                gx,
-                                y,
-                                d.into(),
-                                c.into(),
-                            );
-                            gx += f32::from(hex.font_size - 4);
-                        }
-                        let extra_x = hex.edit_buf.cursor * (hex.font_size - 4);
-                        if idx == app.edit_state.cursor {
-                            draw_text_cursor(
-                                x + f32::from(extra_x),
+                                x,
                                 y,
-                                vertex_buffer,
-                                app.hex_ui.focused_view == Some(key),
-                                app.hex_ui.cursor_flash_timer(),
-                                &this.view.presentation,
-                                hex.font_size,
-                            );
-                        }
-                    },
-                );
-                rs.set_texture(Some(font.texture(hex.font_size.into())));
-            }
-            ViewKind::Dec(dec) => {
-                draw_view(
-                    &this.view,
-                    &app.meta_state.meta.low.perspectives,
-                    &app.meta_state.meta.low.regions,
-                    &app.data,
-                    vertex_buffer,
-                    |vertex_buffer, x, y, data, idx, c| {
-                        if selected_or_find_result_contains(app.hex_ui.selection(), idx, gui) {
+                                f32::from(this.view.col_w),
+                                f32::from(this.view.row_h),
+                                COMPARE_DIFF_COLOR,
+                            )
+                        } else if gui.file_diff_result_window.overlay_contains(idx) {
                             draw_rect(
                                 vertex_buffer,
                                 x,
                                 y,
                                 f32::from(this.view.col_w),
                                 f32::from(this.view.row_h),
-                                this.view.presentation.sel_color.into(),
+                                diff_overlay_color(gui),
                             )
                         }
-                        let mut gx = x;
-                        for (i, mut d) in dec_conv::byte_to_dec_digits(data[0])
-                            .into_iter()
-                            .enumerate()
-                        {
-                            if idx == app.edit_state.cursor && dec.edit_buf.dirty {
-                                d = dec.edit_buf.buf[i];
+                        let glyph = match text_conv::decode_at(&text.text_kind, view_data, idx) {
+                            Decoded::Char('\0') => Some('∅'),
+                            Decoded::Char('\t') => Some('⇥'),
+                            Decoded::Char('\n') => Some('⏎'),
+                            Decoded::Char('\r') => Some('⇤'),
+                            Decoded::Char(' ') => Some('␣'),
+                            Decoded::Char('\u{FF}') if text.text_kind == TextKind::Ascii => {
+                                Some('■')
                             }
+                            Decoded::Char(ch) => Some(ch),
+                            // The character is drawn at its first byte
+                            Decoded::Continuation => None,
+                            Decoded::Invalid => Some('�'),
+                        };
+                        if let Some(glyph) = glyph {
                             draw_glyph(
                                 font,
-                                dec.font_size.into(),
+                                text.font_size.into(),
                                 vertex_buffer,
-                                gx,
+                                x,
                                 y,
-                                d.into(),
+                                glyph as u32,
                                 c.into(),
                             );
-                            gx += f32::from(dec.font_size - 4);
-                        }
-                        let extra_x = dec.edit_buf.cursor * (dec.font_size - 4);
-                        if idx == app.edit_state.cursor {
-                            draw_text_cursor(
-                                x + f32::from(extra_x),
-                                y,
-                                vertex_buffer,
-                                app.hex_ui.focused_view == Some(key),
-                                app.hex_ui.cursor_flash_timer(),
-                                &this.view.presentation,
-                                dec.font_size,
-                            );
-                        }
-                    },
-                );
-                rs.set_texture(Some(font.texture(dec.font_size.into())));
-            }
-            ViewKind::Text(text) => {
-                draw_view(
-                    &this.view,
-                    &app.meta_state.meta.low.perspectives,
-                    &app.meta_state.meta.low.regions,
-                    &app.data,
-                    vertex_buffer,
-                    |vertex_buffer, x, y, data, idx, c| {
-                        if selected_or_find_result_contains(app.hex_ui.selection(), idx, gui) {
-                            draw_rect(
-                                vertex_buffer,
-                                x,
-                                y,
-                                f32::from(this.view.col_w),
-                                f32::from(this.view.row_h),
-                                this.view.presentation.sel_color.into(),
-                            )
                         }
-                        let raw_data = match text.text_kind {
-                            crate::view::TextKind::Ascii => u32::from(data[0]),
-                            crate::view::TextKind::Utf16Le => {
-                                u32::from(u16::from_le_bytes([data[0], data[1]]))
-                            }
-                            crate::view::TextKind::Utf16Be => {
-                                u32::from(u16::from_be_bytes([data[0], data[1]]))
-                            }
-                        };
-                        let glyph = match raw_data {
-                            0x00 => '∅' as u32,
-                            0x09 => '⇥' as u32,
-                            0x0A => '⏎' as u32,
-                            0x0D => '⇤' as u32,
-                            0x20 => '␣' as u32,
-                            0xFF => '■' as u32,
-                            _ => raw_data,
-                        };
-                        draw_glyph(
-                            font,
-                            text.font_size.into(),
-                            vertex_buffer,
-                            x,
-                            y,
-                            glyph,
-                            c.into(),
-                        );
                         if idx == app.edit_state.cursor {
                             draw_text_cursor(
                                 x,
@@ -486,7 +529,10 @@ impl View {
                     &this.view,
                     &app.meta_state.meta.low.perspectives,
                     &app.meta_state.meta.low.regions,
-                    &app.data,
+                    app.view_data(key),
+                    app.hex_ui.sector_grid,
+                    app.args.src.hard_seek.unwrap_or(0),
+                    &app.heatmap,
                     vertex_buffer,
                     |vertex_buffer, x, y, _byte, idx, mut c| {
                         if selected_or_find_result_contains(app.hex_ui.selection(), idx, gui) {
@@ -500,6 +546,30 @@ impl View {
                             f32::from(this.view.row_h),
                             c.into(),
                         );
+                        if app.compare_differs(idx)
+                            || gui.region_compare_window.differs(&app.data, idx)
+                        {
+                            draw_rect_outline(
+                                vertex_buffer,
+                                x,
+                                y,
+                                f32::from(this.view.col_w),
+                                f32::from(this.view.row_h),
+                                COMPARE_DIFF_COLOR,
+                                -1.0,
+                            );
+                        }
+                        if gui.file_diff_result_window.overlay_contains(idx) {
+                            draw_rect_outline(
+                                vertex_buffer,
+                                x,
+                                y,
+                                f32::from(this.view.col_w),
+                                f32::from(this.view.row_h),
+                                diff_overlay_color(gui),
+                                -1.0,
+                            );
+                        }
                         if idx == app.edit_state.cursor {
                             draw_block_cursor(
                                 x,
@@ -603,6 +673,102 @@ fn selected_or_find_result_contains(
     selected(app_selection, idx) || find_result_contains(app_ui, idx)
 }
 
+/// Draw a view that shows each block as a fixed number of digit glyphs, like hex or decimal
+#[expect(
+    clippy::too_many_arguments,
+    reason = "The font size and edit buffer come from different view kinds"
+)]
+fn draw_digits_view<I: IntoIterator<Item = u8>>(
+    key: ViewKey,
+    app: &App,
+    gui: &Gui,
+    vertex_buffer: &mut Vec<Vertex>,
+    font: &Font,
+    font_size: u16,
+    edit_buf: &EditBuffer,
+    digits: impl Fn(&[u8]) -> I,
+) {
+    let this = &app.meta_state.meta.views[key];
+    draw_view(
+        &this.view,
+        &app.meta_state.meta.low.perspectives,
+        &app.meta_state.meta.low.regions,
+        app.view_data(key),
+        app.hex_ui.sector_grid,
+        app.args.src.hard_seek.unwrap_or(0),
+        &app.heatmap,
+        vertex_buffer,
+        |vertex_buffer, x, y, data, idx, c| {
+            if selected_or_find_result_contains(app.hex_ui.selection(), idx, gui) {
+                draw_rect(
+                    vertex_buffer,
+                    x,
+                    y,
+                    f32::from(this.view.col_w),
+                    f32::from(this.view.row_h),
+                    this.view.presentation.sel_color.into(),
+                )
+            } else if app.compare_differs(idx) || gui.region_compare_window.differs(&app.data, idx)
+            {
+                draw_rect(
+                    vertex_buffer,
+                    x,
+                    y,
+                    f32::from(this.view.col_w),
+                    f32::from(this.view.row_h),
+                    COMPARE_DIFF_COLOR,
+                )
+            } else if gui.file_diff_result_window.overlay_contains(idx) {
+                draw_rect(
+                    vertex_buffer,
+                    x,
+                    y,
+                    f32::from(this.view.col_w),
+                    f32::from(this.view.row_h),
+                    diff_overlay_color(gui),
+                )
+            }
+            let cursor_in_block = (idx..idx + data.len()).contains(&app.edit_state.cursor);
+            let mut gx = x;
+            for (i, mut d) in digits(data).into_iter().enumerate() {
+                if cursor_in_block
+                    && edit_buf.dirty
+                    && let Some(&edited) = edit_buf.buf.get(i)
+                {
+                    d = edited;
+                }
+                draw_glyph(
+                    font,
+                    font_size.into(),
+                    vertex_buffer,
+                    gx,
+                    y,
+                    d.into(),
+                    c.into(),
+                );
+                gx += f32::from(font_size - 4);
+            }
+            let extra_x = edit_buf.cursor * (font_size - 4);
+            if cursor_in_block {
+                draw_text_cursor(
+                    x + f32::from(extra_x),
+                    y,
+                    vertex_buffer,
+                    app.hex_ui.focused_view == Some(key),
+                    app.hex_ui.cursor_flash_timer(),
+                    &this.view.presentation,
+                    font_size,
+                );
+            }
+        },
+    );
+}
+
+fn diff_overlay_color(app_ui: &Gui) -> Color {
+    let [r, g, b] = app_ui.file_diff_result_window.overlay_color;
+    Color::rgb(r, g, b)
+}
+
 fn find_result_contains(app_ui: &Gui, idx: usize) -> bool {
     app_ui.find_dialog.open.is() && app_ui.find_dialog.results_set.contains(&idx)
 }
@@ -0,0 +1,254 @@
+use {
+    crate::proc_mem::ProcMem,
+    serde::{Deserialize, Serialize},
+    std::time::Instant,
+};
+
+/// Typed values at addresses of a running process, which are refreshed periodically.
+///
+/// Frozen entries get their value written back on every refresh.
+pub struct WatchList {
+    pub entries: Vec<WatchEntry>,
+    /// The process the entries belong to
+    pid: Option<sysinfo::Pid>,
+    mem: Option<ProcMem>,
+    /// How often the values are refreshed (and frozen values written back)
+    pub interval_ms: u32,
+    last_update: Instant,
+    /// Error that occurred during the last refresh
+    pub last_error: Option<String>,
+}
+
+impl Default for WatchList {
+    fn default() -> Self {
+        Self {
+            entries: Vec::new(),
+            pid: None,
+            mem: None,
+            interval_ms: 100,
+            last_update: Instant::now(),
+            last_error: None,
+        }
+    }
+}
+
+pub struct WatchEntry {
+    pub label: String,
+    pub addr: usize,
+    pub ty: WatchType,
+    /// The value read during the last refresh, if reading succeeded
+    pub value: Option<Vec<u8>>,
+    /// If set, this value is written back to the process on every refresh
+    pub frozen: Option<Vec<u8>>,
+}
+
+impl WatchList {
+    pub fn pid(&self) -> Option<sysinfo::Pid> {
+        self.pid
+    }
+    /// Add a new entry. Entries belonging to a different process are discarded.
+    pub fn add(&mut self, pid: sysinfo::Pid, label: String, addr: usize, ty: WatchType) {
+        if self.pid != Some(pid) {
+            self.entries.clear();
+            self.mem = None;
+            self.pid = Some(pid);
+        }
+        self.entries.push(WatchEntry {
+            label,
+            addr,
+            ty,
+            value: None,
+            frozen: None,
+        });
+    }
+    /// Write `value` to the address of the entry at `idx` once
+    pub fn write(&mut self, idx: usize, value: &[u8]) -> anyhow::Result<()> {
+        let entry = &mut self.entries[idx];
+        self.mem
+            .as_ref()
+            .ok_or_else(|| anyhow::anyhow!("Process memory is not open"))?
+            .write_at(entry.addr, value)?;
+        entry.value = Some(value.to_vec());
+        Ok(())
+    }
+    /// Refresh the values and write back frozen ones, if the refresh interval has elapsed
+    pub fn update(&mut self) {
+        if self.entries.is_empty()
+            || self.last_update.elapsed().as_millis() < u128::from(self.interval_ms)
+        {
+            return;
+        }
+        self.last_update = Instant::now();
+        let Some(pid) = self.pid else {
+            return;
+        };
+        if self.mem.is_none() {
+            match ProcMem::open(pid) {
+                Ok(mem) => self.mem = Some(mem),
+                Err(e) => {
+                    self.last_error = Some(e.to_string());
+                    return;
+                }
+            }
+        }
+        let Some(mem) = &self.mem else {
+            return;
+        };
+        self.last_error = None;
+        for entry in &mut self.entries {
+            if let Some(frozen) = &entry.frozen
+                && let Err(e) = mem.write_at(entry.addr, frozen)
+            {
+                self.last_error = Some(format!("{}: {e}", entry.label));
+            }
+            let mut buf = vec![0; entry.ty.size()];
+            entry.value = mem.read_at(entry.addr, &mut buf).ok().map(|()| buf);
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Serialize, Deserialize)]
+pub enum WatchType {
+    U8,
+    I8,
+    U16,
+    I16,
+    U32,
+    I32,
+    U64,
+    I64,
+    F32,
+    F64,
+}
+
+/// Evaluate `$body` with `$t` being the primitive type corresponding to `$ty`
+macro_rules! with_prim {
+    ($ty:expr, $t:ident => $body:expr) => {
+        match $ty {
+            WatchType::U8 => {
+                type $t = u8;
+                $body
+            }
+            WatchType::I8 => {
+                type $t = i8;
+                $body
+            }
+            WatchType::U16 => {
+                type $t = u16;
+                $body
+            }
+            WatchType::I16 => {
+                type $t = i16;
+                $body
+            }
+            WatchType::U32 => {
+                type $t = u32;
+                $body
+            }
+            WatchType::I32 => {
+                type $t = i32;
+                $body
+            }
+            WatchType::U64 => {
+                type $t = u64;
+                $body
+            }
+            WatchType::I64 => {
+                type $t = i64;
+                $body
+            }
+            WatchType::F32 => {
+                type $t = f32;
+                $body
+            }
+            WatchType::F64 => {
+                type $t = f64;
+                $body
+            }
+        }
+    };
+}
+
+impl WatchType {
+    pub const ALL: [Self; 10] = [
+        Self::U8,
+        Self::I8,
+        Self::U16,
+        Self::I16,
+        Self::U32,
+        Self::I32,
+        Self::U64,
+        Self::I64,
+        Self::F32,
+        Self::F64,
+    ];
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::U8 => "u8",
+            Self::I8 => "i8",
+            Self::U16 => "u16",
+            Self::I16 => "i16",
+            Self::U32 => "u32",
+            Self::I32 => "i32",
+            Self::U64 => "u64",
+            Self::I64 => "i64",
+            Self::F32 => "f32",
+            Self::F64 => "f64",
+        }
+    }
+    pub fn size(self) -> usize {
+        with_prim!(self, T => std::mem::size_of::<T>())
+    }
+    /// Format little endian `bytes` as a value of this type
+    pub fn format(self, bytes: &[u8]) -> Option<String> {
+        with_prim!(self, T => Some(T::from_le_bytes(bytes.try_into().ok()?).to_string()))
+    }
+    /// Read `bytes` as a value of this type, converted to `f64` for comparing with other values
+    pub fn to_f64(self, bytes: &[u8], big_endian: bool) -> Option<f64> {
+        macro_rules! read {
+            ($t:ty) => {{
+                let bytes = bytes.try_into().ok()?;
+                if big_endian {
+                    <$t>::from_be_bytes(bytes)
+                } else {
+                    <$t>::from_le_bytes(bytes)
+                }
+            }};
+        }
+        #[expect(
+            clippy::cast_precision_loss,
+            reason = "Only used for comparisons, 64 bit values this large are rare"
+        )]
+        let value = match self {
+            Self::U8 => f64::from(read!(u8)),
+            Self::I8 => f64::from(read!(i8)),
+            Self::U16 => f64::from(read!(u16)),
+            Self::I16 => f64::from(read!(i16)),
+            Self::U32 => f64::from(read!(u32)),
+            Self::I32 => f64::from(read!(i32)),
+            Self::U64 => read!(u64) as f64,
+            Self::I64 => read!(i64) as f64,
+            Self::F32 => f64::from(read!(f32)),
+            Self::F64 => read!(f64),
+        };
+        Some(value)
+    }
+    /// Parse `s` as a value of this type, and return its little endian bytes
+    pub fn parse(self, s: &str) -> anyhow::Result<Vec<u8>> {
+        with_prim!(self, T => Ok(s.trim().parse::<T>()?.to_le_bytes().to_vec()))
+    }
+}
+
+#[test]
+#[allow(clippy::unwrap_used)]
+fn test_format_parse() {
+    assert_eq!(WatchType::I16.parse("-2").unwrap(), [0xFE, 0xFF]);
+    assert_eq!(WatchType::U32.parse(" 258 ").unwrap(), [2, 1, 0, 0]);
+    assert!(WatchType::U8.parse("256").is_err());
+    assert_eq!(WatchType::I16.format(&[0xFE, 0xFF]).as_deref(), Some("-2"));
+    assert_eq!(
+        WatchType::F32.format(&1.5f32.to_le_bytes()).as_deref(),
+        Some("1.5")
+    );
+    assert_eq!(WatchType::U32.format(&[1, 2]), None);
+}
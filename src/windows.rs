@@ -25,18 +25,21 @@ pub fn load_proc_memory(
         if handle == 0 {
             bail!("Failed to open process.");
         }
-        load_proc_memory_inner(app, handle, start, size, font)
+        load_proc_memory_inner(app, handle, pid, start, size, font)
     }
 }
 
 unsafe fn load_proc_memory_inner(
     app: &mut App,
     handle: windows_sys::Win32::Foundation::HANDLE,
+    pid: sysinfo::Pid,
     start: usize,
     size: usize,
     font: &Font,
 ) -> anyhow::Result<()> {
-    read_proc_memory(handle, &mut app.data, start, size)?;
+    let mut data = Vec::new();
+    read_proc_memory(handle, &mut data, start, size)?;
+    app.data = data.into();
     app.source = Some(Source {
         attr: SourceAttributes {
             permissions: SourcePermissions {
@@ -48,12 +51,13 @@ unsafe fn load_proc_memory_inner(
         },
         provider: SourceProvider::WinProc {
             handle,
+            pid,
             start,
             size,
         },
         state: SourceState::default(),
     });
-    if !app.preferences.keep_meta {
+    if app.should_readjust_meta() {
         app.new_file_readjust(font);
     }
     Ok(())